@@ -1,20 +1,267 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 use anyhow::{bail, Result};
+use regex::Regex;
 
+use crate::error::{CodedError, ErrorCode};
+
+/// Parses `旧名=统一名` entries. Names may contain `=` written as `\=`
+/// (and a literal backslash as `\\`); any other character, including
+/// look-alikes such as `＝`, is taken literally.
 pub fn parse_aliases(raw: &[String]) -> Result<HashMap<String, String>> {
     let mut map = HashMap::new();
     for entry in raw {
-        let parts: Vec<_> = entry.splitn(2, '=').collect();
-        if parts.len() != 2 {
-            bail!("别名参数格式应为 旧名=统一名，当前为：{entry}");
+        let (from, to) = parse_entry(entry)
+            .map_err(|message| CodedError::new(ErrorCode::BadAlias, format!("别名参数{message}")))?;
+        if let Some(message) = conflict(&map, &from, &to) {
+            bail!(CodedError::new(ErrorCode::BadAlias, format!("别名参数冲突：{message}")));
+        }
+        map.insert(from, to);
+    }
+    Ok(map)
+}
+
+/// Reads `--alias-file`: one `旧名=统一名` entry per line, in the same
+/// syntax as `--alias`; blank lines and lines starting with `#` are skipped.
+pub fn parse_alias_file(path: &Path) -> Result<HashMap<String, String>> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| {
+            CodedError::new(ErrorCode::BadAlias, format!("无法读取别名文件：{err}")).with_path(path)
+        })?;
+    let mut map = HashMap::new();
+    for (idx, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
-        let from = parts[0].trim();
-        let to = parts[1].trim();
-        if from.is_empty() || to.is_empty() {
-            bail!("别名参数不能为空：{entry}");
+        let (from, to) = parse_entry(line).map_err(|message| {
+            CodedError::new(
+                ErrorCode::BadAlias,
+                format!("别名文件 {} 第 {} 行{message}", path.display(), idx + 1),
+            )
+            .with_path(path)
+        })?;
+        if let Some(message) = conflict(&map, &from, &to) {
+            bail!(CodedError::new(
+                ErrorCode::BadAlias,
+                format!("别名文件 {} 第 {} 行冲突：{message}", path.display(), idx + 1),
+            )
+            .with_path(path));
         }
-        map.insert(from.to_string(), to.to_string());
+        map.insert(from, to);
     }
     Ok(map)
 }
+
+/// `--alias-pattern 正则=统一名`: every author name the regex matches
+/// (anywhere in the name; anchor with `^...$` for a full match) becomes `to`.
+#[derive(Debug)]
+pub struct AliasPattern {
+    pub regex: Regex,
+    pub to: String,
+}
+
+pub fn parse_alias_patterns(raw: &[String]) -> Result<Vec<AliasPattern>> {
+    raw.iter()
+        .map(|entry| {
+            let (pattern, to) = parse_entry(entry).map_err(|message| {
+                CodedError::new(ErrorCode::BadAlias, format!("--alias-pattern {message}"))
+            })?;
+            Ok(AliasPattern {
+                regex: compile_pattern("--alias-pattern", &pattern)?,
+                to,
+            })
+        })
+        .collect()
+}
+
+pub fn compile_pattern(flag: &str, pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|err| {
+        CodedError::new(
+            ErrorCode::BadAlias,
+            format!("{flag} 的正则表达式无效：\"{pattern}\"（{err}）"),
+        )
+        .into()
+    })
+}
+
+/// Describes a second rule for `from` that names a different target.
+fn conflict(map: &HashMap<String, String>, from: &str, to: &str) -> Option<String> {
+    map.get(from)
+        .filter(|existing| *existing != to)
+        .map(|existing| format!("{from}={existing} 与 {from}={to}"))
+}
+
+/// Follows chains such as `zs=Zhang San`, `Zhang San=张三` to their last
+/// name, so every spelling ends up under the same author. A cycle is an
+/// error that lists its rules.
+pub fn resolve_alias_chains(map: HashMap<String, String>) -> Result<HashMap<String, String>> {
+    let mut resolved = HashMap::with_capacity(map.len());
+    // Sorted so a cycle is always reported from the same rule.
+    let mut froms: Vec<&String> = map.keys().collect();
+    froms.sort();
+    for from in froms {
+        let mut seen = vec![from.as_str()];
+        let mut target = map[from].as_str();
+        while let Some(next) = map.get(target) {
+            if let Some(start) = seen.iter().position(|name| *name == target) {
+                let rules: Vec<String> = seen[start..]
+                    .iter()
+                    .map(|name| format!("{name}={}", map[*name]))
+                    .collect();
+                bail!(CodedError::new(
+                    ErrorCode::BadAlias,
+                    format!("别名规则形成循环：{}", rules.join("，"))
+                ));
+            }
+            seen.push(target);
+            target = next;
+        }
+        resolved.insert(from.clone(), target.to_string());
+    }
+    Ok(resolved)
+}
+
+/// One entry, trimmed; the error completes a sentence naming its source.
+fn parse_entry(entry: &str) -> Result<(String, String), String> {
+    let Some((from, to)) = split_alias(entry) else {
+        return Err(format!(
+            "格式应为 旧名=统一名（名字中的 = 写作 \\=），当前为：{entry}"
+        ));
+    };
+    let (from, to) = (from.trim(), to.trim());
+    if from.is_empty() || to.is_empty() {
+        return Err(format!("不能为空：{entry}"));
+    }
+    Ok((from.to_string(), to.to_string()))
+}
+
+/// Splits at the first unescaped `=` and unescapes both halves.
+fn split_alias(entry: &str) -> Option<(String, String)> {
+    let mut from = String::new();
+    let mut to = String::new();
+    let mut target = &mut from;
+    let mut split = false;
+    let mut chars = entry.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next @ ('=' | '\\')) => target.push(next),
+                Some(next) => {
+                    target.push('\\');
+                    target.push(next);
+                }
+                None => target.push('\\'),
+            },
+            '=' if !split => {
+                split = true;
+                target = &mut to;
+            }
+            _ => target.push(c),
+        }
+    }
+    split.then_some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(raw: &[&str]) -> Result<HashMap<String, String>> {
+        parse_aliases(&raw.iter().map(|entry| entry.to_string()).collect::<Vec<_>>())
+    }
+
+    fn single(entry: &str) -> (String, String) {
+        aliases(&[entry]).unwrap().into_iter().next().unwrap()
+    }
+
+    fn pair(from: &str, to: &str) -> (String, String) {
+        (from.to_string(), to.to_string())
+    }
+
+    #[test]
+    fn escaped_delimiters_work_on_either_side() {
+        assert_eq!(single("ops=Ops\\=SRE Team"), pair("ops", "Ops=SRE Team"));
+        assert_eq!(single("a\\=b=c"), pair("a=b", "c"));
+        // After the split, a bare `=` is part of the target.
+        assert_eq!(single("ops=Ops=SRE"), pair("ops", "Ops=SRE"));
+        // `\\` is one backslash, so the `=` after it splits.
+        assert_eq!(single("a\\\\=b"), pair("a\\", "b"));
+        // Other escapes stay as written.
+        assert_eq!(single("dom\\user=User"), pair("dom\\user", "User"));
+        // A lone trailing backslash is kept.
+        assert_eq!(single("a=b\\"), pair("a", "b\\"));
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_trimmed_and_inner_kept() {
+        assert_eq!(single("  zs  =  Zhang San  "), pair("zs", "Zhang San"));
+        assert_eq!(single("\tz s=\tZhang  San\t"), pair("z s", "Zhang  San"));
+        assert!(aliases(&["  =Zhang San"]).is_err());
+        assert!(aliases(&["zs=   "]).is_err());
+    }
+
+    #[test]
+    fn unicode_equals_look_alikes_are_literal() {
+        for look_alike in ['＝', '⩵', '꞊', '≡'] {
+            let entry = format!("a{look_alike}b");
+            assert!(aliases(&[entry.as_str()]).is_err(), "{entry}");
+            let entry = format!("a{look_alike}b=c");
+            assert_eq!(single(&entry), pair(&format!("a{look_alike}b"), "c"));
+        }
+    }
+
+    #[test]
+    fn conflicting_rules_are_rejected_and_repeats_allowed() {
+        assert_eq!(aliases(&["zs=Zhang San", "zs=Zhang San"]).unwrap().len(), 1);
+        let err = aliases(&["zs=Zhang San", "zs=张三"]).unwrap_err().to_string();
+        assert!(err.contains("zs=Zhang San") && err.contains("zs=张三"), "{err}");
+    }
+
+    fn resolved(raw: &[&str]) -> Result<HashMap<String, String>> {
+        resolve_alias_chains(aliases(raw)?)
+    }
+
+    #[test]
+    fn chains_resolve_to_their_last_name() {
+        let map = resolved(&["zs=Zhang San", "Zhang San=张三", "张三=San Zhang", "ls=Li Si"]).unwrap();
+        assert_eq!(map.len(), 4);
+        for from in ["zs", "Zhang San", "张三"] {
+            assert_eq!(map[from], "San Zhang", "{from}");
+        }
+        assert_eq!(map["ls"], "Li Si");
+        // The order of the rules does not matter.
+        assert_eq!(resolved(&["张三=San Zhang", "Zhang San=张三", "zs=Zhang San"]).unwrap()["zs"], "San Zhang");
+    }
+
+    #[test]
+    fn cycles_are_rejected_with_their_rules() {
+        let err = resolved(&["a=b", "b=a"]).unwrap_err().to_string();
+        assert!(err.contains("循环") && err.contains("a=b") && err.contains("b=a"), "{err}");
+
+        let err = resolved(&["x=y", "y=z", "z=x", "w=x"]).unwrap_err().to_string();
+        for rule in ["x=y", "y=z", "z=x"] {
+            assert!(err.contains(rule), "{err}");
+        }
+        assert!(!err.contains("w=x"), "the lead-in is not part of the cycle: {err}");
+
+        assert!(resolved(&["me=me"]).is_err());
+    }
+
+    #[test]
+    fn alias_files_share_the_syntax_and_skip_comments() {
+        let path = std::env::temp_dir().join(format!("cowhorse-aliases-{}.txt", std::process::id()));
+        std::fs::write(&path, "# team\n\nops = Ops\\=SRE Team\n  zs=Zhang San\n").unwrap();
+        let map = parse_alias_file(&path).unwrap();
+        std::fs::write(&path, "ok=OK\nbroken\n").unwrap();
+        let err = parse_alias_file(&path).unwrap_err().to_string();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["ops"], "Ops=SRE Team");
+        assert_eq!(map["zs"], "Zhang San");
+        assert!(err.contains("第 2 行"), "{err}");
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Parser;
+
+use crate::gitlog::{fetch_commits, Commit, FetchOptions};
+use crate::progress::{NoProgress, Progress};
+
+#[derive(Parser, Debug)]
+#[command(
+    about = "根据共用邮箱和相似名字推荐别名规则（只输出建议，不修改任何文件）",
+    name = "aliases suggest"
+)]
+pub struct AliasSuggestCli {
+    /// 要分析的仓库
+    #[arg(long, default_value = ".", value_name = "PATH")]
+    pub path: PathBuf,
+
+    /// 只看最近多少天的提交（0 = 全部历史）
+    #[arg(long, default_value_t = 0, value_name = "DAYS")]
+    pub days: u32,
+
+    /// 不经过仓库的 .mailmap，直接使用 git 记录的名字
+    #[arg(long)]
+    pub no_mailmap: bool,
+}
+
+/// One name/email pair as recorded in git.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Identity {
+    name: String,
+    email: String,
+}
+
+/// Names that look like one person, with the name to keep.
+#[derive(Debug)]
+struct AliasGroup {
+    canonical: String,
+    /// Commit count per identity, most commits first.
+    identities: Vec<(Identity, usize)>,
+}
+
+pub fn run_alias_suggest(args: &AliasSuggestCli) -> Result<()> {
+    let options = FetchOptions {
+        since: (args.days > 0).then(|| Utc::now() - Duration::days(args.days as i64)),
+        early_stop_slack: Some(100),
+        mailmap: !args.no_mailmap,
+        ..FetchOptions::default()
+    };
+    let commits = fetch_commits(&args.path, &options, &Progress::new(&NoProgress))?.commits;
+    let groups = group_identities(&commits);
+    if groups.is_empty() {
+        println!("没有发现疑似同一个人的不同名字。");
+        return Ok(());
+    }
+
+    for (idx, group) in groups.iter().enumerate() {
+        let total: usize = group.identities.iter().map(|(_, count)| count).sum();
+        println!("{}. {}（共 {total} 次提交）", idx + 1, group.canonical);
+        for (identity, count) in &group.identities {
+            println!("   {} <{}>：{count} 次", identity.name, identity.email);
+        }
+    }
+
+    println!("\n命令行参数：");
+    for group in &groups {
+        for from in group.renamed() {
+            println!(
+                "  --alias \"{}={}\"",
+                escape_alias(from).replace('"', "\\\""),
+                escape_alias(&group.canonical).replace('"', "\\\"")
+            );
+        }
+    }
+
+    println!("\n.cowhorse.toml：");
+    println!("[aliases]");
+    let quote = |raw: &str| toml::Value::String(raw.to_string()).to_string();
+    for group in &groups {
+        for from in group.renamed() {
+            println!("{} = {}", quote(from), quote(&group.canonical));
+        }
+    }
+    Ok(())
+}
+
+impl AliasGroup {
+    /// The names other than `canonical`, each once.
+    fn renamed(&self) -> BTreeSet<&str> {
+        self.identities
+            .iter()
+            .map(|(identity, _)| identity.name.as_str())
+            .filter(|name| *name != self.canonical)
+            .collect()
+    }
+}
+
+/// Case, whitespace and `.`/`-`/`_` are ignored when comparing names.
+fn normalized_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '.' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Links names that share an email or a normalized spelling; groups with a
+/// single name are dropped. The name with the most commits is kept.
+fn group_identities(commits: &[Commit]) -> Vec<AliasGroup> {
+    let mut counts: BTreeMap<Identity, usize> = BTreeMap::new();
+    for commit in commits.iter().filter(|commit| !commit.co_author) {
+        let identity = Identity {
+            name: commit.author.clone(),
+            email: commit.email.clone(),
+        };
+        *counts.entry(identity).or_default() += 1;
+    }
+
+    let names: BTreeSet<String> = counts.keys().map(|identity| identity.name.clone()).collect();
+    let index: HashMap<String, usize> =
+        names.into_iter().enumerate().map(|(i, n)| (n, i)).collect();
+    let mut parent: Vec<usize> = (0..index.len()).collect();
+    fn root(parent: &mut [usize], mut node: usize) -> usize {
+        while parent[node] != node {
+            parent[node] = parent[parent[node]];
+            node = parent[node];
+        }
+        node
+    }
+    let mut union = |a: usize, b: usize| {
+        let (a, b) = (root(&mut parent, a), root(&mut parent, b));
+        parent[a.max(b)] = a.min(b);
+    };
+
+    let mut first_by_key: HashMap<String, usize> = HashMap::new();
+    for identity in counts.keys() {
+        let node = index[identity.name.as_str()];
+        let mut keys = vec![format!("name:{}", normalized_name(&identity.name))];
+        if !identity.email.is_empty() {
+            keys.push(format!("email:{}", identity.email));
+        }
+        for key in keys {
+            let first = *first_by_key.entry(key).or_insert(node);
+            union(first, node);
+        }
+    }
+
+    let mut members: BTreeMap<usize, Vec<(Identity, usize)>> = BTreeMap::new();
+    for (identity, count) in counts {
+        let group = root(&mut parent, index[identity.name.as_str()]);
+        members.entry(group).or_default().push((identity, count));
+    }
+
+    members
+        .into_values()
+        .filter_map(|mut identities| {
+            let mut per_name: BTreeMap<&str, usize> = BTreeMap::new();
+            for (identity, count) in &identities {
+                *per_name.entry(identity.name.as_str()).or_default() += count;
+            }
+            if per_name.len() < 2 {
+                return None;
+            }
+            let canonical = per_name
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(name, _)| name.to_string())?;
+            identities.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            Some(AliasGroup {
+                canonical,
+                identities,
+            })
+        })
+        .collect()
+}
+
+/// Escapes `=` and `\` the way `--alias` reads them.
+fn escape_alias(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('=', "\\=")
+}
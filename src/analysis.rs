@@ -0,0 +1,1166 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Months, TimeZone, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+
+use crate::alias::{
+    compile_pattern, parse_alias_file, parse_alias_patterns, parse_aliases, resolve_alias_chains,
+    AliasPattern,
+};
+use crate::cache;
+use crate::cli::Cli;
+use crate::config::{load_config, LoadedConfig, RepoConfig};
+use crate::defaults::{default_ignored_authors, is_probable_bot};
+use crate::exclude::{parse_excludes, ExcludeRule};
+use crate::forecast::forecast;
+use crate::gitlog::{fetch_commits, head_ref, Commit, FetchOptions, HeadRef};
+use crate::holidays::resolve_holidays;
+use crate::i18n::Msg;
+use crate::metrics::{
+    assess_confidence, attribute_directory, boundary_sensitivity, compute_author_metrics,
+    compute_metrics, find_suspect_identities, list_commits, working_days_between, AliasRule,
+    AuthorMetrics, CoverageGoal, DefaultClassifier, ExcludedSpan, IgnoreSource, IgnoredAuthorStat,
+    MetricsOptions, NightWindow, RepoMetrics, WeekendDays, WorkSchedule,
+};
+use crate::policy::{evaluate_policy, parse_policy, PolicyRule};
+use crate::progress::{Progress, ProgressEvent, ProgressSink};
+use crate::report::{pick_quip, suspect_reason};
+use crate::submodule::scan_submodules;
+use crate::time_filter::parse_time_filter;
+
+const DEFAULT_WINDOW_DAYS: u32 = 90;
+
+/// The resolved time window of one analysis pass.
+#[derive(Debug, Clone)]
+pub struct Window {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// `--rev-range`/`--since-tag` revision range, read instead of a date
+    /// window.
+    pub rev_range: Option<String>,
+    pub description: String,
+}
+
+/// Resolves `--since/--until/--window-days/--all-history` against `now`,
+/// or `--rev-range`/`--since-tag` (clap keeps the two kinds apart).
+pub fn resolve_window(cli: &Cli, now: DateTime<Utc>) -> Result<Window> {
+    let rev_range = match (&cli.rev_range, &cli.since_tag) {
+        (Some(range), _) => Some(range.trim().to_string()),
+        (None, Some(tag)) => Some(format!("{}..HEAD", tag.trim())),
+        (None, None) => None,
+    };
+    if let Some(range) = rev_range {
+        // Passed to git as an argument, so a leading `-` would read as an option.
+        if range.is_empty() || range.starts_with('-') {
+            bail!("提交范围无效：{range:?}（示例：v1.2.0..v1.3.0）");
+        }
+        return Ok(Window {
+            since: None,
+            until: None,
+            description: format!("提交范围 {range}"),
+            rev_range: Some(range),
+        });
+    }
+
+    if cli.since.is_some() && cli.window_days.is_some() {
+        eprintln!("警告：同时指定了 --since 与 --window-days，以 --since 为准，--window-days 被忽略。");
+    }
+    let window_days = if cli.all_history {
+        0
+    } else {
+        cli.window_days.unwrap_or(DEFAULT_WINDOW_DAYS)
+    };
+
+    let since = if let Some(ref raw) = cli.since {
+        Some(parse_time_filter(raw, now)?)
+    } else if window_days > 0 {
+        Some(now - Duration::days(window_days as i64))
+    } else {
+        None
+    };
+
+    let until = if let Some(ref raw) = cli.until {
+        Some(parse_time_filter(raw, now)?)
+    } else if cli.forecast {
+        Some(start_of_next_month(now))
+    } else {
+        None
+    };
+    if cli.forecast && until.is_some_and(|u| u <= now) {
+        bail!("--forecast 需要窗口终点在未来，请调整 --until");
+    }
+
+    if let (Some(s), Some(u)) = (since, until)
+        && s >= u
+    {
+        if cli.since.is_some() {
+            bail!("--since 必须早于 --until");
+        }
+        bail!(
+            "--until 早于 --window-days {window_days} 推算出的起点 {}，请调整 --until 或改用 --since / --all-history",
+            s.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+
+    Ok(Window {
+        since,
+        until,
+        rev_range: None,
+        description: describe_window(since, until, cli.since.is_none(), window_days),
+    })
+}
+
+/// `--work-start/--work-end` win over the config's `work_hours`; a flag
+/// given alone keeps the other bound from the config or the default.
+fn resolve_schedule(cli: &Cli, config: &RepoConfig) -> Result<WorkSchedule> {
+    let default = WorkSchedule::default();
+    let from_config = match config.work_hours.as_deref() {
+        Some(raw) => {
+            let (start, end) = raw
+                .split_once('-')
+                .ok_or_else(|| anyhow!("配置 work_hours 应为 \"HH:MM-HH:MM\"，当前为：{raw}"))?;
+            WorkSchedule::parse(start, end)?
+        }
+        None => default,
+    };
+    if cli.work_start.is_none() && cli.work_end.is_none() {
+        return Ok(from_config);
+    }
+    let start = cli
+        .work_start
+        .clone()
+        .unwrap_or_else(|| from_config.start.format("%H:%M").to_string());
+    let end = cli
+        .work_end
+        .clone()
+        .unwrap_or_else(|| from_config.end.format("%H:%M").to_string());
+    WorkSchedule::parse(&start, &end)
+}
+
+/// Renames every commit to the name used most often with its email, ties
+/// going to the lexicographically first, so one address never shows up as
+/// several authors and the pick does not depend on commit order. Commits
+/// without an email keep their name.
+fn unify_names_by_email(commits: &mut [Commit]) {
+    let mut counts: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    for commit in commits.iter() {
+        if commit.email.is_empty() {
+            continue;
+        }
+        *counts
+            .entry(commit.email.as_str())
+            .or_default()
+            .entry(commit.author.as_str())
+            .or_default() += 1;
+    }
+    let names: HashMap<String, String> = counts
+        .into_iter()
+        .filter_map(|(email, names)| {
+            let (name, _) = names
+                .into_iter()
+                .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))?;
+            Some((email.to_string(), name.to_string()))
+        })
+        .collect();
+    for commit in commits.iter_mut() {
+        if let Some(name) = names.get(&commit.email)
+            && *name != commit.author
+        {
+            let raw = std::mem::replace(&mut commit.author, name.clone());
+            commit.raw_author = Some(raw);
+        }
+    }
+}
+
+/// Lists, per author, the raw names their commits carried before email
+/// unification and aliases, for `--detailed-authors`.
+fn fill_alternate_names(metrics: &mut RepoMetrics, commits: &[Commit]) {
+    let mut alternates: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for commit in commits {
+        if let Some(raw) = commit.raw_author.as_deref()
+            && raw != commit.author
+        {
+            alternates.entry(commit.author.as_str()).or_default().insert(raw);
+        }
+    }
+    for author in metrics
+        .authors
+        .iter_mut()
+        .chain(&mut metrics.top_after_hours_authors)
+        .chain(&mut metrics.chill_authors)
+    {
+        if let Some(names) = alternates.get(author.name.as_str()) {
+            author.alternate_names = names.iter().map(|name| name.to_string()).collect();
+        }
+    }
+}
+
+fn parse_timezone(raw: &str) -> Result<Tz> {
+    raw.trim().parse::<Tz>().map_err(|_| {
+        anyhow!(
+            "未知时区：{raw}（请使用 IANA 时区名，例如 Asia/Shanghai、Europe/Berlin、America/New_York、UTC）"
+        )
+    })
+}
+
+/// `--weekend` wins over the config's `weekend_days`.
+fn resolve_weekend(cli: &Cli, config: &RepoConfig) -> Result<WeekendDays> {
+    if let Some(raw) = &cli.weekend {
+        return WeekendDays::parse(raw);
+    }
+    match &config.weekend_days {
+        Some(days) => WeekendDays::parse(&days.join(","))
+            .context("配置 weekend_days 无效"),
+        None => Ok(WeekendDays::default()),
+    }
+}
+
+fn resolve_night_window(cli: &Cli) -> Result<NightWindow> {
+    if cli.night_start.is_none() && cli.night_end.is_none() {
+        return Ok(NightWindow::default());
+    }
+    let default = NightWindow::default();
+    let start = cli
+        .night_start
+        .clone()
+        .unwrap_or_else(|| default.start.format("%H:%M").to_string());
+    let end = cli
+        .night_end
+        .clone()
+        .unwrap_or_else(|| default.end.format("%H:%M").to_string());
+    NightWindow::parse(&start, &end)
+}
+
+fn start_of_next_month(now: DateTime<Utc>) -> DateTime<Utc> {
+    let local = now.with_timezone(&Local).date_naive();
+    let next = local
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .unwrap_or(local);
+    let naive = next.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map_or_else(|| Utc.from_utc_datetime(&naive), |dt| dt.with_timezone(&Utc))
+}
+
+pub fn describe_window(
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    rolling: bool,
+    window_days: u32,
+) -> String {
+    let fmt = "%Y-%m-%d %H:%M UTC";
+    let start = match since {
+        Some(s) if rolling => format!("最近 {window_days} 天（{} 起）", s.format(fmt)),
+        Some(s) => format!("{} 起", s.format(fmt)),
+        None => "全部历史".to_string(),
+    };
+    match until {
+        Some(u) => format!("{start}，截至 {}", u.format(fmt)),
+        None => start,
+    }
+}
+
+pub enum Analysis {
+    /// Nothing to analyze; the message explains why.
+    Empty(String),
+    Done {
+        metrics: Box<RepoMetrics>,
+        commits: Vec<Commit>,
+    },
+}
+
+/// Everything derived from the CLI and repo config that does not depend on
+/// the window, so several windows can be analyzed with one setup.
+pub struct Analyzer<'a> {
+    pub cli: &'a Cli,
+    pub repo_path: PathBuf,
+    pub now: DateTime<Utc>,
+    config: LoadedConfig,
+    alias_map: HashMap<String, String>,
+    alias_patterns: Vec<AliasPattern>,
+    ignored: HashMap<String, IgnoreSource>,
+    ignore_patterns: Vec<Regex>,
+    policy_rules: Vec<PolicyRule>,
+    exclude_rules: Vec<ExcludeRule>,
+    classifier: DefaultClassifier,
+    /// `--timezone`; `None` judges each commit in its own offset.
+    timezone: Option<Tz>,
+    progress: Progress<'a>,
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn new(
+        cli: &'a Cli,
+        repo_path: &Path,
+        now: DateTime<Utc>,
+        progress: &'a dyn ProgressSink,
+    ) -> Result<Self> {
+        let repo_path = repo_path
+            .canonicalize()
+            .unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let config = load_config(&repo_path, cli.strict_config, cli.no_config)?;
+        let settings = &config.settings;
+
+        // Config first, so explicit CLI rules win on the same name.
+        let mut alias_map: HashMap<String, String> = settings.aliases.clone().into_iter().collect();
+        if let Some(path) = &cli.alias_file {
+            alias_map.extend(parse_alias_file(path)?);
+        }
+        alias_map.extend(parse_aliases(&cli.alias)?);
+        let alias_map = resolve_alias_chains(alias_map)?;
+        // Rules whose old side is an email match `Commit::email`, which is lowercased.
+        let alias_map: HashMap<String, String> = alias_map
+            .into_iter()
+            .map(|(from, to)| {
+                if from.contains('@') {
+                    (from.to_lowercase(), to)
+                } else {
+                    (from, to)
+                }
+            })
+            .collect();
+
+        let alias_patterns = parse_alias_patterns(&cli.alias_pattern)?;
+        let ignore_patterns = cli
+            .ignore_author_pattern
+            .iter()
+            .map(|pattern| compile_pattern("--ignore-author-pattern", pattern))
+            .collect::<Result<Vec<_>>>()?;
+
+        let exclude_rules = parse_excludes(&cli.exclude, now)?;
+        let schedule = resolve_schedule(cli, settings)?;
+        let night = resolve_night_window(cli)?;
+        let weekend = resolve_weekend(cli, settings)?;
+        // Passed to git as arguments, so a leading `-` would read as an option.
+        if let Some(bad) = cli.branch.iter().find(|name| name.starts_with('-')) {
+            bail!("--branch 的分支名不能以 - 开头：{bad}");
+        }
+        let timezone = cli
+            .timezone
+            .as_deref()
+            .or(settings.timezone.as_deref())
+            .map(parse_timezone)
+            .transpose()?;
+        let presets = if cli.holiday_preset.is_empty() {
+            &settings.holiday_presets
+        } else {
+            &cli.holiday_preset
+        };
+        let holidays = resolve_holidays(presets, cli.holidays.as_deref())?;
+
+        let policy_rules = match &cli.policy {
+            Some(raw) => parse_policy(raw)?,
+            None => Vec::new(),
+        };
+
+        let mut ignored: HashMap<String, IgnoreSource> = default_ignored_authors()
+            .into_iter()
+            .map(|name| (name.to_string(), IgnoreSource::Default))
+            .collect();
+        for name in cli.ignore_author.iter().chain(&settings.ignore_authors) {
+            ignored.insert(name.clone(), IgnoreSource::User);
+        }
+        for email in cli.ignore_email.iter().chain(&settings.ignore_emails) {
+            ignored.insert(email.trim().to_lowercase(), IgnoreSource::User);
+        }
+
+        Ok(Analyzer {
+            cli,
+            repo_path,
+            now,
+            config,
+            alias_map,
+            alias_patterns,
+            ignored,
+            ignore_patterns,
+            policy_rules,
+            exclude_rules,
+            classifier: DefaultClassifier {
+                schedule,
+                night,
+                weekend,
+                holidays,
+            },
+            timezone,
+            progress: Progress::new(progress),
+        })
+    }
+
+    pub fn analyze(&self, window: &Window) -> Result<Analysis> {
+        let cli = self.cli;
+        let repo_path = &self.repo_path;
+        let (since, until) = (window.since, window.until);
+
+        let cache_dir = cache::default_dir();
+        let fetch_options = FetchOptions {
+            since,
+            until,
+            author: cli.author.as_deref(),
+            limit: cli.limit,
+            with_paths: cli.group_by_dir.is_some(),
+            early_stop_slack: Some(cli.early_stop_slack).filter(|slack| *slack > 0),
+            min_coverage_days: cli.min_coverage_days,
+            retries: cli.git_retries,
+            verbose: cli.verbose,
+            mailmap: !cli.no_mailmap,
+            no_merges: cli.no_merges,
+            first_parent: cli.first_parent,
+            branches: &cli.branch,
+            all_branches: cli.all_branches,
+            rev_range: window.rev_range.as_deref(),
+            pathspecs: &cli.pathspec,
+            date_source: cli.date_source,
+            coauthors: !cli.no_coauthors,
+            cache_dir: (cli.cache || cli.refresh).then_some(cache_dir.as_path()),
+            refresh_cache: cli.refresh,
+        };
+        let head = head_ref(repo_path).ok();
+        let fetched = fetch_commits(repo_path, &fetch_options, &self.progress)?;
+        let coverage = cli.min_coverage_days.map(|goal| CoverageGoal {
+            goal_days: goal,
+            covered_days: fetched.covered_days,
+            met: fetched.covered_days >= goal,
+        });
+        let mut merges_skipped = fetched.merges_skipped;
+        let mut rewritten = fetched.rewritten;
+        let mut commits = fetched.commits;
+
+        let mut skipped_submodules = Vec::new();
+        if cli.include_submodules {
+            for commit in &mut commits {
+                commit.component = Some(".".to_string());
+            }
+            let scan = scan_submodules(repo_path, cli.submodule_depth)?;
+            for submodule in scan.repos {
+                let fetched = fetch_commits(&submodule.abs_path, &fetch_options, &self.progress)
+                    .with_context(|| format!("读取子模块 {} 失败", submodule.path))?;
+                merges_skipped += fetched.merges_skipped;
+                rewritten += fetched.rewritten;
+                let mut sub_commits = fetched.commits;
+                for commit in &mut sub_commits {
+                    commit.component = Some(submodule.path.clone());
+                }
+                commits.append(&mut sub_commits);
+            }
+            skipped_submodules = scan.skipped;
+        }
+
+        // git lists a commit once per run even when several refs reach it;
+        // this also covers a submodule vendored twice.
+        let mut seen = HashSet::new();
+        commits.retain(|commit| {
+            seen.insert((commit.hash.clone(), commit.co_author.then(|| commit.author.clone())))
+        });
+
+        if cli.warn_rewritten && rewritten > 0 {
+            eprintln!(
+                "警告：{rewritten} 次提交的作者时间与提交时间相差超过 1 小时，历史可能经过 rebase 或 cherry-pick，加班统计可能失真（可尝试 --date-source committer 对比）"
+            );
+        }
+
+        if let Some(email) = &cli.author_email {
+            let email = email.trim().to_lowercase();
+            commits.retain(|commit| commit.email == email);
+        }
+
+        let mut ignored_counts: BTreeMap<String, usize> = self
+            .ignored
+            .keys()
+            .map(|name| (name.clone(), 0))
+            .collect();
+        let mut pattern_counts = vec![0usize; self.ignore_patterns.len()];
+        let mut bot_counts: BTreeMap<String, usize> = BTreeMap::new();
+        let drop_bots = !cli.include_bots;
+        if !self.ignored.is_empty() || !self.ignore_patterns.is_empty() || drop_bots {
+            commits.retain(|commit| {
+                let key = if ignored_counts.contains_key(&commit.email) {
+                    &commit.email
+                } else {
+                    &commit.author
+                };
+                if let Some(count) = ignored_counts.get_mut(key) {
+                    *count += 1;
+                    return false;
+                }
+                match self
+                    .ignore_patterns
+                    .iter()
+                    .position(|pattern| pattern.is_match(&commit.author))
+                {
+                    Some(idx) => {
+                        pattern_counts[idx] += 1;
+                        false
+                    }
+                    None if drop_bots && is_probable_bot(commit) => {
+                        *bot_counts.entry(commit.author.clone()).or_default() += 1;
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        unify_names_by_email(&mut commits);
+
+        if let Some(zone) = self.timezone {
+            for commit in &mut commits {
+                commit.recorded_offset = Some(*commit.timestamp.offset());
+                commit.timestamp = commit.timestamp.with_timezone(&zone).fixed_offset();
+            }
+        }
+
+        let mut alias_rules: Vec<AliasRule> = self
+            .alias_map
+            .iter()
+            .map(|(from, to)| AliasRule {
+                from: from.clone(),
+                to: to.clone(),
+            })
+            .collect();
+        alias_rules.sort_by(|a, b| a.from.cmp(&b.from));
+        if !self.alias_map.is_empty() || !self.alias_patterns.is_empty() {
+            for commit in &mut commits {
+                let mapped = self
+                    .alias_map
+                    .get(commit.email.as_str())
+                    .or_else(|| self.alias_map.get(commit.author.as_str()))
+                    .or_else(|| {
+                        self.alias_patterns
+                            .iter()
+                            .find(|pattern| pattern.regex.is_match(&commit.author))
+                            .map(|pattern| &pattern.to)
+                    });
+                if let Some(mapped) = mapped {
+                    let raw = std::mem::replace(&mut commit.author, mapped.clone());
+                    commit.raw_author.get_or_insert(raw);
+                }
+            }
+        }
+
+        // Checked on the full aliased history, before exclusions and before
+        // the leaderboards are built, so the report can flag those entries.
+        // Co-author credits go last so the commits proper stay a prefix for
+        // the repo-level passes below; `retain` keeps that order.
+        commits.sort_by_key(|commit| commit.co_author);
+        let own = |commits: &[Commit]| commits.partition_point(|commit| !commit.co_author);
+        let suspect_identities =
+            find_suspect_identities(&commits[..own(&commits)], cli.max_commits_per_day);
+        for suspect in &suspect_identities {
+            let reasons: Vec<String> = suspect
+                .reasons
+                .iter()
+                .map(|reason| suspect_reason(reason, cli.lang))
+                .collect();
+            eprintln!(
+                "{}",
+                cli.lang.format(
+                    Msg::SuspectWarning,
+                    &[
+                        &suspect.name,
+                        &reasons.join(cli.lang.text(Msg::ListSeparator)),
+                        &suspect.merged_from.join(", "),
+                    ]
+                )
+            );
+        }
+
+        // Matched against canonical names, so this runs after aliasing.
+        let mut excluded_counts = vec![0usize; self.exclude_rules.len()];
+        if !self.exclude_rules.is_empty() {
+            commits.retain(|commit| {
+                let at = commit.timestamp.with_timezone(&Utc);
+                match self
+                    .exclude_rules
+                    .iter()
+                    .position(|rule| rule.matches(&commit.author, at))
+                {
+                    Some(idx) => {
+                        excluded_counts[idx] += 1;
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        if let Some(depth) = cli.group_by_dir {
+            for commit in &mut commits {
+                commit.directory = Some(attribute_directory(&commit.paths, depth));
+            }
+        }
+
+        if let Some(HeadRef::Unborn { name }) = &head
+            && commits.is_empty()
+        {
+            return Ok(Analysis::Empty(format!(
+                "{} 当前分支 {name} 还没有任何提交，暂时没有可分析的内容。",
+                repo_path.display()
+            )));
+        }
+
+        if commits.is_empty() {
+            return Ok(Analysis::Empty(format!(
+                "在 {} 中没有找到符合过滤条件的提交。",
+                repo_path.display()
+            )));
+        }
+
+        let mut ignored_stats: Vec<IgnoredAuthorStat> = ignored_counts
+            .into_iter()
+            .map(|(name, commits)| IgnoredAuthorStat {
+                source: self.ignored[&name],
+                name,
+                commits,
+            })
+            .collect();
+        ignored_stats.extend(self.ignore_patterns.iter().zip(pattern_counts).map(
+            |(pattern, commits)| IgnoredAuthorStat {
+                name: pattern.as_str().to_string(),
+                commits,
+                source: IgnoreSource::Pattern,
+            },
+        ));
+        ignored_stats.extend(bot_counts.into_iter().map(|(name, commits)| IgnoredAuthorStat {
+            name,
+            commits,
+            source: IgnoreSource::Bot,
+        }));
+        self.progress.emit(ProgressEvent::MetricsStarted {
+            commits: commits.len(),
+        });
+        let mut metrics = compute_metrics(
+            repo_path,
+            &commits,
+            ignored_stats,
+            alias_rules,
+            &self.metrics_options(),
+            &self.classifier,
+        );
+        metrics.skipped_submodules = skipped_submodules;
+        metrics.suspect_identities = suspect_identities;
+        metrics.excluded_spans = self
+            .exclude_rules
+            .iter()
+            .zip(excluded_counts)
+            .map(|(rule, commits)| ExcludedSpan {
+                author: rule.author.clone(),
+                range: rule.range.clone(),
+                commits,
+            })
+            .collect();
+        metrics.repo_config_path = self.config.repo_path.clone();
+        metrics.user_config_path = self.config.user_path.clone();
+        metrics.head = head;
+        metrics.coverage = coverage;
+        metrics.rewritten_commits = cli.warn_rewritten.then_some(rewritten);
+        metrics.skipped_merge_commits = cli.no_merges.then_some(merges_skipped);
+        self.finish(&mut metrics, &commits, window);
+        self.progress.emit(ProgressEvent::MetricsFinished);
+
+        Ok(Analysis::Done {
+            metrics: Box::new(metrics),
+            commits,
+        })
+    }
+
+    /// `--author-report`: `name` is matched against canonical (aliased)
+    /// names in the commits of an [`Analysis::Done`].
+    pub fn author_report(
+        &self,
+        name: &str,
+        metrics: &RepoMetrics,
+        commits: &[Commit],
+    ) -> Result<AuthorMetrics> {
+        if let Some(report) = self.find_author(name, metrics, commits) {
+            return Ok(report);
+        }
+        let known: Vec<&str> = metrics.authors.iter().map(|author| author.name.as_str()).collect();
+        bail!(
+            "窗口内没有 {name} 的提交（需与别名合并后的名字完全一致）。窗口内的作者：{}",
+            known.join(", ")
+        );
+    }
+
+    /// Like [`Analyzer::author_report`], but `None` instead of an error when
+    /// the author has no commits here.
+    pub fn find_author(
+        &self,
+        name: &str,
+        metrics: &RepoMetrics,
+        commits: &[Commit],
+    ) -> Option<AuthorMetrics> {
+        compute_author_metrics(
+            name,
+            metrics,
+            commits,
+            &self.metrics_options(),
+            &self.classifier,
+        )
+    }
+
+    fn metrics_options(&self) -> MetricsOptions {
+        let cli = self.cli;
+        MetricsOptions {
+            rank_by: cli.rank_by,
+            min_commits: cli.min_commits,
+            top: cli.top as usize,
+            sort_authors: cli.sort_authors,
+            weights: cli.weights,
+            now: self.now,
+            overtime_day_min_minutes: cli.overtime_day_min_minutes,
+            long_day_hours: cli.long_day_hours,
+            crunch_threshold: cli.crunch_threshold,
+            vacation_days: cli.vacation_days,
+            session_gap_minutes: cli.session_gap,
+            session_padding_minutes: cli.session_padding,
+            busiest_by: cli.busiest_by,
+            schedule: self.classifier.schedule,
+            night: self.classifier.night,
+            weekend: self.classifier.weekend,
+            severity_overlap: cli.severity_overlap,
+        }
+    }
+
+    /// The parts of a report that depend on the run options and the window
+    /// rather than on the repository, shared by single-repo and aggregate
+    /// metrics. `commits` keeps co-author credits last.
+    fn finish(&self, metrics: &mut RepoMetrics, commits: &[Commit], window: &Window) {
+        let cli = self.cli;
+        let (since, until) = (window.since, window.until);
+        let own = commits.partition_point(|commit| !commit.co_author);
+        metrics.policy_results = evaluate_policy(&self.policy_rules, metrics);
+        metrics.window_description = window.description.clone();
+        if cli.detailed_authors {
+            fill_alternate_names(metrics, &commits[..own]);
+        } else {
+            metrics.all_nighter_events.clear();
+        }
+        metrics.window_start = since;
+        metrics.window_end = until;
+        metrics.rev_range = window.rev_range.clone();
+        metrics.pathspecs = cli.pathspec.clone();
+        metrics.date_source = cli.date_source;
+        metrics.branches = if cli.all_branches {
+            vec!["--all".to_string()]
+        } else {
+            cli.branch.clone()
+        };
+        metrics.timezone = self.timezone.map(|zone| zone.name().to_string());
+        metrics.newest_commit_age_days = metrics
+            .analysis_end
+            .map(|end| (self.now - end.with_timezone(&Utc)).num_minutes() as f64 / (24.0 * 60.0));
+        if !cli.no_quips {
+            let seed = cli
+                .seed
+                .unwrap_or_else(|| self.now.timestamp_nanos_opt().unwrap_or_default() as u64);
+            metrics.quip = Some(pick_quip(metrics.severity_score, seed, cli.lang));
+        }
+        if let (Some(first), Some(last)) = (metrics.analysis_start, metrics.analysis_end) {
+            // An explicit window counts its own days; all-history falls back to
+            // the observed commit range. `until` is an exclusive instant.
+            let start = since.map_or(first.date_naive(), |s| s.date_naive());
+            // A future end (forecast) only counts the days observed so far.
+            let end = match (since, until) {
+                (_, Some(u)) => (u.min(self.now) - Duration::seconds(1)).date_naive(),
+                (Some(_), None) => self.now.date_naive(),
+                (None, None) => last.date_naive(),
+            };
+            let working_days = working_days_between(start, end, &self.classifier);
+            assess_confidence(metrics, working_days);
+        }
+
+        if cli.sensitivity {
+            metrics.sensitivity = boundary_sensitivity(
+                &commits[..own],
+                metrics,
+                cli.overtime_day_min_minutes,
+                &self.classifier,
+            );
+        }
+        if let Some(kind) = cli.show_commits {
+            metrics.shown_commits = Some(list_commits(
+                &commits[..own],
+                kind,
+                cli.show_limit,
+                &self.classifier,
+            ));
+        }
+        if cli.forecast
+            && let (Some(until), Some(first)) = (until, metrics.analysis_start)
+        {
+            let start = since.map_or(first.date_naive(), |s| s.date_naive());
+            metrics.forecast = Some(forecast(
+                &commits[..own],
+                start,
+                self.now,
+                until,
+                metrics,
+                &self.classifier,
+            ));
+        }
+    }
+
+    /// Merges the runs of several repositories: their commits feed one set of
+    /// metrics, judged with this analyzer's schedule and rules. A commit
+    /// reachable from several of them (forks, vendored copies) counts once.
+    /// Returns the merged commits, co-author credits last.
+    pub fn aggregate(
+        &self,
+        repos: &[Box<RepoMetrics>],
+        mut commits: Vec<Commit>,
+        window: &Window,
+    ) -> (Box<RepoMetrics>, Vec<Commit>) {
+        let mut seen = HashSet::new();
+        commits.retain(|commit| {
+            seen.insert((commit.hash.clone(), commit.co_author.then(|| commit.author.clone())))
+        });
+        commits.sort_by_key(|commit| commit.co_author);
+
+        let mut ignored: BTreeMap<String, IgnoredAuthorStat> = BTreeMap::new();
+        let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+        for repo in repos {
+            for stat in &repo.ignored_author_stats {
+                ignored
+                    .entry(stat.name.clone())
+                    .and_modify(|merged| merged.commits += stat.commits)
+                    .or_insert_with(|| stat.clone());
+            }
+            for rule in &repo.alias_rules {
+                aliases.insert(rule.from.clone(), rule.to.clone());
+            }
+        }
+        let alias_rules = aliases
+            .into_iter()
+            .map(|(from, to)| AliasRule { from, to })
+            .collect();
+
+        let label: Vec<String> = repos
+            .iter()
+            .map(|repo| repo.repo_path.display().to_string())
+            .collect();
+        let mut metrics = compute_metrics(
+            Path::new(&label.join(", ")),
+            &commits,
+            ignored.into_values().collect(),
+            alias_rules,
+            &self.metrics_options(),
+            &self.classifier,
+        );
+        for repo in repos {
+            metrics.suspect_identities.extend(repo.suspect_identities.iter().cloned());
+            metrics.skipped_submodules.extend(repo.skipped_submodules.iter().cloned());
+        }
+        self.finish(&mut metrics, &commits, window);
+        (Box::new(metrics), commits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::i18n::Lang;
+    use crate::progress::NoProgress;
+    use crate::testutil::TempRepo;
+    use clap::Parser;
+
+    fn cli_for(repo: &TempRepo, extra: &[&str]) -> Cli {
+        let path = repo.path().to_str().unwrap();
+        let mut args = vec!["zzh", "--path", path, "--all-history", "--no-config", "-q"];
+        args.extend_from_slice(extra);
+        Cli::parse_from(args)
+    }
+
+    fn repo_with_commits() -> TempRepo {
+        let repo = TempRepo::new();
+        repo.commit("A", "2026-09-01T10:00:00+08:00");
+        repo.commit("A", "2026-09-01T21:00:00+08:00");
+        repo.commit("B", "2026-09-02T23:00:00+08:00");
+        repo
+    }
+
+    fn analyze(cli: &Cli, sink: &dyn ProgressSink) -> Analysis {
+        let now = Utc::now();
+        let analyzer = Analyzer::new(cli, Path::new(&cli.path[0]), now, sink).unwrap();
+        analyzer.analyze(&resolve_window(cli, now).unwrap()).unwrap()
+    }
+
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<&'static str>>);
+
+    impl ProgressSink for Recorder {
+        fn on_event(&self, event: ProgressEvent<'_>) {
+            self.0.borrow_mut().push(match event {
+                ProgressEvent::FetchStarted { .. } => "fetch_started",
+                ProgressEvent::CommitsRead { .. } => "commits_read",
+                ProgressEvent::FetchFinished { .. } => "fetch_finished",
+                ProgressEvent::MetricsStarted { .. } => "metrics_started",
+                ProgressEvent::MetricsFinished => "metrics_finished",
+            });
+        }
+    }
+
+    struct Panicking;
+
+    impl ProgressSink for Panicking {
+        fn on_event(&self, _event: ProgressEvent<'_>) {
+            panic!("sink failure");
+        }
+    }
+
+    #[test]
+    fn unborn_branch_reports_that_there_is_nothing_yet() {
+        let repo = TempRepo::new();
+        repo.git(&["switch", "-q", "-c", "x"]);
+        match analyze(&cli_for(&repo, &[]), &NoProgress) {
+            Analysis::Empty(message) => {
+                assert!(message.contains("当前分支 x 还没有任何提交"), "{message}")
+            }
+            Analysis::Done { .. } => panic!("expected an empty analysis"),
+        }
+    }
+
+    #[test]
+    fn detached_head_is_recorded_in_the_metrics() {
+        let repo = repo_with_commits();
+        repo.git(&["checkout", "-q", "--detach"]);
+        let Analysis::Done { metrics, .. } = analyze(&cli_for(&repo, &[]), &NoProgress) else {
+            panic!("expected a finished analysis");
+        };
+        assert_eq!(metrics.total_commits, 3);
+        assert!(matches!(metrics.head, Some(HeadRef::Detached { .. })), "{:?}", metrics.head);
+    }
+
+    #[test]
+    fn bots_are_excluded_and_listed_with_counts_unless_included() {
+        let repo = repo_with_commits();
+        repo.commit("dependabot[bot]", "2026-09-02T03:00:00+08:00");
+        repo.commit("dependabot[bot]", "2026-09-03T03:00:00+08:00");
+        repo.commit("renovate", "2026-09-05T23:00:00+08:00");
+
+        let Analysis::Done { metrics, .. } = analyze(&cli_for(&repo, &[]), &NoProgress) else {
+            panic!("expected a finished analysis");
+        };
+        assert_eq!(metrics.total_commits, 3);
+        let bots: Vec<(&str, usize)> = metrics
+            .ignored_author_stats
+            .iter()
+            .filter(|stat| stat.source == IgnoreSource::Bot)
+            .map(|stat| (stat.name.as_str(), stat.commits))
+            .collect();
+        assert_eq!(bots, [("dependabot[bot]", 2), ("renovate", 1)]);
+
+        let Analysis::Done { metrics, .. } = analyze(&cli_for(&repo, &["--include-bots"]), &NoProgress) else {
+            panic!("expected a finished analysis");
+        };
+        assert_eq!(metrics.total_commits, 6);
+        assert!(metrics.ignored_author_stats.iter().all(|stat| stat.source != IgnoreSource::Bot));
+    }
+
+    #[test]
+    fn progress_events_arrive_in_order() {
+        let repo = repo_with_commits();
+        let cli = cli_for(&repo, &[]);
+        let recorder = Recorder::default();
+        analyze(&cli, &recorder);
+        assert_eq!(
+            *recorder.0.borrow(),
+            ["fetch_started", "fetch_finished", "metrics_started", "metrics_finished"]
+        );
+    }
+
+    #[test]
+    fn a_panicking_sink_does_not_stop_the_analysis() {
+        let repo = repo_with_commits();
+        let cli = cli_for(&repo, &[]);
+        let Analysis::Done { metrics, .. } = analyze(&cli, &Panicking) else {
+            panic!("expected a finished analysis");
+        };
+        assert_eq!(metrics.total_commits, 3);
+    }
+
+    fn named(author: &str, email: &str) -> Commit {
+        Commit {
+            author: author.to_string(),
+            email: email.to_string(),
+            ..Commit::default()
+        }
+    }
+
+    #[test]
+    fn email_display_name_is_most_frequent_then_lexicographic() {
+        for order in [["Zed", "Amy"], ["Amy", "Zed"]] {
+            let mut commits: Vec<Commit> =
+                order.iter().map(|name| named(name, "x@example.com")).collect();
+            commits.push(named("Zed", ""));
+            unify_names_by_email(&mut commits);
+            assert_eq!(commits[0].author, "Amy");
+            assert_eq!(commits[1].author, "Amy");
+            assert_eq!(commits[2].author, "Zed", "commits without an email keep their name");
+        }
+
+        let mut commits = vec![
+            named("Amy", "x@example.com"),
+            named("Zed", "x@example.com"),
+            named("Zed", "x@example.com"),
+        ];
+        unify_names_by_email(&mut commits);
+        assert!(commits.iter().all(|commit| commit.author == "Zed"));
+        assert_eq!(commits[0].raw_author.as_deref(), Some("Amy"));
+    }
+
+    #[test]
+    fn detailed_authors_lists_alternate_names() {
+        let repo = TempRepo::new();
+        repo.commit("Amy", "2026-09-01T10:00:00+08:00");
+        repo.git(&[
+            "-c",
+            "user.name=Amy Old",
+            "-c",
+            "user.email=amy@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "renamed",
+        ]);
+        repo.commit("B", "2026-09-02T10:00:00+08:00");
+
+        let authors = |extra: &[&str]| match analyze(&cli_for(&repo, extra), &NoProgress) {
+            Analysis::Done { metrics, .. } => metrics.authors,
+            Analysis::Empty(message) => panic!("{message}"),
+        };
+        let detailed = authors(&["--detailed-authors"]);
+        assert_eq!(detailed.len(), 2);
+        assert_eq!(detailed[0].name, "Amy");
+        assert_eq!(detailed[0].alternate_names, ["Amy Old"]);
+        assert!(detailed[1].alternate_names.is_empty());
+        assert!(authors(&[]).iter().all(|author| author.alternate_names.is_empty()));
+    }
+
+    #[test]
+    fn group_by_dir_buckets_commits_by_their_changed_paths() {
+        let repo = TempRepo::new();
+        let commit_files = |files: &[&str], date: &str| {
+            for file in files {
+                let path = repo.path().join(file);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, date).unwrap();
+            }
+            repo.git(&["add", "-A"]);
+            repo.commit("A", date);
+        };
+        commit_files(&["backend/api/a.rs", "backend/db/b.rs", "mobile/c.kt"], "2026-09-01T10:00:00+08:00");
+        commit_files(&["backend/api/a.rs"], "2026-09-01T22:00:00+08:00");
+        commit_files(&["mobile/c.kt", "backend/db/b.rs"], "2026-09-02T23:00:00+08:00");
+        repo.commit("A", "2026-09-03T11:00:00+08:00");
+
+        let Analysis::Done { metrics, .. } =
+            analyze(&cli_for(&repo, &["--group-by-dir", "1"]), &NoProgress)
+        else {
+            panic!("expected commits");
+        };
+        let directories: Vec<_> = metrics
+            .directories
+            .iter()
+            .map(|dir| (dir.path.as_str(), dir.total_commits, dir.after_hours_commits))
+            .collect();
+        assert_eq!(directories, [("(mixed)", 2, 1), ("backend", 2, 1)]);
+
+        let Analysis::Done { metrics, .. } = analyze(&cli_for(&repo, &[]), &NoProgress) else {
+            panic!("expected commits");
+        };
+        assert!(metrics.directories.is_empty());
+    }
+
+    #[test]
+    fn quips_follow_the_seed_and_stay_out_of_json_when_disabled() {
+        let repo = repo_with_commits();
+        let quip = |extra: &[&str]| match analyze(&cli_for(&repo, extra), &NoProgress) {
+            Analysis::Done { metrics, .. } => metrics.quip.clone(),
+            Analysis::Empty(_) => panic!("expected commits"),
+        };
+        let seeded = quip(&["--seed", "7"]);
+        assert!(seeded.is_some());
+        assert_eq!(quip(&["--seed", "7"]), seeded);
+
+        let Analysis::Done { metrics, .. } = analyze(&cli_for(&repo, &["--no-quips"]), &NoProgress)
+        else {
+            panic!("expected commits");
+        };
+        let english = quip(&["--seed", "7", "--lang", "en"]);
+        assert_eq!(english, Some(pick_quip(metrics.severity_score, 7, Lang::En)));
+        assert!(metrics.quip.is_none());
+        let json = serde_json::to_value(&*metrics).unwrap();
+        assert!(json.get("quip").is_none());
+    }
+
+    #[test]
+    fn aliasing_two_people_into_one_is_flagged_before_the_leaderboards() {
+        let repo = TempRepo::new();
+        for day in ["01", "02"] {
+            for (author, hour) in [("Alice", 20), ("Bob", 21), ("Alice", 22)] {
+                repo.commit(author, &format!("2026-09-{day}T{hour}:00:00+08:00"));
+            }
+        }
+        let extra = ["--alias", "Alice=Team", "--alias", "Bob=Team", "--max-commits-per-day", "2"];
+        let Analysis::Done { metrics, .. } = analyze(&cli_for(&repo, &extra), &NoProgress) else {
+            panic!("expected commits");
+        };
+        assert_eq!(metrics.suspect_identities.len(), 1);
+        assert_eq!(metrics.suspect_identities[0].name, "Team");
+        assert_eq!(metrics.suspect_identities[0].merged_from, ["Alice", "Bob"]);
+        assert_eq!(metrics.top_after_hours_authors[0].name, "Team");
+
+        let Analysis::Done { metrics, .. } =
+            analyze(&cli_for(&repo, &["--max-commits-per-day", "2"]), &NoProgress)
+        else {
+            panic!("expected commits");
+        };
+        assert!(metrics.suspect_identities.is_empty());
+    }
+
+    #[test]
+    fn mailmap_merges_authors_and_aliases_stack_on_top() {
+        let repo = TempRepo::new();
+        repo.commit("zs", "2026-09-01T21:00:00+08:00");
+        repo.commit("Zhang San", "2026-09-02T22:00:00+08:00");
+        repo.commit("B", "2026-09-02T10:00:00+08:00");
+        std::fs::write(
+            repo.path().join(".mailmap"),
+            "Zhang San <zhang.san@example.com> zs <zs@example.com>\n",
+        )
+        .unwrap();
+
+        let authors = |extra: &[&str]| match analyze(&cli_for(&repo, extra), &NoProgress) {
+            Analysis::Done { metrics, .. } => {
+                let mut authors: Vec<_> = metrics
+                    .authors
+                    .iter()
+                    .map(|author| (author.name.clone(), author.total_commits))
+                    .collect();
+                authors.sort();
+                authors
+            }
+            Analysis::Empty(_) => panic!("expected commits"),
+        };
+        let named = |pairs: &[(&str, usize)]| {
+            pairs.iter().map(|(name, count)| (name.to_string(), *count)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(authors(&[]), named(&[("B", 1), ("Zhang San", 2)]));
+        assert_eq!(authors(&["--no-mailmap"]), named(&[("B", 1), ("Zhang San", 1), ("zs", 1)]));
+        // Aliases see the mailmapped name.
+        assert_eq!(
+            authors(&["--alias", "Zhang San=张三"]),
+            named(&[("B", 1), ("张三", 2)])
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use chrono::NaiveDate;
+
+use crate::gitlog::Commit;
+use crate::metrics::RepoMetrics;
+
+/// Commit counts per author per calendar day, covering every date of the
+/// window (days without commits included).
+pub struct Attendance {
+    pub dates: Vec<NaiveDate>,
+    pub rows: BTreeMap<String, Vec<usize>>,
+}
+
+pub fn build_attendance(commits: &[Commit], start: NaiveDate, end: NaiveDate) -> Attendance {
+    let commit_dates = commits.iter().map(|commit| commit.timestamp.date_naive());
+    let first = commit_dates.clone().min().map_or(start, |d| d.min(start));
+    let last = commit_dates.max().map_or(end, |d| d.max(end));
+
+    let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| *d <= last).collect();
+    let mut rows: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for commit in commits {
+        let index = (commit.timestamp.date_naive() - first).num_days() as usize;
+        rows.entry(commit.author.clone())
+            .or_insert_with(|| vec![0; dates.len()])[index] += 1;
+    }
+
+    Attendance { dates, rows }
+}
+
+pub fn write_attendance_csv(attendance: &Attendance, out: &mut dyn Write) -> io::Result<()> {
+    let mut header = vec!["author".to_string()];
+    header.extend(attendance.dates.iter().map(|d| d.to_string()));
+    header.push("total".to_string());
+    writeln!(out, "{}", header.join(","))?;
+
+    let mut column_totals = vec![0usize; attendance.dates.len()];
+    for (author, counts) in &attendance.rows {
+        let mut fields = vec![csv_field(author)];
+        fields.extend(counts.iter().map(|c| c.to_string()));
+        fields.push(counts.iter().sum::<usize>().to_string());
+        writeln!(out, "{}", fields.join(","))?;
+
+        for (total, count) in column_totals.iter_mut().zip(counts) {
+            *total += count;
+        }
+    }
+
+    let mut fields = vec!["total".to_string()];
+    fields.extend(column_totals.iter().map(|c| c.to_string()));
+    fields.push(column_totals.iter().sum::<usize>().to_string());
+    writeln!(out, "{}", fields.join(","))?;
+    Ok(())
+}
+
+/// One row per author: counts and the after-hours ratio (0-1).
+pub fn write_authors_csv(metrics: &RepoMetrics, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "author,total_commits,after_hours_commits,weekend_commits,night_commits,after_hours_ratio"
+    )?;
+    for author in &metrics.authors {
+        writeln!(
+            out,
+            "{},{},{},{},{},{:.4}",
+            csv_field(&author.name),
+            author.total_commits,
+            author.after_hours_commits,
+            author.weekend_commits,
+            author.night_commits,
+            author.after_hours_ratio
+        )?;
+    }
+    Ok(())
+}
+
+/// One row per commit day, oldest first.
+pub fn write_days_csv(metrics: &RepoMetrics, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "date,total_commits,after_hours_commits,after_hours_minutes")?;
+    for day in &metrics.daily {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            day.date, day.total_commits, day.after_hours_commits, day.after_hours_minutes
+        )?;
+    }
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::metrics::{compute_metrics, DefaultClassifier, MetricsOptions};
+
+    fn commit(author: &str, timestamp: &str) -> Commit {
+        Commit {
+            hash: format!("{author}-{timestamp}"),
+            author: author.to_string(),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+            ..Commit::default()
+        }
+    }
+
+    fn commits() -> Vec<Commit> {
+        vec![
+            commit("Zhang, San", "2026-09-01T10:00:00+08:00"),
+            commit("Zhang, San", "2026-09-01T21:00:00+08:00"),
+            commit("Li \"Boss\" Si", "2026-09-03T23:30:00+08:00"),
+        ]
+    }
+
+    fn render(write: fn(&RepoMetrics, &mut dyn Write) -> io::Result<()>) -> String {
+        let metrics = compute_metrics(
+            Path::new("."),
+            &commits(),
+            Vec::new(),
+            Vec::new(),
+            &MetricsOptions { min_commits: 1, ..MetricsOptions::default() },
+            &DefaultClassifier::default(),
+        );
+        let mut out = Vec::new();
+        write(&metrics, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("Zhang San"), "Zhang San");
+        assert_eq!(csv_field("张三"), "张三");
+        assert_eq!(csv_field("Zhang, San"), "\"Zhang, San\"");
+        assert_eq!(csv_field("Li \"Boss\" Si"), "\"Li \"\"Boss\"\" Si\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn authors_csv_has_one_quoted_row_per_author_with_dot_decimals() {
+        let csv = render(write_authors_csv);
+        let mut lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines.remove(0),
+            "author,total_commits,after_hours_commits,weekend_commits,night_commits,after_hours_ratio"
+        );
+        lines.sort();
+        assert_eq!(
+            lines,
+            ["\"Li \"\"Boss\"\" Si\",1,1,0,1,1.0000", "\"Zhang, San\",2,1,0,0,0.5000"]
+        );
+    }
+
+    #[test]
+    fn days_csv_lists_commit_days_oldest_first() {
+        let csv = render(write_days_csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "date,total_commits,after_hours_commits,after_hours_minutes");
+        assert!(lines[1].starts_with("2026-09-01,2,1,"), "{csv}");
+        assert!(lines[2].starts_with("2026-09-03,1,1,"), "{csv}");
+        assert_eq!(lines.len(), 3);
+    }
+}
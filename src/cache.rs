@@ -0,0 +1,597 @@
+//! On-disk cache of raw `git log` output, one entry per repository and set
+//! of log-shaping options (`--cache`).
+//!
+//! An entry holds the whole unwindowed history as of one branch tip; any
+//! window is then cut from it client-side, as for a live read. Since git's
+//! own `--since` is never applied, a cached read does not stop at the first
+//! backdated commit the way git's walk does, and can find in-window commits
+//! behind one that a live read misses. Entries are replaced wholesale when
+//! the tip moves. Concurrent runs are serialized by
+//! a lock file next to the entry, and writes go to a temp file renamed into
+//! place, so readers never see a half-written entry. The header records the
+//! payload length and checksum anyway: a torn or foreign file fails them and
+//! is refetched instead of being parsed.
+//!
+//! Layout: a `zzh-commit-cache <version>` line, a JSON [`CacheHeader`] line,
+//! then the payload.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+use crate::gitlog::{count_records, full_log, read_log, FetchOptions, Fetched};
+use crate::progress::Progress;
+
+/// Bumped whenever the payload or header layout changes; entries written by
+/// another version are refetched.
+pub const CACHE_FORMAT_VERSION: u32 = 1;
+const MAGIC: &str = "zzh-commit-cache";
+const ENTRY_EXTENSION: &str = "cache";
+
+/// How long a run waits for another one to release an entry before reading
+/// git directly.
+const LOCK_WAIT: Duration = Duration::from_secs(30);
+const LOCK_POLL: Duration = Duration::from_millis(25);
+/// A lock file this old was left behind by a run that died.
+const LOCK_STALE: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheHeader {
+    pub version: u32,
+    /// Canonical repository path.
+    pub repo: PathBuf,
+    /// The log-shaping options the payload was read with.
+    pub key: String,
+    /// Commit hashes the read started from (`git rev-parse` of the refs).
+    pub tip: String,
+    pub commits: usize,
+    /// Payload length in bytes and its FNV-1a checksum.
+    pub length: u64,
+    pub checksum: String,
+    pub refreshed_at: DateTime<Utc>,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "查看或清理 --cache 保存的提交缓存", name = "cache")]
+pub struct CacheCli {
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// 列出缓存的仓库：路径、缓存时的最新提交、提交数、文件大小和上次刷新时间
+    Info,
+    /// 删除缓存
+    Clear {
+        /// 只删除这个仓库的缓存
+        #[arg(long, value_name = "PATH")]
+        repo: Option<PathBuf>,
+    },
+}
+
+/// One entry of the cache directory, described from its header alone.
+#[derive(Debug)]
+pub struct CacheEntry {
+    pub file: PathBuf,
+    pub size: u64,
+    pub header: CacheHeader,
+}
+
+pub fn run_cache(args: &CacheCli) -> Result<()> {
+    let dir = default_dir();
+    match &args.action {
+        CacheAction::Info => {
+            let stdout = io::stdout();
+            write_info(&dir, &entries(&dir)?, &mut stdout.lock())?;
+        }
+        CacheAction::Clear { repo } => {
+            let removed = clear(&dir, repo.as_deref())?;
+            println!("已删除 {removed} 个缓存文件（{}）", dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// `~/.cache/zzh/commits` or the platform's equivalent.
+pub fn default_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("zzh")
+        .join("commits")
+}
+
+/// Serves `options` from the cache in `dir`, filling or replacing the entry
+/// from git when it is missing, stale or damaged. `None` when the cache
+/// cannot be used this time (unborn branch, entry locked for too long); the
+/// caller then reads git as usual.
+pub fn fetch(
+    repo_path: &Path,
+    dir: &Path,
+    options: &FetchOptions<'_>,
+    progress: &Progress<'_>,
+) -> Result<Option<Fetched>> {
+    let Some(tip) = tip(repo_path, options) else {
+        return Ok(None);
+    };
+    let repo = repo_path
+        .canonicalize()
+        .unwrap_or_else(|_| repo_path.to_path_buf());
+    let key = key(options);
+    if let Err(err) = fs::create_dir_all(dir) {
+        eprintln!("警告：无法创建提交缓存目录 {}：{err}", dir.display());
+        return Ok(None);
+    }
+    let path = entry_path(dir, &repo, &key);
+    let Some(_lock) = CacheLock::acquire(&path) else {
+        if options.verbose {
+            eprintln!("{} 正被其他进程使用，本次直接读取 git", path.display());
+        }
+        return Ok(None);
+    };
+
+    if !options.refresh_cache
+        && let Some(log) = read_entry(&path, &key, &tip)
+    {
+        return read_log(log.as_slice(), repo_path, options, progress).map(Some);
+    }
+    let log = full_log(repo_path, options)?;
+    let header = CacheHeader {
+        version: CACHE_FORMAT_VERSION,
+        repo,
+        key,
+        tip,
+        commits: count_records(&log, options.with_paths)?,
+        length: log.len() as u64,
+        checksum: checksum(&log),
+        refreshed_at: Utc::now(),
+    };
+    if let Err(err) = write_entry(&path, &header, &log) {
+        eprintln!("警告：无法写入提交缓存 {}：{err:#}", path.display());
+    }
+    read_log(log.as_slice(), repo_path, options, progress).map(Some)
+}
+
+/// What the refs being read point at; `None` when they do not resolve yet.
+fn tip(repo_path: &Path, options: &FetchOptions<'_>) -> Option<String> {
+    let mut cmd = process::Command::new("git");
+    cmd.arg("-C").arg(repo_path);
+    if options.all_branches {
+        cmd.args(["for-each-ref", "--format=%(objectname)"]);
+    } else {
+        cmd.arg("rev-parse");
+        if options.branches.is_empty() && options.rev_range.is_none() {
+            cmd.arg("HEAD");
+        }
+        cmd.args(options.branches);
+        cmd.args(options.rev_range);
+    }
+    let output = cmd.stderr(process::Stdio::null()).output().ok()?;
+    let refs = String::from_utf8(output.stdout).ok()?;
+    if !output.status.success() || refs.trim().is_empty() {
+        return None;
+    }
+    let refs: Vec<&str> = refs.split_whitespace().collect();
+    Some(match refs.as_slice() {
+        [single] => single.to_string(),
+        many => format!("{} refs, {}", many.len(), checksum(many.join("\n").as_bytes())),
+    })
+}
+
+/// Everything besides the window that changes what `git log` prints.
+fn key(options: &FetchOptions<'_>) -> String {
+    format!(
+        "author={:?} paths={} mailmap={} first_parent={} all={} branches={:?} range={:?} pathspecs={:?}",
+        options.author,
+        options.with_paths,
+        options.mailmap,
+        options.first_parent,
+        options.all_branches,
+        options.branches,
+        options.rev_range,
+        options.pathspecs,
+    )
+}
+
+fn entry_path(dir: &Path, repo: &Path, key: &str) -> PathBuf {
+    let id = format!("{}\0{key}", repo.display());
+    dir.join(format!("{}.{ENTRY_EXTENSION}", checksum(id.as_bytes())))
+}
+
+/// FNV-1a, 64 bits, as hex.
+fn checksum(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Reads the magic line and header, leaving `reader` at the payload.
+fn read_header(reader: &mut impl BufRead) -> Option<CacheHeader> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    if line.trim_end() != format!("{MAGIC} {CACHE_FORMAT_VERSION}") {
+        return None;
+    }
+    line.clear();
+    reader.read_line(&mut line).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// The payload of the entry at `path` if it was written for `key` at `tip`
+/// and is intact.
+fn read_entry(path: &Path, key: &str, tip: &str) -> Option<Vec<u8>> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let header = read_header(&mut reader)?;
+    if header.version != CACHE_FORMAT_VERSION || header.key != key || header.tip != tip {
+        return None;
+    }
+    let mut payload = Vec::new();
+    reader.read_to_end(&mut payload).ok()?;
+    (payload.len() as u64 == header.length && checksum(&payload) == header.checksum)
+        .then_some(payload)
+}
+
+fn write_entry(path: &Path, header: &CacheHeader, payload: &[u8]) -> Result<()> {
+    static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let temp = path.with_extension(format!(
+        "tmp.{}.{}",
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = (|| -> io::Result<()> {
+        let mut file = File::create(&temp)?;
+        writeln!(file, "{MAGIC} {CACHE_FORMAT_VERSION}")?;
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        file.write_all(payload)?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written.with_context(|| format!("写入 {} 失败", temp.display()))
+}
+
+/// The entries in `dir` whose header this version can read, by repository.
+/// Only the header lines are read, not the payloads.
+pub fn entries(dir: &Path) -> Result<Vec<CacheEntry>> {
+    let listing = match fs::read_dir(dir) {
+        Ok(listing) => listing,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("无法读取缓存目录：{}", dir.display())),
+    };
+    let mut entries = Vec::new();
+    for file in listing {
+        let file = file?.path();
+        if file.extension().is_none_or(|extension| extension != ENTRY_EXTENSION) {
+            continue;
+        }
+        let Ok(opened) = File::open(&file) else {
+            continue;
+        };
+        let size = opened.metadata()?.len();
+        if let Some(header) = read_header(&mut BufReader::new(opened)) {
+            entries.push(CacheEntry { file, size, header });
+        }
+    }
+    entries.sort_by(|a, b| a.header.repo.cmp(&b.header.repo).then(a.header.key.cmp(&b.header.key)));
+    Ok(entries)
+}
+
+/// Removes every entry in `dir`, or only those of `repo`, and returns how
+/// many went. Entries of an older format are removed only when clearing all.
+pub fn clear(dir: &Path, repo: Option<&Path>) -> Result<usize> {
+    let targets: Vec<PathBuf> = match repo {
+        Some(repo) => {
+            let repo = repo.canonicalize().unwrap_or_else(|_| repo.to_path_buf());
+            entries(dir)?
+                .into_iter()
+                .filter(|entry| entry.header.repo == repo)
+                .map(|entry| entry.file)
+                .collect()
+        }
+        None => match fs::read_dir(dir) {
+            Ok(listing) => listing
+                .filter_map(|file| Some(file.ok()?.path()))
+                .filter(|file| file.extension().is_some_and(|extension| extension == ENTRY_EXTENSION))
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("无法读取缓存目录：{}", dir.display()));
+            }
+        },
+    };
+    for file in &targets {
+        let _lock = CacheLock::acquire(file);
+        fs::remove_file(file).with_context(|| format!("无法删除缓存文件：{}", file.display()))?;
+    }
+    Ok(targets.len())
+}
+
+pub fn write_info(dir: &Path, entries: &[CacheEntry], out: &mut dyn Write) -> io::Result<()> {
+    if entries.is_empty() {
+        return writeln!(out, "提交缓存为空（{}）", dir.display());
+    }
+    writeln!(out, "提交缓存目录：{}", dir.display())?;
+    for CacheEntry { file, size, header } in entries {
+        let tip = if header.tip.len() == 40 { &header.tip[..12] } else { &header.tip };
+        writeln!(out)?;
+        writeln!(out, "{}", header.repo.display())?;
+        writeln!(out, "  缓存至：{tip}，共 {} 条提交", header.commits)?;
+        writeln!(out, "  文件：{}（{}）", file.display(), format_size(*size))?;
+        writeln!(
+            out,
+            "  上次刷新：{}",
+            header.refreshed_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        )?;
+        writeln!(out, "  读取选项：{}", header.key)?;
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Exclusive hold on one cache entry, released on drop.
+struct CacheLock(PathBuf);
+
+impl CacheLock {
+    /// Waits up to [`LOCK_WAIT`]; `None` if the entry stays locked or the
+    /// lock file cannot be created.
+    fn acquire(entry: &Path) -> Option<CacheLock> {
+        let path = entry.with_extension("lock");
+        let deadline = Instant::now() + LOCK_WAIT;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Some(CacheLock(path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                        .is_some_and(|age| age > LOCK_STALE);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        return None;
+                    }
+                    thread::sleep(LOCK_POLL);
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::gitlog::fetch_commits;
+    use crate::metrics::{compute_metrics, DefaultClassifier, MetricsOptions};
+    use crate::progress::NoProgress;
+    use crate::testutil::TempRepo;
+
+    fn cache_dir(repo: &TempRepo) -> PathBuf {
+        repo.path().with_extension("cache")
+    }
+
+    fn fetch_json(repo: &TempRepo, dir: Option<&Path>) -> String {
+        let since = DateTime::parse_from_rfc3339("2026-09-02T00:00:00Z").unwrap();
+        let options = FetchOptions {
+            since: Some(since.with_timezone(&Utc)),
+            mailmap: true,
+            coauthors: true,
+            cache_dir: dir,
+            ..FetchOptions::default()
+        };
+        let commits = fetch_commits(repo.path(), &options, &Progress::new(&NoProgress))
+            .unwrap()
+            .commits;
+        let metrics_options = MetricsOptions {
+            min_commits: 1,
+            now: DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            ..MetricsOptions::default()
+        };
+        let metrics = compute_metrics(
+            Path::new("."),
+            &commits,
+            Vec::new(),
+            Vec::new(),
+            &metrics_options,
+            &DefaultClassifier::default(),
+        );
+        serde_json::to_string(&metrics).unwrap()
+    }
+
+    fn entry_is_intact(entry: &Path) -> bool {
+        let mut reader = BufReader::new(File::open(entry).unwrap());
+        let header = read_header(&mut reader).unwrap();
+        read_entry(entry, &header.key, &header.tip).is_some()
+    }
+
+    fn fixture() -> TempRepo {
+        let repo = TempRepo::new();
+        for day in 1..=9 {
+            repo.commit("A", &format!("2026-09-0{day}T21:00:00+08:00"));
+            repo.commit("B", &format!("2026-09-0{day}T10:00:00+08:00"));
+        }
+        repo
+    }
+
+    #[test]
+    fn concurrent_cached_runs_agree_with_git() {
+        let repo = fixture();
+        let dir = cache_dir(&repo);
+        let expected = fetch_json(&repo, None);
+
+        let results: Vec<String> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope.spawn(|| {
+                        (0..5)
+                            .map(|_| fetch_json(&repo, Some(&dir)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        assert!(results.iter().all(|result| *result == expected));
+
+        let entries: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(entries.len(), 1, "no lock or temp file is left behind: {entries:?}");
+        let mut reader = BufReader::new(File::open(&entries[0]).unwrap());
+        assert_eq!(read_header(&mut reader).unwrap().commits, 18);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn damaged_or_outdated_entries_are_refetched() {
+        let repo = fixture();
+        let dir = cache_dir(&repo);
+        let expected = fetch_json(&repo, Some(&dir));
+        let entry = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        let intact = fs::read(&entry).unwrap();
+
+        // A torn write: the payload stops short of the recorded length.
+        fs::write(&entry, &intact[..intact.len() - 40]).unwrap();
+        assert_eq!(fetch_json(&repo, Some(&dir)), expected);
+        assert!(entry_is_intact(&entry));
+
+        // Same length, flipped bytes.
+        let mut flipped = intact.clone();
+        let last = flipped.len() - 2;
+        flipped[last] ^= 0x20;
+        fs::write(&entry, &flipped).unwrap();
+        assert_eq!(fetch_json(&repo, Some(&dir)), expected);
+
+        // Written by another format version.
+        let text = String::from_utf8_lossy(&intact).replacen(
+            &format!("{MAGIC} {CACHE_FORMAT_VERSION}"),
+            &format!("{MAGIC} 0"),
+            1,
+        );
+        fs::write(&entry, text.as_bytes()).unwrap();
+        assert_eq!(fetch_json(&repo, Some(&dir)), expected);
+        assert!(entry_is_intact(&entry));
+
+        // A new commit moves the tip.
+        repo.commit("A", "2026-09-10T22:00:00+08:00");
+        assert_eq!(fetch_json(&repo, Some(&dir)), fetch_json(&repo, None));
+        let mut reader = BufReader::new(File::open(&entry).unwrap());
+        assert_eq!(read_header(&mut reader).unwrap().commits, 19);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn cached_run(repo: &TempRepo, dir: &Path, refresh_cache: bool) {
+        let options = FetchOptions {
+            cache_dir: Some(dir),
+            refresh_cache,
+            ..FetchOptions::default()
+        };
+        fetch_commits(repo.path(), &options, &Progress::new(&NoProgress)).unwrap();
+    }
+
+    #[test]
+    fn info_reports_each_repo_after_two_runs() {
+        let (first, second) = (fixture(), TempRepo::new());
+        second.commit("C", "2026-09-01T10:00:00+08:00");
+        let dir = cache_dir(&first);
+        cached_run(&first, &dir, false);
+        cached_run(&second, &dir, false);
+        second.commit("C", "2026-09-02T10:00:00+08:00");
+        cached_run(&first, &dir, false);
+        cached_run(&second, &dir, false);
+
+        let listed = entries(&dir).unwrap();
+        assert_eq!(listed.len(), 2);
+        let by_repo = |repo: &TempRepo| {
+            let repo = repo.path().canonicalize().unwrap();
+            listed.iter().find(|entry| entry.header.repo == repo).unwrap()
+        };
+        assert_eq!(by_repo(&first).header.commits, 18);
+        assert_eq!(by_repo(&second).header.commits, 2);
+        let head = |repo: &TempRepo| {
+            let output = process::Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        assert_eq!(by_repo(&second).header.tip, head(&second));
+        assert_eq!(by_repo(&second).size, fs::metadata(&by_repo(&second).file).unwrap().len());
+
+        let mut out = Vec::new();
+        write_info(&dir, &listed, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains(&format!("缓存至：{}，共 2 条提交", &head(&second)[..12])));
+        assert!(text.contains("共 18 条提交"));
+        assert_eq!(text.matches("上次刷新：").count(), 2);
+
+        let refreshed_at = |repo: &TempRepo| {
+            let repo = repo.path().canonicalize().unwrap();
+            let listed = entries(&dir).unwrap();
+            listed.into_iter().find(|entry| entry.header.repo == repo).unwrap().header.refreshed_at
+        };
+        let before = refreshed_at(&first);
+        cached_run(&first, &dir, false);
+        assert_eq!(refreshed_at(&first), before, "an unchanged tip is served from the cache");
+        cached_run(&first, &dir, true);
+        assert!(refreshed_at(&first) > before, "--refresh rewrites the entry");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clear_removes_only_the_given_repo() {
+        let (first, second) = (fixture(), fixture());
+        let dir = cache_dir(&first);
+        cached_run(&first, &dir, false);
+        cached_run(&second, &dir, false);
+
+        assert_eq!(clear(&dir, Some(first.path())).unwrap(), 1);
+        let left = entries(&dir).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].header.repo, second.path().canonicalize().unwrap());
+        assert_eq!(clear(&dir, Some(first.path())).unwrap(), 0);
+
+        cached_run(&first, &dir, false);
+        assert_eq!(clear(&dir, None).unwrap(), 2);
+        assert!(entries(&dir).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,16 +1,29 @@
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 
+use crate::compare::CompareBaseline;
+use crate::digest::DigestPeriod;
+use crate::gitlog::DateSource;
+use crate::i18n::Lang;
+use crate::metrics::{BusiestBy, RankBy, SeverityOverlap, ShowCommits, SeverityWeights, SortAuthors};
+use crate::output::{ColorChoice, Format};
+
 #[derive(Parser, Debug)]
 #[command(
     name = "cow-horse",
     about = "根据 Git 提交历史衡量一个仓库的“牛马”程度。"
 )]
 pub struct Cli {
-    /// Path to the git repository to inspect
-    #[arg(long, default_value = ".", value_name = "PATH")]
-    pub path: PathBuf,
+    /// Path to the git repository to inspect; repeat to aggregate several repositories [default: .]
+    #[arg(long, value_name = "PATH")]
+    pub path: Vec<PathBuf>,
+
+    /// Also analyze the repositories listed in this file, one path per line (# starts a comment; relative paths are relative to the file)
+    #[arg(long, value_name = "FILE")]
+    pub paths_file: Option<PathBuf>,
 
     /// Only include commits after this instant (e.g. 2023-01-01 or 30d for 30 days ago)
     #[arg(long, value_name = "SINCE")]
@@ -20,31 +33,510 @@ pub struct Cli {
     #[arg(long, value_name = "UNTIL")]
     pub until: Option<String>,
 
-    /// Default rolling window (in days) when --since is omitted
-    #[arg(long, default_value_t = 90, value_name = "DAYS")]
-    pub window_days: u32,
+    /// Rolling window (in days) used when --since is omitted [default: 90; 0 = all history]
+    #[arg(long, value_name = "DAYS")]
+    pub window_days: Option<u32>,
+
+    /// Analyze the whole history (same as --window-days 0)
+    #[arg(long, conflicts_with_all = ["window_days", "since"])]
+    pub all_history: bool,
+
+    /// Analyze a revision range such as v1.2.0..v1.3.0 instead of a date window
+    #[arg(
+        long,
+        value_name = "RANGE",
+        conflicts_with_all = ["since", "until", "window_days", "all_history", "digest", "forecast", "since_tag", "branch", "all_branches"]
+    )]
+    pub rev_range: Option<String>,
+
+    /// Analyze everything since a tag (same as --rev-range TAG..HEAD)
+    #[arg(
+        long,
+        value_name = "TAG",
+        conflicts_with_all = ["since", "until", "window_days", "all_history", "digest", "forecast", "branch", "all_branches"]
+    )]
+    pub since_tag: Option<String>,
+
+    /// Print a ready-to-post digest of the previous ISO week or calendar month
+    #[arg(long, value_enum, value_name = "PERIOD", conflicts_with_all = ["since", "until", "window_days", "all_history"])]
+    pub digest: Option<DigestPeriod>,
+
+    /// Compare against a baseline window; `previous` is the equal-length window just before this one
+    #[arg(
+        long,
+        value_enum,
+        value_name = "BASELINE",
+        conflicts_with_all = ["compare_since", "digest", "rev_range", "since_tag", "all_history"]
+    )]
+    pub compare: Option<CompareBaseline>,
+
+    /// Start of an explicit baseline window to compare against (same syntax as --since)
+    #[arg(long, value_name = "SINCE", conflicts_with_all = ["digest", "rev_range", "since_tag"])]
+    pub compare_since: Option<String>,
+
+    /// End of the baseline window [default: start of the analyzed window]
+    #[arg(long, value_name = "UNTIL", requires = "compare_since")]
+    pub compare_until: Option<String>,
 
     /// Filter commits by author substring (passed through to git)
-    #[arg(long, value_name = "AUTHOR")]
+    #[arg(long, value_name = "AUTHOR", allow_hyphen_values = true)]
     pub author: Option<String>,
 
-    /// Limit the number of commits to read (useful for massive histories)
+    /// Only analyze commits with exactly this author email (case-insensitive)
+    #[arg(long, value_name = "EMAIL")]
+    pub author_email: Option<String>,
+
+    /// Report on one author (name after aliases) instead of the whole repository; with several --path, merges their commits across them. Human or JSON only
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["compare", "compare_since", "digest"]
+    )]
+    pub author_report: Option<String>,
+
+    /// Limit the number of commits to analyze, counted inside the window (useful for massive histories)
     #[arg(long, value_name = "COMMITS")]
     pub limit: Option<usize>,
 
-    /// Output JSON instead of the human summary
+    /// Stop reading history after this many consecutive commits older than the window (0 = never)
+    #[arg(long, default_value_t = 100, value_name = "COMMITS")]
+    pub early_stop_slack: usize,
+
+    /// Retry `git log` this many times on transient failures (lock files, I/O hiccups)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub git_retries: u32,
+
+    /// Keep the raw git history in a local cache and reuse it while the branch tip is unchanged; windows are still applied exactly
+    #[arg(long)]
+    pub cache: bool,
+
+    /// Re-read the git history even if the commit cache has it, and store the fresh copy (implies --cache)
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Suppress the one-line `cow-horse: ...` status summary on stderr
+    #[arg(long, short = 'q')]
+    pub quiet: bool,
+
+    /// Print diagnostics (e.g. how many commits were read) to stderr
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    /// Also analyze initialized submodules listed in .gitmodules
+    #[arg(long)]
+    pub include_submodules: bool,
+
+    /// How many levels of nested submodules to follow
+    #[arg(long, default_value_t = 1, value_name = "LEVELS", requires = "include_submodules")]
+    pub submodule_depth: u32,
+
+    /// Attribute each commit to the directory (at this depth) holding most of its changed files
+    #[arg(long = "group-by-dir", value_name = "DEPTH", value_parser = clap::value_parser!(u32).range(1..))]
+    pub group_by_dir: Option<u32>,
+
+    /// Seed for the encouragement line so output stays deterministic
+    #[arg(long, value_name = "SEED")]
+    pub seed: Option<u64>,
+
+    /// Leaderboard ordering: raw after-hours ratio, or a sample-size aware composite score
+    #[arg(long, value_enum, default_value_t = RankBy::Ratio)]
+    pub rank_by: RankBy,
+
+    /// Leave authors with fewer commits than this out of the leaderboards; they still count toward the totals
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub min_commits: usize,
+
+    /// How many authors each leaderboard shows
+    #[arg(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub top: u32,
+
+    /// Also print a table of every author
+    #[arg(long)]
+    pub all_authors: bool,
+
+    /// Ordering of the full author table (--all-authors, JSON `authors`, --csv-authors)
+    #[arg(long, value_enum, default_value_t = SortAuthors::Name)]
+    pub sort_authors: SortAuthors,
+
+    /// Severity weights, e.g. after=30,weekend=40,night=15,overtime-days=10,streak=5; omitted terms keep their default and the total is scaled to 100
+    #[arg(long, value_name = "WEIGHTS", default_value = "after=40,weekend=20,night=20,overtime-days=10,streak=10", value_parser = SeverityWeights::parse)]
+    pub weights: SeverityWeights,
+
+    /// Start of the working day, HH:MM in each commit's local time [default: 10:00]
+    #[arg(long, value_name = "HH:MM")]
+    pub work_start: Option<String>,
+
+    /// End of the working day, HH:MM; commits from this minute on are after hours [default: 18:00]
+    #[arg(long, value_name = "HH:MM")]
+    pub work_end: Option<String>,
+
+    /// Read this branch or ref instead of HEAD (can repeat)
+    #[arg(long, value_name = "REF", conflicts_with = "all_branches")]
+    pub branch: Vec<String>,
+
+    /// Read every branch and ref (`git log --all`); commits on several branches count once
+    #[arg(long)]
+    pub all_branches: bool,
+
+    /// Timestamp to analyze: author date (default) or committer date
+    #[arg(long, value_enum, default_value_t = DateSource::Author)]
+    pub date_source: DateSource,
+
+    /// Warn when commits' author and committer dates differ by more than an hour (rewritten history)
+    #[arg(long)]
+    pub warn_rewritten: bool,
+
+    /// Only count commits touching these paths, e.g. services/payments (can repeat)
+    #[arg(long, value_name = "PATH")]
+    pub pathspec: Vec<String>,
+
+    /// Ignore Co-authored-by trailers; by default each co-author is credited with the commit
+    #[arg(long)]
+    pub no_coauthors: bool,
+
+    /// Leave merge commits out of the analysis; the report says how many were dropped
+    #[arg(long)]
+    pub no_merges: bool,
+
+    /// Only follow the first parent of merges, like `git log --first-parent`
+    #[arg(long)]
+    pub first_parent: bool,
+
+    /// Use raw author names instead of resolving them through the repo's .mailmap
+    #[arg(long)]
+    pub no_mailmap: bool,
+
+    /// Judge every commit in this IANA time zone (e.g. Asia/Shanghai) instead of its own author offset
+    #[arg(long, value_name = "ZONE")]
+    pub timezone: Option<String>,
+
+    /// Start of the night window, HH:MM; must be later in the day than --night-end [default: 23:00]
+    #[arg(long, value_name = "HH:MM")]
+    pub night_start: Option<String>,
+
+    /// End of the night window (exclusive), HH:MM the next morning [default: 06:00]
+    #[arg(long, value_name = "HH:MM")]
+    pub night_end: Option<String>,
+
+    /// Weekend days as a comma list, e.g. `fri,sat`; `none` disables weekend accounting [default: sat,sun]
+    #[arg(long, value_name = "DAYS")]
+    pub weekend: Option<String>,
+
+    /// Holiday calendar: one YYYY-MM-DD per line counts as a weekend day, !YYYY-MM-DD marks a makeup workday (调休), # starts a comment
+    #[arg(long, value_name = "FILE")]
+    pub holidays: Option<PathBuf>,
+
+    /// Built-in holiday calendar (repeatable): cn-2023, cn-2024, cn-2025; a --holidays file overrides it per date
+    #[arg(long, value_name = "PRESET")]
+    pub holiday_preset: Vec<String>,
+
+    /// How commits in several buckets feed the severity score: `independent`
+    /// counts a Saturday-night commit as night, weekend and after hours;
+    /// `deduplicated` counts each commit once, in the first of night >
+    /// weekend/holiday > after hours. Raw counts are reported either way
+    #[arg(long, value_enum, default_value_t = SeverityOverlap::Independent)]
+    pub severity_overlap: SeverityOverlap,
+
+    /// Only count a day as overtime once its estimated after-hours minutes reach this
+    #[arg(long, value_name = "MINUTES", default_value_t = 0)]
+    pub overtime_day_min_minutes: i64,
+
+    /// Days whose first-to-last commit span exceeds this many hours count as long days
+    #[arg(long, value_name = "HOURS", default_value_t = 10.0)]
+    pub long_day_hours: f64,
+
+    /// After-hours ratio (0-1) above which a busier-than-usual 14-day window counts as a crunch period
+    #[arg(long, value_name = "RATIO", default_value_t = 0.4)]
+    pub crunch_threshold: f64,
+
+    /// Days in a row without commits that count as a break in the per-author breaks section
+    #[arg(long, value_name = "DAYS", default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    pub vacation_days: u32,
+
+    /// Commits less than this many minutes apart count as one work session when estimating overtime hours
+    #[arg(long, value_name = "MINUTES", default_value_t = 60, value_parser = clap::value_parser!(u32).range(1..))]
+    pub session_gap: u32,
+
+    /// Minutes of work assumed before the first commit of each session
+    #[arg(long, value_name = "MINUTES", default_value_t = 30)]
+    pub session_padding: u32,
+
+    /// Pick the busiest day by commit count or by estimated after-hours minutes
+    #[arg(long, value_enum, default_value_t = BusiestBy::Commits)]
+    pub busiest_by: BusiestBy,
+
+    /// Do not print an encouragement line after the severity score
+    #[arg(long)]
+    pub no_quips: bool,
+
+    /// List the matching commits (short hash, time, author, subject) after the report
+    #[arg(long, value_enum, value_name = "KIND")]
+    pub show_commits: Option<ShowCommits>,
+
+    /// How many commits --show-commits lists, newest first
+    #[arg(long, value_name = "COMMITS", default_value_t = 20, requires = "show_commits")]
+    pub show_limit: usize,
+
+    /// Include per-event details (e.g. every all-nighter) in the JSON output
+    #[arg(long)]
+    pub detailed_authors: bool,
+
+    /// Deprecated: same as --format json
     #[arg(long)]
     pub json: bool,
 
+    /// Report layout: human, json, csv (one row per author), markdown, html (a self-contained page), or badge (shields.io endpoint JSON)
+    #[arg(long, value_enum, default_value_t = Format::Human, conflicts_with = "json")]
+    pub format: Format,
+
+    /// Language of the human-readable report; JSON field names stay the same
+    #[arg(long, value_enum, env = "COW_HORSE_LANG", default_value_t = Lang::Zh)]
+    pub lang: Lang,
+
+    /// Color the human report: always, auto (terminal only, honoring NO_COLOR) or never
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Write the report to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// On failure print a JSON error object ({"error": {"code", "message", "path"}}) to stdout
+    #[arg(long)]
+    pub json_errors: bool,
+
+    /// Also write the JSON report to this file (composes with the stdout output)
+    #[arg(long = "json-file", value_name = "FILE")]
+    pub json_file: Option<PathBuf>,
+
+    /// Write an author x date commit-count matrix as CSV
+    #[arg(long = "attendance-csv", value_name = "FILE")]
+    pub attendance_csv: Option<PathBuf>,
+
+    /// Write per-author counts and after-hours ratio as CSV
+    #[arg(long = "csv-authors", value_name = "FILE")]
+    pub csv_authors: Option<PathBuf>,
+
+    /// Write per-day commit and after-hours counts as CSV
+    #[arg(long = "csv-days", value_name = "FILE")]
+    pub csv_days: Option<PathBuf>,
+
+    /// Allow --attendance-csv for windows longer than a year (the file gets wide)
+    #[arg(long, requires = "attendance_csv")]
+    pub allow_wide_attendance: bool,
+
+    /// Policy rules checked against the ratios, e.g. after_hours<=0.10,weekend<=0.05
+    #[arg(long, value_name = "RULES")]
+    pub policy: Option<String>,
+
+    /// Exit with code 2 when any --policy rule fails
+    #[arg(long, requires = "policy")]
+    pub fail_on_policy: bool,
+
+    /// Exit with code 2 (after printing the report) when the severity score is above this
+    #[arg(long, value_name = "SCORE")]
+    pub fail_above: Option<f64>,
+
+    /// Exit with code 2 when the share of after-hours commits is above this percentage (0-100)
+    #[arg(long, value_name = "PCT", value_parser = parse_percent)]
+    pub fail_after_hours_ratio: Option<f64>,
+
     /// Authors to drop from the stats (can repeat)
-    #[arg(long = "ignore-author", value_name = "AUTHOR")]
+    #[arg(long = "ignore-author", value_name = "AUTHOR", allow_hyphen_values = true)]
     pub ignore_author: Vec<String>,
 
-    /// Merge多个作者名称：格式为“旧名=统一名”，可重复
-    #[arg(long = "alias", value_name = "A=B")]
+    /// Drop authors whose name matches this regex, e.g. '^svc-' (can repeat)
+    #[arg(long, value_name = "REGEX", allow_hyphen_values = true)]
+    pub ignore_author_pattern: Vec<String>,
+
+    /// Keep commits by probable bots (`[bot]` names, well-known automation accounts); dropped by default
+    #[arg(long)]
+    pub include_bots: bool,
+
+    /// Author emails to drop from the stats, case-insensitive (can repeat)
+    #[arg(long = "ignore-email", value_name = "EMAIL")]
+    pub ignore_email: Vec<String>,
+
+    /// Show how after-hours counts and severity move for work-end boundaries 17:00-20:00
+    #[arg(long)]
+    pub sensitivity: bool,
+
+    /// Draw the window as a GitHub-style calendar of commits per day (last 53 weeks at most)
+    #[arg(long)]
+    pub heatmap: bool,
+
+    /// Project window-end counts and severity from the days observed so far
+    /// (window end: --until, or the start of next month)
+    #[arg(long, conflicts_with = "digest")]
+    pub forecast: bool,
+
+    /// Treat this instant as "now" for relative windows and streak checks (RFC3339 or YYYY-MM-DD)
+    #[arg(long, value_name = "TIME")]
+    pub now: Option<String>,
+
+    /// Warn when the newest analyzed commit is older than this (e.g. 7d, 2w)
+    #[arg(long, value_name = "DURATION")]
+    pub expect_fresh: Option<String>,
+
+    /// Turn warnings such as a failed --expect-fresh check into a non-zero exit, and stop a multi-repo run at the first repository that fails (by default it is skipped and the run exits with 6)
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Drop one author's commits within an inclusive range, e.g. "Zhang San:2024-05-01..2024-05-14" (can repeat)
+    #[arg(long, value_name = "AUTHOR:FROM..TO")]
+    pub exclude: Vec<String>,
+
+    /// Read history newest-first only until this many distinct commit days are covered
+    #[arg(long, value_name = "DAYS", conflicts_with = "limit")]
+    pub min_coverage_days: Option<usize>,
+
+    /// Warn about an aliased identity exceeding this many commits per day on two or more days
+    #[arg(long, value_name = "COMMITS", default_value_t = 50)]
+    pub max_commits_per_day: usize,
+
+    /// Print the authors ignored by default on this build and exit
+    #[arg(long)]
+    pub print_default_ignores: bool,
+
+    /// Merge多个作者名称：格式为“旧名=统一名”或“邮箱=统一名”，可重复；名字里的 = 写作 \=
+    #[arg(long = "alias", value_name = "A=B", allow_hyphen_values = true)]
     pub alias: Vec<String>,
 
+    /// Read alias rules from a file, one 旧名=统一名 per line (# starts a comment); --alias wins on the same name
+    #[arg(long, value_name = "FILE")]
+    pub alias_file: Option<PathBuf>,
+
+    /// Merge every author whose name matches a regex, as REGEX=统一名 (can repeat); applied after exact aliases
+    #[arg(long, value_name = "REGEX=NAME", allow_hyphen_values = true)]
+    pub alias_pattern: Vec<String>,
+
+    /// 配置文件（用户配置或仓库内 .cowhorse.toml）格式错误时直接报错（默认仅警告并忽略）
+    #[arg(long)]
+    pub strict_config: bool,
+
+    /// Ignore the user-level config and the repo's .cowhorse.toml
+    #[arg(long)]
+    pub no_config: bool,
+
     /// 快速转换 Unix 时间戳为可读时间（优先执行该操作）
     #[arg(long = "unix", value_name = "TIMESTAMP")]
     pub unix: Option<i64>,
 }
+
+fn parse_percent(raw: &str) -> Result<f64, String> {
+    match raw.trim().trim_end_matches('%').parse::<f64>() {
+        Ok(pct) if (0.0..=100.0).contains(&pct) => Ok(pct),
+        _ => Err(format!("需要 0 到 100 之间的百分比，当前为：{raw}")),
+    }
+}
+
+impl Cli {
+    /// `--path` flags followed by the `--paths-file` entries, duplicates
+    /// dropped with a warning; the current directory when neither is given.
+    /// Paths are compared after symlink resolution, so `/repo`, `/repo/` and
+    /// a symlink to it are one repository. A path inside another listed
+    /// path is an error, since its commits would be counted twice.
+    pub fn repo_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = self.path.clone();
+        if let Some(file) = &self.paths_file {
+            let raw = fs::read_to_string(file)
+                .with_context(|| format!("无法读取仓库列表：{}", file.display()))?;
+            let base = file.parent().unwrap_or(Path::new(""));
+            paths.extend(
+                raw.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| base.join(line)),
+            );
+        }
+        if paths.is_empty() {
+            paths.push(PathBuf::from("."));
+        }
+        // (resolved, as given), first occurrence only.
+        let mut kept: Vec<(PathBuf, PathBuf)> = Vec::new();
+        for path in paths {
+            let resolved = path.canonicalize().unwrap_or_else(|_| path.clone());
+            match kept.iter().find(|(seen, _)| *seen == resolved) {
+                Some((_, first)) => eprintln!(
+                    "警告：{} 与 {} 是同一个仓库，已忽略重复的路径",
+                    path.display(),
+                    first.display()
+                ),
+                None => kept.push((resolved, path)),
+            }
+        }
+        for (inner, inner_given) in &kept {
+            if let Some((_, outer_given)) = kept
+                .iter()
+                .find(|(outer, _)| outer != inner && inner.starts_with(outer))
+            {
+                bail!(
+                    "{} 位于同时指定的 {} 之内，两者的提交会被重复统计；请去掉其中一个路径",
+                    inner_given.display(),
+                    outer_given.display()
+                );
+            }
+        }
+        Ok(kept.into_iter().map(|(_, given)| given).collect())
+    }
+
+    /// The repository of a run that only supports one.
+    pub fn single_repo(&self, feature: &str) -> Result<PathBuf> {
+        match self.repo_paths()?.as_slice() {
+            [path] => Ok(path.clone()),
+            _ => bail!("{feature} 只支持分析单个仓库"),
+        }
+    }
+
+    /// `--format`, with the deprecated `--json` mapped onto it.
+    pub fn report_format(&self) -> Format {
+        if self.json {
+            Format::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempRepo;
+
+    fn cli_with_paths(paths: &[&Path]) -> Cli {
+        let mut args = vec!["zzh".to_string()];
+        for path in paths {
+            args.push("--path".to_string());
+            args.push(path.display().to_string());
+        }
+        Cli::parse_from(args)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn duplicate_paths_collapse_after_symlink_resolution() {
+        let first = TempRepo::new();
+        let second = TempRepo::new();
+        let link = first.path().with_extension("link");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(first.path(), &link).unwrap();
+        let trailing = PathBuf::from(format!("{}/", first.path().display()));
+
+        let cli = cli_with_paths(&[first.path(), &trailing, &link, second.path()]);
+        let paths = cli.repo_paths();
+        let _ = fs::remove_file(&link);
+        assert_eq!(paths.unwrap(), [first.path(), second.path()]);
+    }
+
+    #[test]
+    fn nested_roots_are_rejected() {
+        let outer = TempRepo::new();
+        let inner = outer.path().join("vendor");
+        fs::create_dir_all(&inner).unwrap();
+
+        let err = cli_with_paths(&[outer.path(), &inner]).repo_paths().unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains(&inner.display().to_string()), "{message}");
+        assert!(message.contains(&outer.path().display().to_string()), "{message}");
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::analysis::{describe_window, Window};
+use crate::cli::Cli;
+use crate::metrics::{percentage, RepoMetrics};
+use crate::time_filter::parse_time_filter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompareBaseline {
+    /// The equal-length window ending where the analyzed one starts.
+    Previous,
+}
+
+/// `--compare previous` or `--compare-since/--compare-until`, resolved
+/// against the already resolved analysis window.
+pub fn baseline_window(
+    cli: &Cli,
+    current: &Window,
+    now: DateTime<Utc>,
+) -> Result<Option<Window>> {
+    let (since, until) = if let Some(raw) = &cli.compare_since {
+        let since = parse_time_filter(raw, now)?;
+        let until = match &cli.compare_until {
+            Some(raw) => parse_time_filter(raw, now)?,
+            None => current.since.unwrap_or(now),
+        };
+        if since >= until {
+            bail!("--compare-since 必须早于基线窗口终点（--compare-until，默认为分析窗口起点）");
+        }
+        (since, until)
+    } else if cli.compare.is_some() {
+        let Some(start) = current.since else {
+            bail!("--compare previous 需要有起点的时间窗口，请指定 --since 或 --window-days");
+        };
+        let length = current.until.unwrap_or(now) - start;
+        (start - length, start)
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(Window {
+        since: Some(since),
+        until: Some(until),
+        rev_range: None,
+        description: describe_window(Some(since), Some(until), false, 0),
+    }))
+}
+
+/// `↑`, `↓`, or `→` for an unchanged value.
+pub fn trend_arrow(delta: f64) -> &'static str {
+    if delta > 0.0 {
+        "↑"
+    } else if delta < 0.0 {
+        "↓"
+    } else {
+        "→"
+    }
+}
+
+/// The analyzed window next to a baseline window; `delta` is always
+/// current minus baseline.
+#[derive(Debug, Serialize)]
+pub struct Comparison<'a> {
+    pub current: &'a RepoMetrics,
+    pub baseline: &'a RepoMetrics,
+    pub delta: ComparisonDelta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComparisonDelta {
+    pub total_commits: i64,
+    pub severity_score: f64,
+    /// Change of the share of after-hours commits (0-1 scale).
+    pub after_hours_ratio: f64,
+    /// Authors with commits in both windows, biggest ratio change first.
+    pub authors: Vec<AuthorDelta>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthorDelta {
+    pub name: String,
+    pub baseline_commits: usize,
+    pub current_commits: usize,
+    pub baseline_after_hours_ratio: f64,
+    pub current_after_hours_ratio: f64,
+    pub after_hours_ratio: f64,
+}
+
+pub fn compare<'a>(current: &'a RepoMetrics, baseline: &'a RepoMetrics) -> Comparison<'a> {
+    let before: HashMap<&str, _> = baseline
+        .authors
+        .iter()
+        .map(|author| (author.name.as_str(), author))
+        .collect();
+    let mut authors: Vec<AuthorDelta> = current
+        .authors
+        .iter()
+        .filter_map(|now| {
+            let then = before.get(now.name.as_str())?;
+            Some(AuthorDelta {
+                name: now.name.clone(),
+                baseline_commits: then.total_commits,
+                current_commits: now.total_commits,
+                baseline_after_hours_ratio: then.after_hours_ratio,
+                current_after_hours_ratio: now.after_hours_ratio,
+                after_hours_ratio: now.after_hours_ratio - then.after_hours_ratio,
+            })
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.after_hours_ratio
+            .abs()
+            .total_cmp(&a.after_hours_ratio.abs())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let ratio = |m: &RepoMetrics| percentage(m.after_hours_commits, m.total_commits);
+    Comparison {
+        current,
+        baseline,
+        delta: ComparisonDelta {
+            total_commits: current.total_commits as i64 - baseline.total_commits as i64,
+            severity_score: current.severity_score - baseline.severity_score,
+            after_hours_ratio: ratio(current) - ratio(baseline),
+            authors,
+        },
+    }
+}
+
+pub fn write_comparison(out: &mut dyn Write, comparison: &Comparison<'_>) -> io::Result<()> {
+    let Comparison {
+        current,
+        baseline,
+        delta,
+    } = comparison;
+    writeln!(out, "窗口对比")?;
+    writeln!(out, "  本期：{}", current.window_description)?;
+    writeln!(out, "  基线：{}", baseline.window_description)?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "牛马指数：{:.1} -> {:.1}（{}{:.1}）",
+        baseline.severity_score,
+        current.severity_score,
+        trend_arrow(delta.severity_score),
+        delta.severity_score.abs()
+    )?;
+    writeln!(
+        out,
+        "下班后提交占比：{:.1}% -> {:.1}%（{}{:.1} 个百分点）",
+        percentage(baseline.after_hours_commits, baseline.total_commits) * 100.0,
+        percentage(current.after_hours_commits, current.total_commits) * 100.0,
+        trend_arrow(delta.after_hours_ratio),
+        delta.after_hours_ratio.abs() * 100.0
+    )?;
+    writeln!(
+        out,
+        "提交数：{} -> {}（{}{}）",
+        baseline.total_commits,
+        current.total_commits,
+        trend_arrow(delta.total_commits as f64),
+        delta.total_commits.unsigned_abs()
+    )?;
+
+    writeln!(out)?;
+    if delta.authors.is_empty() {
+        writeln!(out, "两个窗口没有共同的作者。")?;
+        return Ok(());
+    }
+    writeln!(out, "作者变化（两个窗口都有提交）：")?;
+    for author in &delta.authors {
+        writeln!(
+            out,
+            "  {}：下班后占比 {:.1}% -> {:.1}%（{}{:.1}），提交 {} -> {}",
+            author.name,
+            author.baseline_after_hours_ratio * 100.0,
+            author.current_after_hours_ratio * 100.0,
+            trend_arrow(author.after_hours_ratio),
+            author.after_hours_ratio.abs() * 100.0,
+            author.baseline_commits,
+            author.current_commits
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+pub const REPO_CONFIG_FILE: &str = ".cowhorse.toml";
+/// Under the platform config directory, e.g. `~/.config/cow-horse/config.toml`.
+pub const USER_CONFIG_DIR: &str = "cow-horse";
+pub const USER_CONFIG_FILE: &str = "config.toml";
+
+/// Analysis defaults from a config file. Team conventions live in the
+/// repository's `.cowhorse.toml`, personal ones in the user-level file; both
+/// use this schema.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RepoConfig {
+    /// 旧名 -> 统一名
+    pub aliases: BTreeMap<String, String>,
+    pub ignore_authors: Vec<String>,
+    pub ignore_emails: Vec<String>,
+    /// e.g. "09:30-18:30"
+    pub work_hours: Option<String>,
+    /// e.g. ["fri", "sat"]
+    pub weekend_days: Option<Vec<String>>,
+    /// IANA name, e.g. "Asia/Shanghai"
+    pub timezone: Option<String>,
+    /// e.g. ["cn-2024"]
+    pub holiday_presets: Vec<String>,
+    /// Keys this version does not know; warned about, not rejected, so newer
+    /// configs still load.
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl RepoConfig {
+    /// Lays `over` on top of `self`: its scalar settings win, and lists and
+    /// aliases are combined (its aliases win on the same name).
+    fn layered(mut self, over: RepoConfig) -> RepoConfig {
+        self.aliases.extend(over.aliases);
+        self.ignore_authors.extend(over.ignore_authors);
+        self.ignore_emails.extend(over.ignore_emails);
+        RepoConfig {
+            work_hours: over.work_hours.or(self.work_hours),
+            weekend_days: over.weekend_days.or(self.weekend_days),
+            timezone: over.timezone.or(self.timezone),
+            holiday_presets: if over.holiday_presets.is_empty() {
+                self.holiday_presets
+            } else {
+                over.holiday_presets
+            },
+            ..self
+        }
+    }
+}
+
+/// The config layers that apply to one repository, merged. CLI flags win
+/// over both; the caller applies them on top.
+#[derive(Debug, Default)]
+pub struct LoadedConfig {
+    pub settings: RepoConfig,
+    pub user_path: Option<PathBuf>,
+    pub repo_path: Option<PathBuf>,
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(USER_CONFIG_DIR).join(USER_CONFIG_FILE))
+}
+
+/// Reads the user-level config and the repo's `.cowhorse.toml`, repo first
+/// in precedence. `disabled` (`--no-config`) skips both.
+pub fn load_config(repo_path: &Path, strict: bool, disabled: bool) -> Result<LoadedConfig> {
+    let mut loaded = LoadedConfig::default();
+    if disabled {
+        return Ok(loaded);
+    }
+    if let Some(path) = user_config_path()
+        && let Some(config) = read_config(&path, "用户配置", strict)?
+    {
+        loaded.settings = config;
+        loaded.user_path = Some(path);
+    }
+    let path = repo_path.join(REPO_CONFIG_FILE);
+    if let Some(config) = read_config(&path, "仓库配置", strict)? {
+        loaded.settings = std::mem::take(&mut loaded.settings).layered(config);
+        loaded.repo_path = Some(path);
+    }
+    Ok(loaded)
+}
+
+/// A broken file is reported as a warning and skipped so one bad commit does
+/// not break everyone's report; `strict` turns that into an error.
+fn read_config(path: &Path, kind: &str, strict: bool) -> Result<Option<RepoConfig>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let parsed = fs::read_to_string(path)
+        .with_context(|| format!("无法读取{kind}：{}", path.display()))
+        .and_then(|data| {
+            toml::from_str::<RepoConfig>(&data)
+                .with_context(|| format!("{kind}格式错误：{}", path.display()))
+        });
+
+    match parsed {
+        Ok(config) => {
+            if !config.unknown.is_empty() {
+                let keys: Vec<&str> = config.unknown.keys().map(String::as_str).collect();
+                eprintln!(
+                    "警告：{kind} {} 中有无法识别的配置项：{}，已忽略",
+                    path.display(),
+                    keys.join(", ")
+                );
+            }
+            Ok(Some(config))
+        }
+        Err(err) if strict => Err(err),
+        Err(err) => {
+            eprintln!("警告：{err:#}，已忽略该文件（使用 --strict-config 可改为报错）");
+            Ok(None)
+        }
+    }
+}
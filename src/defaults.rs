@@ -0,0 +1,115 @@
+use std::env::consts::OS;
+
+use crate::gitlog::Commit;
+
+/// Service accounts that commit on behalf of automation, keyed by the
+/// platform this binary was built for (`None` applies everywhere). Commits
+/// by these names are dropped unless the run says otherwise.
+const DEFAULT_IGNORES: &[(Option<&str>, &str)] = &[
+    // Windows BITS transfer account; its commits show up in mirrored repos
+    // regardless of where the analysis runs.
+    (None, "BitsAdmin"),
+    (Some("linux"), "root"),
+    (Some("linux"), "ubuntu"),
+];
+
+/// Automation accounts whose names carry no `[bot]` suffix, compared
+/// case-insensitively.
+const KNOWN_BOTS: &[&str] = &[
+    "dependabot",
+    "dependabot-preview",
+    "renovate",
+    "renovate-bot",
+    "github-actions",
+    "greenkeeper",
+    "snyk-bot",
+    "pre-commit-ci",
+    "semantic-release-bot",
+    "allcontributors",
+    "gitlab-bot",
+    "copilot",
+];
+
+/// Whether the commit looks automated: the author name ends in `[bot]`
+/// (GitHub apps), is a well-known bot account, or the email is a GitHub
+/// no-reply address of a bot app (`123+name[bot]@users.noreply.github.com`).
+pub fn is_probable_bot(commit: &Commit) -> bool {
+    let name = commit.author.trim().to_lowercase();
+    if name.ends_with("[bot]") || KNOWN_BOTS.contains(&name.as_str()) {
+        return true;
+    }
+    commit
+        .email
+        .strip_suffix("@users.noreply.github.com")
+        .is_some_and(|local| local.ends_with("[bot]"))
+}
+
+/// Extra names baked in at build time, comma separated, e.g.
+/// `COWHORSE_DEFAULT_IGNORES="ci-bot,jenkins" cargo build`.
+const EXTRA_IGNORES: Option<&str> = option_env!("COWHORSE_DEFAULT_IGNORES");
+
+/// The default ignore list active on this build, in table order.
+pub fn default_ignored_authors() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = DEFAULT_IGNORES
+        .iter()
+        .filter(|(platform, _)| platform.is_none_or(|p| p == OS))
+        .map(|(_, name)| *name)
+        .collect();
+    for name in EXTRA_IGNORES.unwrap_or_default().split(',') {
+        let name = name.trim();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+pub fn print_default_ignores() {
+    println!("当前构建（{OS}）默认忽略的作者：");
+    for name in default_ignored_authors() {
+        println!("  {name}");
+    }
+    if EXTRA_IGNORES.is_some() {
+        println!("（含编译时 COWHORSE_DEFAULT_IGNORES 追加的名称）");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn by(author: &str, email: &str) -> Commit {
+        Commit {
+            author: author.to_string(),
+            email: email.to_string(),
+            ..Commit::default()
+        }
+    }
+
+    #[test]
+    fn bot_suffixes_known_names_and_bot_noreply_emails_are_bots() {
+        for commit in [
+            by("dependabot[bot]", "49699333+dependabot[bot]@users.noreply.github.com"),
+            by("my-app[BOT]", "ci@example.com"),
+            by("Renovate", "renovate@whitesourcesoftware.com"),
+            by(" github-actions ", "actions@github.com"),
+            by("Deploy", "41898282+github-actions[bot]@users.noreply.github.com"),
+        ] {
+            assert!(is_probable_bot(&commit), "{} <{}>", commit.author, commit.email);
+        }
+    }
+
+    #[test]
+    fn people_with_botlike_names_or_noreply_emails_are_not() {
+        for commit in [
+            by("Zhang San", "1234+zhangsan@users.noreply.github.com"),
+            by("Abbott", "abbott@example.com"),
+            by("robot lover", "robot@example.com"),
+            by("renovate-team", "team@example.com"),
+            by("bot", "bot@example.com"),
+            by("Li Si", "li[bot]@example.com"),
+        ] {
+            assert!(!is_probable_bot(&commit), "{} <{}>", commit.author, commit.email);
+        }
+    }
+}
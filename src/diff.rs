@@ -0,0 +1,134 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+
+use crate::compare::trend_arrow;
+use crate::metrics::{percentage, severity_band, RepoMetrics, ATTRIBUTION_OTHERS};
+
+#[derive(Parser, Debug)]
+#[command(about = "比较两份保存下来的 JSON 报告（--format json 的输出）", name = "diff")]
+pub struct DiffCli {
+    /// 较早的一份报告
+    #[arg(value_name = "OLD")]
+    pub old: PathBuf,
+
+    /// 较新的一份报告
+    #[arg(value_name = "NEW")]
+    pub new: PathBuf,
+
+    /// 两份报告来自不同仓库时也照样比较
+    #[arg(long)]
+    pub force: bool,
+
+    /// 牛马指数上升超过这么多分时以退出码 2 结束
+    #[arg(long, value_name = "POINTS")]
+    pub fail_if_worse_by: Option<f64>,
+}
+
+/// Prints the differences and returns whether the score rose past
+/// `--fail-if-worse-by`.
+pub fn run_diff(args: &DiffCli) -> Result<bool> {
+    let old = load_report(&args.old)?;
+    let new = load_report(&args.new)?;
+    if old.repo_path != new.repo_path && !args.force {
+        bail!(
+            "两份报告来自不同的仓库（{} 与 {}），如确需比较请加上 --force",
+            old.repo_path.display(),
+            new.repo_path.display()
+        );
+    }
+    if old.severity_weights != new.severity_weights && !args.force {
+        bail!(
+            "两份报告的牛马指数权重不同（{} 与 {}），分数不可直接比较，如确需比较请加上 --force",
+            old.severity_weights.describe(),
+            new.severity_weights.describe()
+        );
+    }
+
+    let stdout = io::stdout();
+    write_diff(&mut stdout.lock(), &old, &new)?;
+
+    let worse_by = new.severity_score - old.severity_score;
+    Ok(args.fail_if_worse_by.is_some_and(|limit| worse_by > limit))
+}
+
+fn load_report(path: &Path) -> Result<RepoMetrics> {
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("无法读取报告：{}", path.display()))?;
+    let mut metrics: RepoMetrics = serde_json::from_str(&raw)
+        .with_context(|| format!("无法解析报告（需要 --format json 的输出）：{}", path.display()))?;
+    // The band's index and label are not serialized.
+    metrics.severity_band = severity_band(metrics.severity_score);
+    Ok(metrics)
+}
+
+/// Every author name a report mentions. Reports only list the leaderboards
+/// and attribution, so authors below both can be missed.
+fn author_names(metrics: &RepoMetrics) -> BTreeSet<&str> {
+    metrics
+        .authors
+        .iter()
+        .chain(&metrics.top_after_hours_authors)
+        .chain(&metrics.chill_authors)
+        .map(|author| author.name.as_str())
+        .chain(metrics.attribution.iter().map(|entry| entry.author.as_str()))
+        .filter(|name| *name != ATTRIBUTION_OTHERS)
+        .collect()
+}
+
+pub fn write_diff(out: &mut dyn Write, old: &RepoMetrics, new: &RepoMetrics) -> io::Result<()> {
+    writeln!(out, "报告对比")?;
+    writeln!(out, "  旧：{}（{}）", old.window_description, old.repo_path.display())?;
+    writeln!(out, "  新：{}（{}）", new.window_description, new.repo_path.display())?;
+    writeln!(out)?;
+
+    let score = new.severity_score - old.severity_score;
+    writeln!(
+        out,
+        "牛马指数：{:.1} -> {:.1}（{}{:.1}），{} -> {}",
+        old.severity_score,
+        new.severity_score,
+        trend_arrow(score),
+        score.abs(),
+        old.severity_label,
+        new.severity_label
+    )?;
+    let old_ratio = percentage(old.after_hours_commits, old.total_commits);
+    let new_ratio = percentage(new.after_hours_commits, new.total_commits);
+    writeln!(
+        out,
+        "下班后提交占比：{:.1}% -> {:.1}%（{}{:.1} 个百分点）",
+        old_ratio * 100.0,
+        new_ratio * 100.0,
+        trend_arrow(new_ratio - old_ratio),
+        (new_ratio - old_ratio).abs() * 100.0
+    )?;
+    writeln!(out, "提交数：{} -> {}", old.total_commits, new.total_commits)?;
+    writeln!(
+        out,
+        "最长连续工作：{} 天 -> {} 天",
+        old.longest_streak_days, new.longest_streak_days
+    )?;
+    writeln!(
+        out,
+        "当前连续工作：{} 天 -> {} 天",
+        old.current_streak_days, new.current_streak_days
+    )?;
+
+    let before = author_names(old);
+    let after = author_names(new);
+    let joined = |names: Vec<&str>| names.join("、");
+    let added: Vec<&str> = after.difference(&before).copied().collect();
+    let missing: Vec<&str> = before.difference(&after).copied().collect();
+    if !added.is_empty() {
+        writeln!(out, "新出现的作者：{}", joined(added))?;
+    }
+    if !missing.is_empty() {
+        writeln!(out, "不再出现的作者：{}", joined(missing))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use clap::ValueEnum;
+
+use crate::analysis::Window;
+use crate::compare::trend_arrow;
+use crate::i18n::Lang;
+use crate::metrics::{percentage, RepoMetrics};
+use crate::report::band_text;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DigestPeriod {
+    Weekly,
+    Monthly,
+}
+
+/// A digest period plus the one before it, for the delta.
+pub struct DigestWindows {
+    pub label: String,
+    pub current: Window,
+    pub previous: Window,
+}
+
+/// The last complete ISO week (or calendar month) before `now`, in local time.
+pub fn digest_windows(period: DigestPeriod, now: DateTime<Local>) -> DigestWindows {
+    let today = now.date_naive();
+    let (start, end, before) = match period {
+        DigestPeriod::Weekly => {
+            let this_monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            let start = this_monday - Duration::days(7);
+            (start, this_monday, start - Duration::days(7))
+        }
+        DigestPeriod::Monthly => {
+            let this_month = first_of_month(today);
+            let start = first_of_month(this_month - Duration::days(1));
+            (start, this_month, first_of_month(start - Duration::days(1)))
+        }
+    };
+
+    let label = match period {
+        DigestPeriod::Weekly => {
+            let week = start.iso_week();
+            format!(
+                "{}-W{:02}（{} ~ {}）",
+                week.year(),
+                week.week(),
+                start.format("%m-%d"),
+                (end - Duration::days(1)).format("%m-%d")
+            )
+        }
+        DigestPeriod::Monthly => start.format("%Y-%m").to_string(),
+    };
+
+    DigestWindows {
+        label,
+        current: local_window(start, end),
+        previous: local_window(before, start),
+    }
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn local_window(start: NaiveDate, end: NaiveDate) -> Window {
+    let to_utc = |date: NaiveDate| -> DateTime<Utc> {
+        let naive = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map_or_else(|| Utc.from_utc_datetime(&naive), |dt| dt.with_timezone(&Utc))
+    };
+    Window {
+        since: Some(to_utc(start)),
+        until: Some(to_utc(end)),
+        rev_range: None,
+        description: format!("{} ~ {}", start, end - Duration::days(1)),
+    }
+}
+
+/// Picks up to three facts worth posting, in a fixed order of priority:
+/// the ratio that moved most versus the previous period, all-nighters, and
+/// the longest streak.
+pub fn notable_facts(current: &RepoMetrics, previous: Option<&RepoMetrics>) -> Vec<String> {
+    let mut facts = Vec::new();
+
+    if let Some(previous) = previous {
+        let ratios = |m: &RepoMetrics| {
+            [
+                ("下班后提交占比", percentage(m.after_hours_commits, m.total_commits)),
+                ("周末提交占比", percentage(m.weekend_commits, m.total_commits)),
+                ("深夜提交占比", percentage(m.night_commits, m.total_commits)),
+            ]
+        };
+        let biggest = ratios(current)
+            .into_iter()
+            .zip(ratios(previous))
+            .map(|((name, now), (_, before))| (name, now, now - before))
+            .max_by(|a, b| a.2.abs().partial_cmp(&b.2.abs()).unwrap_or(std::cmp::Ordering::Equal));
+        if let Some((name, now, delta)) = biggest
+            && delta.abs() >= 0.005
+        {
+            facts.push(format!(
+                "{name} {:.1}%，{} {:.1} 个百分点",
+                now * 100.0,
+                if delta > 0.0 { "上升" } else { "下降" },
+                delta.abs() * 100.0
+            ));
+        }
+    }
+
+    if let Some(event) = &current.worst_all_nighter {
+        facts.push(format!(
+            "出现 {} 次通宵，最长一次是 {} 的 {:.1} 小时",
+            current.all_nighters,
+            event.author,
+            event.duration_minutes as f64 / 60.0
+        ));
+    }
+
+    if current.longest_streak_days > 1 {
+        facts.push(format!("最长连续工作 {} 天", current.longest_streak_days));
+    }
+
+    facts.truncate(3);
+    facts
+}
+
+pub fn write_digest(
+    out: &mut dyn Write,
+    period: DigestPeriod,
+    label: &str,
+    current: &RepoMetrics,
+    previous: Option<&RepoMetrics>,
+) -> io::Result<()> {
+    let title = match period {
+        DigestPeriod::Weekly => "牛马周报",
+        DigestPeriod::Monthly => "牛马月报",
+    };
+    let unit = match period {
+        DigestPeriod::Weekly => "上周",
+        DigestPeriod::Monthly => "上月",
+    };
+    writeln!(out, "## {title}：{label}")?;
+    writeln!(out)?;
+
+    let delta = match previous {
+        Some(previous) => {
+            let delta = current.severity_score - previous.severity_score;
+            format!("（较{unit} {}{:.1}）", trend_arrow(delta), delta.abs())
+        }
+        None => format!("（{unit}无提交）"),
+    };
+    writeln!(
+        out,
+        "**牛马指数 {:.1}**{delta} -> {}，共 {} 次提交",
+        current.severity_score,
+        band_text(current, Lang::Zh),
+        current.total_commits
+    )?;
+
+    let facts = notable_facts(current, previous);
+    if !facts.is_empty() {
+        writeln!(out)?;
+        for fact in facts {
+            writeln!(out, "- {fact}")?;
+        }
+    }
+
+    if let Some(quip) = &current.quip {
+        writeln!(out)?;
+        writeln!(out, "> {quip}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::{DateTime, FixedOffset};
+
+    use super::*;
+    use crate::metrics::{compute_metrics, severity_band, AllNighterEvent, DefaultClassifier, MetricsOptions};
+
+    fn local(date: &str) -> DateTime<Local> {
+        let naive = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        Local
+            .from_local_datetime(&naive.and_hms_opt(12, 0, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    /// Local dates of the window's start and (exclusive) end.
+    fn bounds(window: &Window) -> (String, String) {
+        let date = |at: Option<DateTime<Utc>>| at.unwrap().with_timezone(&Local).date_naive().to_string();
+        (date(window.since), date(window.until))
+    }
+
+    /// `total` commits of which `after_hours` after hours, scoring `score`.
+    fn metrics(total: usize, after_hours: usize, score: f64) -> RepoMetrics {
+        let mut metrics = compute_metrics(
+            Path::new("."),
+            &[],
+            Vec::new(),
+            Vec::new(),
+            &MetricsOptions::default(),
+            &DefaultClassifier::default(),
+        );
+        metrics.total_commits = total;
+        metrics.after_hours_commits = after_hours;
+        metrics.severity_score = score;
+        metrics.severity_band = severity_band(score);
+        metrics
+    }
+
+    fn all_nighter(author: &str, minutes: i64) -> AllNighterEvent {
+        let start = DateTime::<FixedOffset>::parse_from_rfc3339("2026-10-07T23:00:00+08:00").unwrap();
+        AllNighterEvent {
+            author: author.to_string(),
+            start,
+            end: start + Duration::minutes(minutes),
+            commits: 4,
+            duration_minutes: minutes,
+        }
+    }
+
+    #[test]
+    fn weekly_windows_are_the_last_two_complete_iso_weeks() {
+        // A Wednesday and the Monday of the same week give the same digest.
+        for now in ["2026-10-14", "2026-10-12"] {
+            let windows = digest_windows(DigestPeriod::Weekly, local(now));
+            assert_eq!(windows.label, "2026-W41（10-05 ~ 10-11）");
+            assert_eq!(bounds(&windows.current), ("2026-10-05".into(), "2026-10-12".into()));
+            assert_eq!(bounds(&windows.previous), ("2026-09-28".into(), "2026-10-05".into()));
+        }
+        // Across a year boundary: ISO week 1 of 2026 starts 2025-12-29.
+        let windows = digest_windows(DigestPeriod::Weekly, local("2026-01-07"));
+        assert_eq!(windows.label, "2026-W01（12-29 ~ 01-04）");
+    }
+
+    #[test]
+    fn monthly_windows_are_the_last_two_calendar_months() {
+        let windows = digest_windows(DigestPeriod::Monthly, local("2026-03-01"));
+        assert_eq!(windows.label, "2026-02");
+        assert_eq!(bounds(&windows.current), ("2026-02-01".into(), "2026-03-01".into()));
+        assert_eq!(bounds(&windows.previous), ("2026-01-01".into(), "2026-02-01".into()));
+        assert_eq!(windows.current.description, "2026-02-01 ~ 2026-02-28");
+
+        let windows = digest_windows(DigestPeriod::Monthly, local("2026-01-15"));
+        assert_eq!(windows.label, "2025-12");
+        assert_eq!(bounds(&windows.previous).0, "2025-11-01");
+    }
+
+    #[test]
+    fn facts_come_in_priority_order_and_skip_what_did_not_happen() {
+        let mut current = metrics(20, 8, 45.0);
+        current.weekend_commits = 3;
+        current.all_nighters = 2;
+        current.worst_all_nighter = Some(all_nighter("Zhang San", 270));
+        current.longest_streak_days = 6;
+        let mut previous = metrics(20, 4, 30.0);
+        previous.weekend_commits = 4;
+
+        assert_eq!(
+            notable_facts(&current, Some(&previous)),
+            [
+                "下班后提交占比 40.0%，上升 20.0 个百分点",
+                "出现 2 次通宵，最长一次是 Zhang San 的 4.5 小时",
+                "最长连续工作 6 天",
+            ]
+        );
+
+        // The weekend share moved most, downwards.
+        previous.after_hours_commits = 7;
+        previous.weekend_commits = 9;
+        assert_eq!(notable_facts(&current, Some(&previous))[0], "周末提交占比 15.0%，下降 30.0 个百分点");
+
+        // Nothing moved, no all-nighter, no streak.
+        let quiet = metrics(10, 1, 5.0);
+        assert!(notable_facts(&quiet, Some(&quiet)).is_empty());
+        assert!(notable_facts(&quiet, None).is_empty());
+    }
+
+    #[test]
+    fn digest_renders_headline_facts_and_quip() {
+        let mut current = metrics(20, 8, 45.0);
+        current.longest_streak_days = 3;
+        current.quip = Some("记得休息。".to_string());
+        let previous = metrics(20, 8, 50.5);
+
+        let mut out = Vec::new();
+        write_digest(&mut out, DigestPeriod::Weekly, "2026-W41（10-05 ~ 10-11）", &current, Some(&previous))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "## 牛马周报：2026-W41（10-05 ~ 10-11）\n\n\
+                 **牛马指数 45.0**（较上周 ↓5.5） -> {}，共 20 次提交\n\n\
+                 - 最长连续工作 3 天\n\n\
+                 > 记得休息。\n",
+                Lang::Zh.band_label(2)
+            )
+        );
+
+        let mut out = Vec::new();
+        current.quip = None;
+        write_digest(&mut out, DigestPeriod::Monthly, "2026-09", &current, None).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("## 牛马月报：2026-09\n\n**牛马指数 45.0**（上月无提交）"), "{text}");
+        assert!(!text.contains("\n> "), "{text}");
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Stable machine-readable failure categories for `--json-errors`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NotARepo,
+    GitMissing,
+    GitFailed,
+    BadTimeFilter,
+    BadAlias,
+    StateCorrupt,
+    Internal,
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            ErrorCode::NotARepo => "NOT_A_REPO",
+            ErrorCode::GitMissing => "GIT_MISSING",
+            ErrorCode::GitFailed => "GIT_FAILED",
+            ErrorCode::BadTimeFilter => "BAD_TIME_FILTER",
+            ErrorCode::BadAlias => "BAD_ALIAS",
+            ErrorCode::StateCorrupt => "STATE_CORRUPT",
+            ErrorCode::Internal => "INTERNAL",
+        };
+        f.write_str(code)
+    }
+}
+
+/// An error raised at a known failure point. It travels inside `anyhow`
+/// like any other error and is looked up again in `main`.
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub path: Option<PathBuf>,
+}
+
+impl CodedError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        CodedError {
+            code,
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: ErrorCode,
+    message: String,
+    path: Option<&'a Path>,
+}
+
+#[derive(Serialize)]
+struct ErrorEnvelope<'a> {
+    error: ErrorBody<'a>,
+}
+
+/// Finds the coded error in `err`, whether it is the root cause or was
+/// attached as context. Anything else is `INTERNAL`.
+pub fn classify(err: &anyhow::Error) -> (ErrorCode, Option<&Path>) {
+    err.downcast_ref::<CodedError>()
+        .or_else(|| err.chain().find_map(|e| e.downcast_ref::<CodedError>()))
+        .map_or((ErrorCode::Internal, None), |coded| {
+            (coded.code, coded.path.as_deref())
+        })
+}
+
+pub fn error_json(err: &anyhow::Error) -> String {
+    let (code, path) = classify(err);
+    let envelope = ErrorEnvelope {
+        error: ErrorBody {
+            code,
+            message: format!("{err:#}"),
+            path,
+        },
+    };
+    serde_json::to_string(&envelope).unwrap_or_else(|_| {
+        r#"{"error":{"code":"INTERNAL","message":"failed to encode error"}}"#.to_string()
+    })
+}
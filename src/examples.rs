@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::Parser;
+
+use crate::cli::Cli;
+
+#[derive(Parser, Debug)]
+#[command(about = "列出常用的调用示例，或直接在指定仓库上运行其中一个", name = "examples")]
+pub struct ExamplesCli {
+    /// 运行第 N 个示例（编号见列表）
+    #[arg(long, value_name = "N")]
+    pub run: Option<usize>,
+
+    /// 运行示例时要分析的仓库
+    #[arg(long, default_value = ".", value_name = "PATH")]
+    pub path: PathBuf,
+}
+
+/// A curated invocation. `args` excludes the program name and `--path`,
+/// which is supplied when the example is run.
+pub struct Example {
+    pub description: &'static str,
+    pub args: &'static [&'static str],
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        description: "最近 90 天的默认报告",
+        args: &[],
+    },
+    Example {
+        description: "上周的团队周报（Markdown，可直接贴到群里）",
+        args: &["--digest", "weekly"],
+    },
+    Example {
+        description: "CI 门禁：下班后占比超过 30% 或牛马指数超过 60 时以退出码 2 失败",
+        args: &["--policy", "after_hours<=0.3,severity<=60", "--fail-on-policy", "--no-quips"],
+    },
+    Example {
+        description: "镜像新鲜度检查：最新提交超过 7 天即失败",
+        args: &["--expect-fresh", "7d", "--strict"],
+    },
+    Example {
+        description: "合并同一个人的两个名字，并忽略机器人账号",
+        args: &["--alias", "zhangsan=张三", "--ignore-author", "dependabot[bot]"],
+    },
+    Example {
+        description: "全部历史按顶层目录拆分，同时导出 JSON",
+        args: &["--all-history", "--group-by-dir", "1", "--json-file", "cowhorse.json"],
+    },
+    Example {
+        description: "争论 18:30 算不算加班时：看下班时间的敏感性",
+        args: &["--sensitivity"],
+    },
+    Example {
+        description: "按本月目前的节奏预测月底的牛马指数",
+        args: &["--forecast"],
+    },
+];
+
+/// Parses an example into a `Cli` exactly as if it had been typed.
+pub fn example_cli(example: &Example, path: &str) -> Result<Cli> {
+    let argv = ["cow-horse"]
+        .into_iter()
+        .chain(example.args.iter().copied())
+        .chain(["--path", path]);
+    Ok(Cli::try_parse_from(argv)?)
+}
+
+pub fn print_examples() {
+    for (idx, example) in EXAMPLES.iter().enumerate() {
+        println!("{}. {}", idx + 1, example.description);
+        let command: Vec<String> = ["zzh"]
+            .iter()
+            .chain(example.args)
+            .map(|arg| shell_quote(arg))
+            .collect();
+        println!("   {}", command.join(" "));
+    }
+    println!();
+    println!("运行其中一个：zzh examples --run <N> --path <仓库>");
+}
+
+/// Picks example `n` (1-based) and builds its `Cli` for `path`.
+pub fn selected_example(n: usize, path: &str) -> Result<Cli> {
+    let Some(example) = n.checked_sub(1).and_then(|idx| EXAMPLES.get(idx)) else {
+        bail!("没有第 {n} 个示例，可选 1..={}", EXAMPLES.len());
+    };
+    eprintln!("运行示例 {n}：{}", example.description);
+    example_cli(example, path)
+}
+
+fn shell_quote(arg: &str) -> String {
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_=./,".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::analysis::Analyzer;
+    use crate::progress::NoProgress;
+    use crate::testutil::TempRepo;
+
+    #[test]
+    fn every_example_parses_and_sets_up_an_analysis() {
+        let repo = TempRepo::new();
+        repo.commit("A", "2026-09-01T21:00:00+08:00");
+        let path = repo.path().to_str().unwrap();
+        for (idx, example) in EXAMPLES.iter().enumerate() {
+            assert!(!example.description.is_empty());
+            let cli = example_cli(example, path)
+                .unwrap_or_else(|err| panic!("example {} no longer parses: {err}", idx + 1));
+            assert_eq!(cli.path, [Path::new(path)]);
+            // Values clap cannot check (policy rules, aliases, ...) are
+            // checked when the analyzer is built.
+            if let Err(err) = Analyzer::new(&cli, repo.path(), Utc::now(), &NoProgress) {
+                panic!("example {} is rejected: {err:#}", idx + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn examples_are_numbered_from_one() {
+        let first = selected_example(1, "/tmp/repo").unwrap();
+        assert_eq!(first.path, [Path::new("/tmp/repo")]);
+        let last = selected_example(EXAMPLES.len(), "/tmp/repo").unwrap();
+        assert!(last.forecast);
+        assert!(selected_example(0, ".").is_err());
+        assert!(selected_example(EXAMPLES.len() + 1, ".").is_err());
+    }
+
+    #[test]
+    fn printed_commands_quote_what_a_shell_would_split() {
+        assert_eq!(shell_quote("after_hours<=0.3,severity<=60"), "'after_hours<=0.3,severity<=60'");
+        assert_eq!(shell_quote("zhangsan=张三"), "'zhangsan=张三'");
+        assert_eq!(shell_quote("dependabot[bot]"), "'dependabot[bot]'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("--group-by-dir"), "--group-by-dir");
+    }
+}
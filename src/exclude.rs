@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::error::{CodedError, ErrorCode};
+use crate::time_filter::parse_time_filter;
+
+/// One `--exclude "作者:起..止"` rule; `end` is exclusive.
+#[derive(Debug, Clone)]
+pub struct ExcludeRule {
+    pub author: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// The range as written, for the report.
+    pub range: String,
+}
+
+impl ExcludeRule {
+    pub fn matches(&self, author: &str, timestamp: DateTime<Utc>) -> bool {
+        self.author == author && self.start <= timestamp && timestamp < self.end
+    }
+}
+
+/// Parses `作者:起..止`. Both endpoints use the `--since` syntax and are
+/// inclusive; a plain date covers that whole day. The author may contain
+/// `:` itself, the separator is the first `:` after which a valid start
+/// endpoint follows.
+pub fn parse_excludes(raw: &[String], now: DateTime<Utc>) -> Result<Vec<ExcludeRule>> {
+    raw.iter().map(|entry| parse_exclude(entry, now)).collect()
+}
+
+fn parse_exclude(entry: &str, now: DateTime<Utc>) -> Result<ExcludeRule> {
+    let bad = |reason: &str| {
+        CodedError::new(
+            ErrorCode::BadTimeFilter,
+            format!("--exclude {entry:?} 无效：{reason}（格式：作者:2024-05-01..2024-05-14）"),
+        )
+    };
+
+    let Some((head, end_raw)) = entry.rsplit_once("..") else {
+        bail!(bad("缺少 .. 分隔的日期范围"));
+    };
+    let end_raw = end_raw.trim();
+    if end_raw.is_empty() {
+        bail!(bad("缺少结束日期，不支持开放区间"));
+    }
+
+    let mut split = None;
+    for (idx, _) in head.match_indices(':') {
+        let start_raw = head[idx + 1..].trim();
+        if start_raw.is_empty() {
+            bail!(bad("缺少开始日期，不支持开放区间"));
+        }
+        if let Ok(start) = parse_time_filter(start_raw, now) {
+            split = Some((head[..idx].trim(), start_raw, start));
+            break;
+        }
+    }
+    let Some((author, start_raw, start)) = split else {
+        bail!(bad("无法识别作者或开始日期"));
+    };
+    if author.is_empty() {
+        bail!(bad("作者不能为空"));
+    }
+
+    let end = parse_time_filter(end_raw, now).map_err(|_| bad("无法解析结束日期"))?;
+    let end = if NaiveDate::parse_from_str(end_raw, "%Y-%m-%d").is_ok() {
+        end + Duration::days(1)
+    } else {
+        end + Duration::seconds(1)
+    };
+    if end <= start {
+        bail!(bad("结束日期早于开始日期"));
+    }
+
+    Ok(ExcludeRule {
+        author: author.to_string(),
+        start,
+        end,
+        range: format!("{start_raw}..{end_raw}"),
+    })
+}
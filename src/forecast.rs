@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::gitlog::Commit;
+use crate::metrics::{
+    severity_label, severity_score, Classifier, RepoMetrics, SeverityBuckets, SeverityCounts,
+};
+
+/// Window-end projection made from the part of the window observed so far.
+/// The after-hours, weekend and night projections follow the run's
+/// [`SeverityOverlap`], so they are the counts behind the projected score.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Forecast {
+    pub until: DateTime<Utc>,
+    pub observed_weekdays: usize,
+    pub observed_weekend_days: usize,
+    pub remaining_weekdays: usize,
+    pub remaining_weekend_days: usize,
+    pub projected_total_commits: f64,
+    pub projected_after_hours_commits: f64,
+    pub projected_weekend_commits: f64,
+    pub projected_night_commits: f64,
+    pub projected_commit_days: f64,
+    pub projected_overtime_days: f64,
+    pub projected_severity_score: f64,
+    pub projected_severity_label: String,
+}
+
+/// Per-day-kind totals; weekdays and weekends are extrapolated separately
+/// because their commit mix differs a lot.
+#[derive(Default)]
+struct Observed {
+    days: usize,
+    commits: usize,
+    buckets: SeverityBuckets,
+    commit_days: usize,
+    overtime_days: usize,
+}
+
+impl Observed {
+    /// `self` plus `remaining` more days at the observed per-day rate.
+    fn project(&self, remaining: usize) -> [f64; 6] {
+        let rate = |count: usize| {
+            if self.days == 0 {
+                0.0
+            } else {
+                count as f64 / self.days as f64 * remaining as f64
+            }
+        };
+        [
+            self.commits,
+            self.buckets.after_hours,
+            self.buckets.weekend,
+            self.buckets.night,
+            self.commit_days,
+            self.overtime_days,
+        ]
+        .map(|count| count as f64 + rate(count))
+    }
+}
+
+
+/// Extrapolates the counts of `commits` (observed from `start` up to and
+/// including today) linearly to the exclusive instant `until`. The longest
+/// streak and overlap mode are taken from `metrics`; the streak is carried
+/// over as observed rather than guessed. Days are split into workdays and
+/// rest days by `classifier`.
+pub fn forecast<C: Classifier + ?Sized>(
+    commits: &[Commit],
+    start: NaiveDate,
+    now: DateTime<Utc>,
+    until: DateTime<Utc>,
+    metrics: &RepoMetrics,
+    classifier: &C,
+) -> Forecast {
+    let is_weekend = |date: NaiveDate| classifier.is_rest_day(date);
+    let overlap = metrics.severity_overlap;
+    let today = now.date_naive();
+    let last_day = (until - Duration::seconds(1)).date_naive();
+
+    let mut weekdays = Observed::default();
+    let mut weekends = Observed::default();
+    for date in start.iter_days().take_while(|date| *date <= today) {
+        let bucket = if is_weekend(date) { &mut weekends } else { &mut weekdays };
+        bucket.days += 1;
+    }
+
+    // date -> after-hours commits that day
+    let mut per_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for commit in commits {
+        let date = commit.timestamp.date_naive();
+        let classification = classifier.classify(commit);
+        let after_hours = classification.after_hours;
+        let bucket = if is_weekend(date) { &mut weekends } else { &mut weekdays };
+        bucket.commits += 1;
+        bucket.buckets.add(
+            overlap,
+            after_hours,
+            classification.weekend || classification.holiday,
+            classification.night,
+        );
+        *per_day.entry(date).or_default() += usize::from(after_hours);
+    }
+    for (date, after_hours) in &per_day {
+        let bucket = if is_weekend(*date) { &mut weekends } else { &mut weekdays };
+        bucket.commit_days += 1;
+        bucket.overtime_days += usize::from(*after_hours > 0);
+    }
+
+    let (mut remaining_weekdays, mut remaining_weekend_days) = (0, 0);
+    for date in (today + Duration::days(1))
+        .iter_days()
+        .take_while(|date| *date <= last_day)
+    {
+        if is_weekend(date) {
+            remaining_weekend_days += 1;
+        } else {
+            remaining_weekdays += 1;
+        }
+    }
+
+    let weekday_part = weekdays.project(remaining_weekdays);
+    let weekend_part = weekends.project(remaining_weekend_days);
+    let [total, after_hours, weekend, night, commit_days, overtime_days] =
+        std::array::from_fn(|i| weekday_part[i] + weekend_part[i]);
+
+    let score = severity_score(
+        &SeverityCounts {
+            total: total.round() as usize,
+            after_hours: after_hours.round() as usize,
+            weekend: weekend.round() as usize,
+            night: night.round() as usize,
+            overtime_days: overtime_days.round() as usize,
+            commit_days: commit_days.round() as usize,
+            longest_streak: metrics.longest_streak_days,
+        },
+        &metrics.severity_weights,
+    );
+
+    Forecast {
+        until,
+        observed_weekdays: weekdays.days,
+        observed_weekend_days: weekends.days,
+        remaining_weekdays,
+        remaining_weekend_days,
+        projected_total_commits: total,
+        projected_after_hours_commits: after_hours,
+        projected_weekend_commits: weekend,
+        projected_night_commits: night,
+        projected_commit_days: commit_days,
+        projected_overtime_days: overtime_days,
+        projected_severity_score: score,
+        projected_severity_label: severity_label(score).to_string(),
+    }
+}
@@ -1,85 +1,912 @@
+use std::collections::HashSet;
 use std::path::Path;
-use std::process::Command;
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration as StdDuration;
 
-use anyhow::{anyhow, bail, Context, Result};
-use chrono::{DateTime, FixedOffset, Utc};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+use crate::cache;
+use crate::error::{CodedError, ErrorCode};
+use crate::progress::{Progress, ProgressEvent, COMMITS_READ_STEP};
+
+#[derive(Debug, Clone, Default)]
 pub struct Commit {
+    pub hash: String,
     pub author: String,
+    /// Author email, lowercased; empty if git recorded none.
+    pub email: String,
     pub timestamp: DateTime<FixedOffset>,
+    /// The offset git recorded, kept when `--timezone` re-expressed
+    /// `timestamp` in another zone.
+    pub recorded_offset: Option<FixedOffset>,
+    /// First line of the commit message.
+    pub subject: String,
+    /// Submodule path the commit was read from (`.` for the superproject);
+    /// only set with `--include-submodules`.
+    pub component: Option<String>,
+    /// Files touched by the commit; only read when `FetchOptions::with_paths`.
+    pub paths: Vec<String>,
+    /// Directory bucket chosen by `--group-by-dir`.
+    pub directory: Option<String>,
+    /// Name as recorded in git, kept when an alias replaced `author`.
+    pub raw_author: Option<String>,
+    /// A `Co-authored-by:` credit copied from the commit `hash` for this
+    /// co-author; counts toward them but not toward repo totals.
+    pub co_author: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FetchOptions<'a> {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub author: Option<&'a str>,
+    /// Most commits to keep, counted after the window and merge filters;
+    /// co-author credits do not count.
+    pub limit: Option<usize>,
+    pub with_paths: bool,
+    /// Stop reading after this many consecutive commits older than `since`.
+    /// History is newest-first, but rebased branches can interleave dates, so
+    /// a single old commit is not enough to conclude the window is covered.
+    pub early_stop_slack: Option<usize>,
+    /// Stop once kept commits span this many distinct commit dates (plus the
+    /// same slack as above, so the last covered day is read completely).
+    pub min_coverage_days: Option<usize>,
+    /// Extra attempts after a `git log` failure that looks transient.
+    pub retries: u32,
+    /// Log each retry to stderr.
+    pub verbose: bool,
+    /// Resolve author names through the repo's `.mailmap`, as `git shortlog`
+    /// does; `--author` then matches the mapped names too.
+    pub mailmap: bool,
+    /// Drop merge commits. Done here rather than with git's `--no-merges`
+    /// so the number dropped can be reported.
+    pub no_merges: bool,
+    /// Follow only the first parent of merges (`git log --first-parent`).
+    pub first_parent: bool,
+    /// Refs to read instead of HEAD; empty means HEAD.
+    pub branches: &'a [String],
+    /// Read every ref (`git log --all`).
+    pub all_branches: bool,
+    /// Revision range such as `v1.2.0..v1.3.0`, read as given.
+    pub rev_range: Option<&'a str>,
+    /// Only commits touching these paths (git pathspecs).
+    pub pathspecs: &'a [String],
+    pub date_source: DateSource,
+    /// Add a co-author credit per `Co-authored-by:` trailer.
+    pub coauthors: bool,
+    /// Directory of the commit cache (see [`crate::cache`]); `None` always
+    /// reads from git.
+    pub cache_dir: Option<&'a Path>,
+    /// Read git even if the cache has the history, then store it (`--refresh`).
+    pub refresh_cache: bool,
+}
+
+/// Commits read from one `git log` run plus how much raw history it took.
+#[derive(Debug, Default)]
+pub struct Fetched {
+    pub commits: Vec<Commit>,
+    /// Commit records parsed from git, including ones outside the window.
+    pub read: usize,
+    /// Whether reading stopped before git reached the end of its output.
+    pub stopped_early: bool,
+    /// Distinct commit dates among the kept commits.
+    pub covered_days: usize,
+    /// In-window merge commits dropped by `FetchOptions::no_merges`.
+    pub merges_skipped: usize,
+    /// Kept commits whose author and committer dates differ by more than
+    /// [`REWRITTEN_THRESHOLD`], a sign of rebased or cherry-picked history.
+    pub rewritten: usize,
+}
+
+/// `--pretty` format for one commit. Every field ends in a NUL, which git
+/// never lets into names, emails or messages, so a record survives authors
+/// containing newlines or `\x1f` (seen in imported SVN history). The last
+/// field holds `Co-authored-by:` trailer values separated by `\x1e`.
+const PRETTY_FORMAT: &str = "%H%x00{an}%x00%ad%x00{ae}%x00%P%x00%cd%x00%s%x00%(trailers:key=Co-authored-by,valueonly,separator=%x1e)%x00";
+
+/// Number of NUL-terminated fields [`PRETTY_FORMAT`] produces per commit.
+const HEADER_FIELDS: usize = 8;
+
+/// Splits trailer values like `Zhang San <zs@corp.com>` into name and
+/// lowercased email; values without an email keep an empty one.
+fn parse_co_authors(raw: &str) -> Vec<(String, String)> {
+    raw.split('\x1e')
+        .filter_map(|value| {
+            let value = value.trim();
+            let (name, email) = match value.split_once('<') {
+                Some((name, rest)) => (name.trim(), rest.trim_end_matches('>').trim()),
+                None => (value, ""),
+            };
+            (!name.is_empty()).then(|| (name.to_string(), email.to_lowercase()))
+        })
+        .collect()
+}
+
+pub const REWRITTEN_THRESHOLD: Duration = Duration::hours(1);
+
+/// Which of a commit's two timestamps to analyze.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    /// When the change was first written (`%ad`); survives rebases.
+    #[default]
+    Author,
+    /// When the commit was last created (`%cd`), e.g. by a rebase or merge button.
+    Committer,
+}
+
+/// What HEAD points at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HeadRef {
+    /// A branch with at least one commit.
+    Branch { name: String },
+    /// A branch created by `git init`/`git switch -c` that has no commits yet.
+    Unborn { name: String },
+    /// HEAD names a commit directly (typical for CI checkouts).
+    Detached { sha: String },
+}
+
+impl HeadRef {
+    pub fn describe(&self) -> String {
+        match self {
+            HeadRef::Branch { name } => name.clone(),
+            HeadRef::Unborn { name } => format!("{name}（尚无提交）"),
+            HeadRef::Detached { sha } => format!("detached@{}", &sha[..sha.len().min(12)]),
+        }
+    }
+}
+
+/// Resolves HEAD into a branch name, an unborn branch, or a bare SHA.
+pub fn head_ref(repo_path: &Path) -> Result<HeadRef> {
+    let symbolic = git_output(repo_path, &["symbolic-ref", "-q", "HEAD"])?;
+    let commit = git_output(repo_path, &["rev-parse", "--verify", "-q", "HEAD"])?;
+    match (symbolic, commit) {
+        (Some(reference), Some(_)) => Ok(HeadRef::Branch {
+            name: short_ref(&reference),
+        }),
+        (Some(reference), None) => Ok(HeadRef::Unborn {
+            name: short_ref(&reference),
+        }),
+        (None, Some(sha)) => Ok(HeadRef::Detached { sha }),
+        (None, None) => bail!("无法解析 {} 的 HEAD", repo_path.display()),
+    }
+}
+
+fn short_ref(reference: &str) -> String {
+    reference
+        .strip_prefix("refs/heads/")
+        .unwrap_or(reference)
+        .to_string()
+}
+
+/// Runs a quiet git query; `None` when git exits non-zero.
+fn git_output(repo_path: &Path, args: &[&str]) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .map_err(|err| spawn_error(err, &format!("git {}", args.join(" ")), repo_path))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8(output.stdout)?.trim().to_string()))
 }
 
+/// Maps a failure to start git onto `GIT_MISSING` (not installed) or
+/// `GIT_FAILED`.
+pub fn spawn_error(err: io::Error, what: &str, repo_path: &Path) -> anyhow::Error {
+    let code = if err.kind() == io::ErrorKind::NotFound {
+        ErrorCode::GitMissing
+    } else {
+        ErrorCode::GitFailed
+    };
+    CodedError::new(
+        code,
+        format!("failed to execute `{what}` in {}: {err}", repo_path.display()),
+    )
+    .with_path(repo_path)
+    .into()
+}
+
+/// Classifies a non-zero git exit from its stderr.
+pub fn git_failure(what: &str, repo_path: &Path, stderr: &[u8]) -> CodedError {
+    let stderr = String::from_utf8_lossy(stderr);
+    let code = if stderr.contains("not a git repository") {
+        ErrorCode::NotARepo
+    } else {
+        ErrorCode::GitFailed
+    };
+    CodedError::new(code, format!("{what} failed: {}", stderr.trim_end())).with_path(repo_path)
+}
+
+/// Extra margin on the git-side date filters. Git's own `--since/--until`
+/// handling has produced boundaries that are off by hours around midnight,
+/// so we ask git for a slightly wider range and apply the exact window here.
+const BOUNDARY_SLACK_DAYS: i64 = 1;
+
+/// Reads the commits of `repo_path`, newest first.
+///
+/// `since` and `until` are applied client-side against each commit's own
+/// timestamp, so a commit at 2024-05-01T07:30+08:00 is excluded by
+/// `since = 2024-05-01T00:00Z` (it is 23:30Z the day before) no matter how the
+/// installed git interprets the bound. `limit` caps the commits kept after
+/// that exact window and the merge filter, so records from the slack margin
+/// never use it up.
+///
+/// Output is parsed as it streams from git, so with `early_stop_slack` the
+/// child can be stopped once the window is clearly covered.
+///
+/// Failures whose stderr matches [`TRANSIENT_GIT_ERRORS`] are retried up to
+/// `retries` times with exponential backoff; anything else fails at once.
 pub fn fetch_commits(
     repo_path: &Path,
-    since: Option<DateTime<Utc>>,
-    until: Option<DateTime<Utc>>,
-    author: Option<&str>,
-    limit: Option<usize>,
-) -> Result<Vec<Commit>> {
+    options: &FetchOptions<'_>,
+    progress: &Progress<'_>,
+) -> Result<Fetched> {
+    progress.emit(ProgressEvent::FetchStarted { repo: repo_path });
+    let mut attempt = 0;
+    loop {
+        match fetch_once(repo_path, options, progress) {
+            Ok(fetched) => {
+                progress.emit(ProgressEvent::FetchFinished {
+                    repo: repo_path,
+                    read: fetched.read,
+                    stopped_early: fetched.stopped_early,
+                });
+                return Ok(fetched);
+            }
+            Err(err) if attempt < options.retries && is_transient(&err) => {
+                let delay = RETRY_BASE_DELAY
+                    .saturating_mul(1 << attempt.min(16))
+                    .min(RETRY_MAX_DELAY);
+                attempt += 1;
+                if options.verbose {
+                    eprintln!(
+                        "git log 临时失败（{}），{} 毫秒后进行第 {attempt}/{} 次重试",
+                        err,
+                        delay.as_millis(),
+                        options.retries
+                    );
+                }
+                thread::sleep(delay);
+            }
+            Err(err) if attempt > 0 => {
+                return Err(err.context(format!("git log 在重试 {attempt} 次后仍然失败")));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// stderr fragments of `git log` failures worth retrying (lowercased): a
+/// lock held by a concurrent git process, or a timeout. Missing or corrupt
+/// objects fail the same way every time, so they are not listed.
+const TRANSIENT_GIT_ERRORS: &[&str] = &[
+    ".lock': file exists",
+    "index.lock",
+    "resource temporarily unavailable",
+    "timed out",
+];
+const RETRY_BASE_DELAY: StdDuration = StdDuration::from_millis(200);
+const RETRY_MAX_DELAY: StdDuration = StdDuration::from_secs(5);
+
+fn is_transient(err: &anyhow::Error) -> bool {
+    let Some(coded) = err.downcast_ref::<CodedError>() else {
+        return false;
+    };
+    let message = coded.message.to_lowercase();
+    coded.code == ErrorCode::GitFailed
+        && TRANSIENT_GIT_ERRORS
+            .iter()
+            .any(|pattern| message.contains(pattern))
+}
+
+/// The header fields of one commit record.
+struct Header<'a> {
+    hash: &'a str,
+    author: &'a str,
+    email: String,
+    is_merge: bool,
+    author_date: DateTime<FixedOffset>,
+    committer_date: DateTime<FixedOffset>,
+    subject: &'a str,
+    co_authors: &'a str,
+}
+
+fn parse_header(fields: &[String]) -> Result<Header<'_>> {
+    let [hash, author, author_date, email, parents, committer_date, subject, co_authors] = fields else {
+        bail!(
+            "git log record has {} of {HEADER_FIELDS} fields",
+            fields.len()
+        );
+    };
+    if hash.is_empty() || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("git log record starts with {hash:?} instead of a commit hash");
+    }
+    let parse = |raw: &str| {
+        DateTime::parse_from_rfc3339(raw)
+            .with_context(|| format!("commit {hash}: failed to parse timestamp {raw:?}"))
+    };
+    Ok(Header {
+        hash,
+        author,
+        email: email.trim().to_lowercase(),
+        is_merge: parents.split_whitespace().count() > 1,
+        author_date: parse(author_date)?,
+        committer_date: parse(committer_date)?,
+        subject,
+        co_authors,
+    })
+}
+
+/// One commit as read from `git log -z`: its header fields and, with
+/// `--name-only`, the files it touched.
+struct Record {
+    fields: Vec<String>,
+    paths: Vec<String>,
+}
+
+/// Reads the next record from the NUL-separated token stream. Records are
+/// separated by an empty token; with `--name-only` the paths follow the
+/// header, the first one after a newline.
+fn next_record(
+    tokens: &mut impl Iterator<Item = io::Result<Vec<u8>>>,
+    with_paths: bool,
+) -> Result<Option<Record>> {
+    let mut next = || -> Result<Option<String>> {
+        match tokens.next() {
+            Some(token) => {
+                let token = token.context("failed to read git log output")?;
+                Ok(Some(String::from_utf8_lossy(&token).into_owned()))
+            }
+            None => Ok(None),
+        }
+    };
+
+    let hash = loop {
+        match next()? {
+            Some(token) if token.trim().is_empty() => continue,
+            Some(token) => break token,
+            None => return Ok(None),
+        }
+    };
+    let mut fields = vec![hash];
+    while fields.len() < HEADER_FIELDS {
+        match next()? {
+            Some(field) => fields.push(field),
+            None => bail!(
+                "git log output for commit {} ended after {} of {HEADER_FIELDS} fields",
+                fields[0],
+                fields.len()
+            ),
+        }
+    }
+
+    let mut paths = Vec::new();
+    if with_paths {
+        while let Some(path) = next()? {
+            if path.is_empty() {
+                break;
+            }
+            paths.push(path.strip_prefix('\n').map(str::to_string).unwrap_or(path));
+        }
+    }
+    Ok(Some(Record { fields, paths }))
+}
+
+/// The `git log` invocation for `options`. Without `windowed` the date
+/// bounds are left out, so the output is the whole history the commit cache
+/// can serve any window from.
+fn log_command(repo_path: &Path, options: &FetchOptions<'_>, windowed: bool) -> Command {
+    let FetchOptions {
+        since,
+        until,
+        author,
+        with_paths,
+        mailmap,
+        first_parent,
+        branches,
+        all_branches,
+        rev_range,
+        pathspecs,
+        ..
+    } = *options;
+
     let mut cmd = Command::new("git");
     cmd.arg("-C").arg(repo_path);
-    cmd.args([
-        "log",
-        "--no-color",
-        "--pretty=format:%H\x1f%an\x1f%ad",
-        "--date=iso-strict",
-    ]);
+    cmd.args(["-c", "core.quotepath=off"]);
+    cmd.args(["log", "--no-color", "--date=iso-strict"]);
+    cmd.arg("-z");
+    let (flag, name, email) = if mailmap {
+        ("--use-mailmap", "%aN", "%aE")
+    } else {
+        ("--no-use-mailmap", "%an", "%ae")
+    };
+    let pretty = PRETTY_FORMAT.replace("{an}", name).replace("{ae}", email);
+    cmd.args([flag, &format!("--pretty=format:{pretty}")]);
+    if first_parent {
+        cmd.arg("--first-parent");
+    }
 
-    if let Some(since) = since {
-        cmd.arg(format!("--since={}", since.to_rfc3339()));
+    if with_paths {
+        cmd.arg("--name-only");
     }
 
-    if let Some(until) = until {
-        cmd.arg(format!("--until={}", until.to_rfc3339()));
+    let slack = Duration::days(BOUNDARY_SLACK_DAYS);
+    if let Some(since) = since.filter(|_| windowed) {
+        cmd.arg(format!("--since={}", (since - slack).to_rfc3339()));
+    }
+
+    if let Some(until) = until.filter(|_| windowed) {
+        cmd.arg(format!("--until={}", (until + slack).to_rfc3339()));
     }
 
     if let Some(author) = author {
         cmd.arg(format!("--author={author}"));
     }
 
-    if let Some(limit) = limit {
-        cmd.arg(format!("-n{limit}"));
+    if all_branches {
+        cmd.arg("--all");
+    }
+    cmd.args(branches);
+    if let Some(range) = rev_range {
+        cmd.arg(range);
     }
+    // Separates refs from paths, so a branch named like a file stays a ref.
+    cmd.arg("--");
+    cmd.args(pathspecs);
+    cmd
+}
 
-    let output = cmd
-        .output()
-        .with_context(|| format!("failed to execute `git log` in {}", repo_path.display()))?;
+fn fetch_once(
+    repo_path: &Path,
+    options: &FetchOptions<'_>,
+    progress: &Progress<'_>,
+) -> Result<Fetched> {
+    if let Some(dir) = options.cache_dir
+        && let Some(fetched) = cache::fetch(repo_path, dir, options, progress)?
+    {
+        return Ok(fetched);
+    }
+
+    let mut cmd = log_command(repo_path, options, true);
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| spawn_error(err, "git log", repo_path))?;
+    let (Some(stdout), Some(mut stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        let _ = child.kill();
+        let _ = child.wait();
+        bail!("git log output was not captured");
+    };
+    // Drained on its own thread: git blocks once the stderr pipe fills, and
+    // we only look at stdout until the log ends.
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        buf
+    });
+
+    let read = read_log(stdout, repo_path, options, progress);
+    // Whether we stopped on purpose or on a bad record, git may still be
+    // writing; it must not outlive us either way.
+    if read.as_ref().map_or(true, |fetched| fetched.stopped_early) {
+        let _ = child.kill();
+    }
+    let status = child.wait();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    let fetched = read?;
+    if fetched.stopped_early {
+        // We have what we need; the rest of the history is not worth waiting for.
+        return Ok(fetched);
+    }
+
+    let status =
+        status.with_context(|| format!("failed to wait for `git log` in {}", repo_path.display()))?;
+    if !status.success() {
+        // An unborn branch fails `git log` outright; it is just empty history.
+        if matches!(head_ref(repo_path), Ok(HeadRef::Unborn { .. })) {
+            return Ok(Fetched::default());
+        }
+        bail!(git_failure("git log", repo_path, &stderr));
+    }
 
+    Ok(fetched)
+}
+
+/// The whole unwindowed `git log` output for `options`, for the commit
+/// cache; empty for an unborn branch.
+pub(crate) fn full_log(repo_path: &Path, options: &FetchOptions<'_>) -> Result<Vec<u8>> {
+    let output = log_command(repo_path, options, false)
+        .output()
+        .map_err(|err| spawn_error(err, "git log", repo_path))?;
     if !output.status.success() {
-        bail!(
-            "git log failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
+        if matches!(head_ref(repo_path), Ok(HeadRef::Unborn { .. })) {
+            return Ok(Vec::new());
+        }
+        bail!(git_failure("git log", repo_path, &output.stderr));
     }
+    Ok(output.stdout)
+}
+
+/// Number of commit records in `git log` output read with `with_paths`.
+pub(crate) fn count_records(log: &[u8], with_paths: bool) -> Result<usize> {
+    let mut tokens = BufReader::new(log).split(b'\0');
+    let mut count = 0;
+    while next_record(&mut tokens, with_paths)?.is_some() {
+        count += 1;
+    }
+    Ok(count)
+}
 
-    let stdout = String::from_utf8(output.stdout)?;
-    let mut commits = Vec::new();
+/// Parses `git log` output as it streams in, applying the window, merge,
+/// coverage and limit filters of `options`.
+pub(crate) fn read_log(
+    stdout: impl Read,
+    repo_path: &Path,
+    options: &FetchOptions<'_>,
+    progress: &Progress<'_>,
+) -> Result<Fetched> {
+    let FetchOptions {
+        since,
+        until,
+        limit,
+        with_paths,
+        early_stop_slack,
+        min_coverage_days,
+        no_merges,
+        date_source,
+        coauthors,
+        ..
+    } = *options;
+
+    let mut fetched = Fetched::default();
+    let mut too_old_run = 0usize;
+    let mut covered: HashSet<NaiveDate> = HashSet::new();
+    // Commits on dates beyond the coverage goal, in a row.
+    let mut beyond_goal_run = 0usize;
+    // Commits pushed for their author, toward `limit`.
+    let mut kept = 0usize;
+
+    let mut tokens = BufReader::new(stdout).split(b'\0');
+    while let Some(record) = next_record(&mut tokens, with_paths)? {
+        let header = parse_header(&record.fields).with_context(|| {
+            format!("failed to parse git log record {}", fetched.read + 1)
+        })?;
+        let Header {
+            hash,
+            author,
+            email,
+            is_merge,
+            subject,
+            co_authors,
+            ..
+        } = header;
+        let timestamp = match date_source {
+            DateSource::Author => header.author_date,
+            DateSource::Committer => header.committer_date,
+        };
+        fetched.read += 1;
+        if fetched.read % COMMITS_READ_STEP == 0 {
+            progress.emit(ProgressEvent::CommitsRead {
+                repo: repo_path,
+                read: fetched.read,
+            });
+        }
+
+        let too_old = since.is_some_and(|s| timestamp < s);
+        too_old_run = if too_old { too_old_run + 1 } else { 0 };
+        if early_stop_slack.is_some_and(|slack| too_old_run >= slack) {
+            fetched.stopped_early = true;
+            break;
+        }
+
+        if too_old || until.is_some_and(|u| timestamp > u) {
+            continue;
+        }
+        if no_merges && is_merge {
+            fetched.merges_skipped += 1;
+            continue;
+        }
+        if limit.is_some_and(|limit| kept >= limit) {
+            fetched.stopped_early = true;
+            break;
+        }
 
-    for line in stdout.lines() {
-        if line.trim().is_empty() {
+        let date = timestamp.date_naive();
+        if let Some(goal) = min_coverage_days
+            && covered.len() >= goal
+            && !covered.contains(&date)
+        {
+            beyond_goal_run += 1;
+            if beyond_goal_run > early_stop_slack.unwrap_or(0) {
+                fetched.stopped_early = true;
+                break;
+            }
             continue;
         }
-        let mut parts = line.split('\x1f');
-        let _hash = parts
-            .next()
-            .ok_or_else(|| anyhow!("git log output missing hash column"))?;
-        let author = parts
-            .next()
-            .ok_or_else(|| anyhow!("git log output missing author column"))?;
-        let timestamp_str = parts
-            .next()
-            .ok_or_else(|| anyhow!("git log output missing timestamp column"))?;
-        let timestamp =
-            DateTime::parse_from_rfc3339(timestamp_str).with_context(|| {
-                format!("failed to parse timestamp {timestamp_str:?}")
-            })?;
-
-        commits.push(Commit {
+        beyond_goal_run = 0;
+        covered.insert(date);
+        if (header.author_date - header.committer_date).abs() > REWRITTEN_THRESHOLD {
+            fetched.rewritten += 1;
+        }
+
+        let credits: Vec<(String, String)> = if coauthors {
+            parse_co_authors(co_authors)
+                .into_iter()
+                .filter(|(name, co_email)| *co_email != email && name != author)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        fetched.commits.push(Commit {
+            hash: hash.to_string(),
             author: author.to_string(),
+            email,
             timestamp,
+            recorded_offset: None,
+            subject: subject.to_string(),
+            component: None,
+            paths: record.paths,
+            directory: None,
+            raw_author: None,
+            co_author: false,
         });
+        kept += 1;
+        for (name, co_email) in credits {
+            fetched.commits.push(Commit {
+                hash: hash.to_string(),
+                author: name,
+                email: co_email,
+                timestamp,
+                recorded_offset: None,
+                subject: subject.to_string(),
+                component: None,
+                paths: Vec::new(),
+                directory: None,
+                raw_author: None,
+                co_author: true,
+            });
+        }
     }
 
-    Ok(commits)
+    fetched.covered_days = covered.len();
+    Ok(fetched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+    use crate::testutil::TempRepo;
+
+    fn utc(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    fn fetch(repo: &TempRepo, options: &FetchOptions<'_>) -> Vec<Commit> {
+        fetch_commits(repo.path(), options, &Progress::new(&NoProgress))
+            .unwrap()
+            .commits
+    }
+
+    fn dates(commits: &[Commit]) -> Vec<String> {
+        commits.iter().map(|c| c.timestamp.to_rfc3339()).collect()
+    }
+
+    #[test]
+    fn window_bounds_are_inclusive_in_any_offset() {
+        let repo = TempRepo::new();
+        // 2026-05-01T00:00Z and one second before it, seen from UTC+8.
+        repo.commit("A", "2026-05-01T07:59:59+08:00");
+        repo.commit("A", "2026-05-01T08:00:00+08:00");
+        // 2026-05-10T00:00Z and one second after it, seen from UTC-5.
+        repo.commit("A", "2026-05-09T19:00:00-05:00");
+        repo.commit("A", "2026-05-09T19:00:01-05:00");
+
+        let commits = fetch(
+            &repo,
+            &FetchOptions {
+                since: Some(utc("2026-05-01T00:00:00Z")),
+                until: Some(utc("2026-05-10T00:00:00Z")),
+                ..FetchOptions::default()
+            },
+        );
+        assert_eq!(
+            dates(&commits),
+            ["2026-05-09T19:00:00-05:00", "2026-05-01T08:00:00+08:00"]
+        );
+    }
+
+    #[test]
+    fn limit_counts_only_commits_inside_the_window() {
+        let repo = TempRepo::new();
+        for hour in 10..13 {
+            repo.commit("A", &format!("2026-05-09T{hour}:00:00+00:00"));
+        }
+        // Newer than `until` but within the slack git is asked for, so git
+        // lists these first.
+        for hour in 10..13 {
+            repo.commit("A", &format!("2026-05-10T{hour}:00:00+00:00"));
+        }
+
+        let commits = fetch(
+            &repo,
+            &FetchOptions {
+                until: Some(utc("2026-05-10T00:00:00Z")),
+                limit: Some(2),
+                ..FetchOptions::default()
+            },
+        );
+        assert_eq!(
+            dates(&commits),
+            ["2026-05-09T12:00:00+00:00", "2026-05-09T11:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn limit_skips_dropped_merges() {
+        let repo = TempRepo::new();
+        repo.commit("A", "2026-05-01T10:00:00+00:00");
+        repo.git(&["checkout", "-q", "-b", "side"]);
+        repo.commit("A", "2026-05-02T10:00:00+00:00");
+        repo.git(&["checkout", "-q", "main"]);
+        repo.commit("A", "2026-05-03T10:00:00+00:00");
+        repo.git(&[
+            "-c",
+            "user.name=A",
+            "-c",
+            "user.email=a@example.com",
+            "merge",
+            "-q",
+            "--no-ff",
+            "--no-edit",
+            "side",
+        ]);
+
+        let fetched = fetch_commits(
+            repo.path(),
+            &FetchOptions {
+                limit: Some(1),
+                no_merges: true,
+                ..FetchOptions::default()
+            },
+            &Progress::new(&NoProgress),
+        )
+        .unwrap();
+        assert_eq!(fetched.merges_skipped, 1);
+        assert_eq!(fetched.commits.len(), 1);
+        assert!(!fetched.commits[0].subject.starts_with("Merge"));
+    }
+
+    #[test]
+    fn head_on_a_branch_is_the_symbolic_ref() {
+        let repo = TempRepo::new();
+        repo.commit("A", "2026-05-01T10:00:00+00:00");
+        let head = head_ref(repo.path()).unwrap();
+        assert!(matches!(&head, HeadRef::Branch { name } if name == "main"), "{head:?}");
+        assert_eq!(head.describe(), "main");
+    }
+
+    #[test]
+    fn detached_head_is_the_bare_sha() {
+        let repo = TempRepo::new();
+        repo.commit("A", "2026-05-01T10:00:00+00:00");
+        repo.git(&["checkout", "-q", "--detach"]);
+        let sha = git_output(repo.path(), &["rev-parse", "HEAD"]).unwrap().unwrap();
+
+        let HeadRef::Detached { sha: reported } = head_ref(repo.path()).unwrap() else {
+            panic!("expected a detached HEAD");
+        };
+        assert_eq!(reported, sha);
+        assert_eq!(fetch(&repo, &FetchOptions::default()).len(), 1);
+    }
+
+    #[test]
+    fn unborn_branch_is_empty_history() {
+        let repo = TempRepo::new();
+        repo.git(&["switch", "-q", "-c", "x"]);
+
+        let head = head_ref(repo.path()).unwrap();
+        assert!(matches!(&head, HeadRef::Unborn { name } if name == "x"), "{head:?}");
+        assert_eq!(head.describe(), "x（尚无提交）");
+        assert!(fetch(&repo, &FetchOptions::default()).is_empty());
+        assert!(full_log(repo.path(), &FetchOptions::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn git_stderr_reaches_the_error() {
+        let dir = std::env::temp_dir().join(format!("cowhorse-test-norepo-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let err = fetch_commits(&dir, &FetchOptions::default(), &Progress::new(&NoProgress))
+            .unwrap_err();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(crate::error::classify(&err).0, ErrorCode::NotARepo);
+    }
+
+    #[test]
+    fn only_lock_and_timeout_failures_are_transient() {
+        let failure =
+            |stderr: &str| anyhow::Error::new(git_failure("git log", Path::new("."), stderr.as_bytes()));
+        for stderr in [
+            "fatal: Unable to create '/repo/.git/index.lock': File exists.",
+            "fatal: cannot lock ref 'HEAD': Unable to create '/repo/.git/HEAD.lock': File exists.",
+            "error: Resource temporarily unavailable",
+            "fatal: unable to access 'https://example.com/': Operation timed out",
+        ] {
+            assert!(is_transient(&failure(stderr)), "{stderr}");
+        }
+        for stderr in [
+            "fatal: unable to read tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904",
+            "error: unable to read sha1 file of src/main.rs",
+            "fatal: cannot read object 4b825dc6",
+            "fatal: bad revision 'nope'",
+        ] {
+            assert!(!is_transient(&failure(stderr)), "{stderr}");
+        }
+        assert!(!is_transient(&anyhow::anyhow!("index.lock")));
+    }
+
+    const HASH_A: &str = "1111111111111111111111111111111111111111";
+    const HASH_B: &str = "2222222222222222222222222222222222222222";
+
+    /// One record as `git log -z` with [`PRETTY_FORMAT`] writes it.
+    fn record(hash: &str, author: &str, date: &str) -> String {
+        format!("{hash}\0{author}\0{date}\0{author}@example.com\0\0{date}\0subject\0\0")
+    }
+
+    fn parse(log: &str) -> Result<Vec<Commit>> {
+        read_log(log.as_bytes(), Path::new("."), &FetchOptions::default(), &Progress::new(&NoProgress))
+            .map(|fetched| fetched.commits)
+    }
+
+    #[test]
+    fn records_survive_newlines_separators_and_multibyte_names() {
+        let names = ["Zhang\nSan", "svn\x1fimport", "张三 🐄", "trailing\n"];
+        let log: Vec<String> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| record(&format!("{i}{}", &HASH_A[1..]), name, "2026-09-01T21:00:00+08:00"))
+            .collect();
+        // `-z` puts a NUL between records.
+        let commits = parse(&log.join("\0")).unwrap();
+        let authors: Vec<&str> = commits.iter().map(|c| c.author.as_str()).collect();
+        assert_eq!(authors, names);
+        assert_eq!(commits[2].email, "张三 🐄@example.com");
+    }
+
+    #[test]
+    fn malformed_records_name_the_commit() {
+        let bad_date = record(HASH_A, "A", "yesterday");
+        let err = format!("{:#}", parse(&bad_date).unwrap_err());
+        assert!(err.contains(HASH_A) && err.contains("yesterday"), "{err}");
+
+        let good = record(HASH_A, "A", "2026-09-01T21:00:00+08:00");
+        let truncated = format!("{good}\0{HASH_B}\0B\02026-09-01T21:00:00+08:00\0");
+        let err = format!("{:#}", parse(&truncated).unwrap_err());
+        assert!(err.contains(HASH_B) && err.contains("3 of 8"), "{err}");
+
+        let shifted = format!("not-a-hash\0{}", &good[HASH_A.len() + 1..]);
+        let err = format!("{:#}", parse(&shifted).unwrap_err());
+        assert!(err.contains("not-a-hash"), "{err}");
+    }
+
+    #[test]
+    fn unusual_names_round_trip_through_git() {
+        let repo = TempRepo::new();
+        repo.commit("svn\x1fimport 张三", "2026-09-01T21:00:00+08:00");
+        repo.commit("B", "2026-09-02T21:00:00+08:00");
+        let commits = fetch(&repo, &FetchOptions::default());
+        let mut authors: Vec<&str> = commits.iter().map(|c| c.author.as_str()).collect();
+        authors.sort();
+        assert_eq!(authors, ["B", "svn\x1fimport 张三"]);
+    }
 }
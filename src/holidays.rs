@@ -0,0 +1,194 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+
+/// Dates that override the weekly weekend rule: holidays are rest days
+/// whatever the weekday, makeup workdays (调休) are workdays even on a
+/// weekend day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    pub holidays: BTreeSet<NaiveDate>,
+    pub makeup_workdays: BTreeSet<NaiveDate>,
+}
+
+impl HolidayCalendar {
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+
+    pub fn is_makeup_workday(&self, date: NaiveDate) -> bool {
+        self.makeup_workdays.contains(&date)
+    }
+
+    /// Records `date` as a holiday or makeup workday, replacing any earlier
+    /// entry for it.
+    fn mark(&mut self, date: NaiveDate, workday: bool) {
+        if workday {
+            self.holidays.remove(&date);
+            self.makeup_workdays.insert(date);
+        } else {
+            self.makeup_workdays.remove(&date);
+            self.holidays.insert(date);
+        }
+    }
+}
+
+/// A built-in calendar for `--holiday-preset`. Entries are single dates or
+/// inclusive `start..end` ranges.
+struct Preset {
+    name: &'static str,
+    holidays: &'static [&'static str],
+    makeup_workdays: &'static [&'static str],
+}
+
+/// Mainland China statutory holidays and 调休 workdays as published by the
+/// State Council General Office for each year.
+const PRESETS: &[Preset] = &[
+    Preset {
+        name: "cn-2023",
+        holidays: &[
+            "2022-12-31..2023-01-02",
+            "2023-01-21..2023-01-27",
+            "2023-04-05",
+            "2023-04-29..2023-05-03",
+            "2023-06-22..2023-06-24",
+            "2023-09-29..2023-10-06",
+        ],
+        makeup_workdays: &[
+            "2023-01-28",
+            "2023-01-29",
+            "2023-04-23",
+            "2023-05-06",
+            "2023-06-25",
+            "2023-10-07",
+            "2023-10-08",
+        ],
+    },
+    Preset {
+        name: "cn-2024",
+        holidays: &[
+            "2024-01-01",
+            "2024-02-10..2024-02-17",
+            "2024-04-04..2024-04-06",
+            "2024-05-01..2024-05-05",
+            "2024-06-08..2024-06-10",
+            "2024-09-15..2024-09-17",
+            "2024-10-01..2024-10-07",
+        ],
+        makeup_workdays: &[
+            "2024-02-04",
+            "2024-02-18",
+            "2024-04-07",
+            "2024-04-28",
+            "2024-05-11",
+            "2024-09-14",
+            "2024-09-29",
+            "2024-10-12",
+        ],
+    },
+    Preset {
+        name: "cn-2025",
+        holidays: &[
+            "2025-01-01",
+            "2025-01-28..2025-02-04",
+            "2025-04-04..2025-04-06",
+            "2025-05-01..2025-05-05",
+            "2025-05-31..2025-06-02",
+            "2025-10-01..2025-10-08",
+        ],
+        makeup_workdays: &[
+            "2025-01-26",
+            "2025-02-08",
+            "2025-04-27",
+            "2025-09-28",
+            "2025-10-11",
+        ],
+    },
+];
+
+/// Builds the calendar for `--holiday-preset` names and an optional
+/// `--holidays` file. Later sources win on the same date, so a file can
+/// correct or extend a preset.
+pub fn resolve_holidays(presets: &[String], file: Option<&Path>) -> Result<HolidayCalendar> {
+    let mut calendar = HolidayCalendar::default();
+    for name in presets {
+        let Some(preset) = PRESETS.iter().find(|preset| preset.name == name.trim()) else {
+            let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+            bail!("未知的节假日预设：{name}（可用：{}）", names.join(", "));
+        };
+        for entry in preset.holidays {
+            for date in expand_entry(entry)? {
+                calendar.mark(date, false);
+            }
+        }
+        for entry in preset.makeup_workdays {
+            for date in expand_entry(entry)? {
+                calendar.mark(date, true);
+            }
+        }
+    }
+    if let Some(path) = file {
+        let from_file = load_holidays(path)?;
+        for date in from_file.holidays {
+            calendar.mark(date, false);
+        }
+        for date in from_file.makeup_workdays {
+            calendar.mark(date, true);
+        }
+    }
+    Ok(calendar)
+}
+
+fn expand_entry(entry: &str) -> Result<Vec<NaiveDate>> {
+    let parse = |raw: &str| {
+        NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .with_context(|| format!("节假日预设中的日期无效：{entry}"))
+    };
+    match entry.split_once("..") {
+        Some((start, end)) => {
+            let end = parse(end)?;
+            Ok(parse(start)?
+                .iter_days()
+                .take_while(|date| *date <= end)
+                .collect())
+        }
+        None => Ok(vec![parse(entry)?]),
+    }
+}
+
+/// Reads a `--holidays` file: one `YYYY-MM-DD` per line, `!YYYY-MM-DD` for
+/// a makeup workday, `#` starts a comment.
+fn load_holidays(path: &Path) -> Result<HolidayCalendar> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("无法读取节假日文件：{}", path.display()))?;
+    parse_holidays(&data).with_context(|| format!("节假日文件格式错误：{}", path.display()))
+}
+
+fn parse_holidays(data: &str) -> Result<HolidayCalendar> {
+    let mut calendar = HolidayCalendar::default();
+    for (idx, line) in data.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (raw, workday) = match line.strip_prefix('!') {
+            Some(rest) => (rest.trim(), true),
+            None => (line, false),
+        };
+        let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") else {
+            bail!("第 {} 行无法解析为日期：{line:?}（应为 YYYY-MM-DD 或 !YYYY-MM-DD）", idx + 1);
+        };
+        if workday {
+            calendar.makeup_workdays.insert(date);
+        } else {
+            calendar.holidays.insert(date);
+        }
+    }
+    if let Some(date) = calendar.holidays.intersection(&calendar.makeup_workdays).next() {
+        bail!("{date} 同时被标记为节假日和调休工作日");
+    }
+    Ok(calendar)
+}
@@ -0,0 +1,746 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+
+/// Language of the human-facing text. JSON field names and other
+/// machine-readable output stay the same in every language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+/// Ids of the translated messages. A `{}` in a message is a slot filled by
+/// [`Lang::format`], in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    Heading,
+    Note,
+    LowConfidence,
+    LowConfidenceSuffix,
+    ReposOverview,
+    Combined,
+    FailedRepos,
+    FailedRepoRow,
+    ScoredRow,
+    AuthorRow,
+    Repo,
+    RepoWithHead,
+    Branches,
+    AllBranches,
+    Window,
+    WindowWithRange,
+    Timezone,
+    TimezoneViews,
+    WorkHours,
+    Coverage,
+    CoverageMet,
+    CoverageMissed,
+    UserConfig,
+    RepoConfig,
+    AuthorFilter,
+    PathFilter,
+    IgnoredDefault,
+    IgnoredUser,
+    IgnoredPattern,
+    IgnoredBot,
+    IgnoredLine,
+    ExcludedSpan,
+    ExcludedSpans,
+    SkippedMerges,
+    AliasesMerged,
+    Analyzed,
+    Score,
+    BandJustEntered,
+    BandBelowNext,
+    BandAboveFloor,
+    CustomWeights,
+    Forecast,
+    AfterHoursLine,
+    WeekendNotCounted,
+    WeekendLine,
+    HolidayLine,
+    NightLine,
+    Deduplicated,
+    OvertimeDaysLine,
+    EstimatedOvertimeLine,
+    WorkdaySpanLine,
+    Weeks996Line,
+    Worst996Weeks,
+    Week996Entry,
+    Authors996,
+    Author996Entry,
+    LongestStreakLine,
+    CurrentStreakLine,
+    OvertimeStreakLine,
+    AllNighters,
+    WorstAllNighter,
+    BusiestDayLine,
+    WeeklySparkline,
+    TrendLine,
+    TrendInsufficient,
+    TrendImproving,
+    TrendStable,
+    TrendWorsening,
+    CrunchPeriods,
+    CrunchRow,
+    CrunchLegend,
+    Breaks,
+    BreakRow,
+    NoBreakRow,
+    BreaksOmitted,
+    DaysSinceBreak,
+    NobodyBreaks,
+    PunchCard,
+    PunchCardLegend,
+    Calendar,
+    CalendarOlderWeeks,
+    CalendarLegend,
+    CalendarLegendColor,
+    CalendarLegendShape,
+    RecentWeeks,
+    WeeklyHeader,
+    Submodules,
+    ComponentRow,
+    Directories,
+    SkippedSubmodules,
+    Sensitivity,
+    SensitivityHeader,
+    DriftWarnings,
+    DriftRow,
+    PolicyChecks,
+    PolicyRow,
+    PolicyPassed,
+    PolicyFailed,
+    Attribution,
+    AttributionAfterHours,
+    AttributionWeekend,
+    AttributionNight,
+    AttributionLegend,
+    AttributionOthers,
+    SuspectIdentities,
+    SuspectRow,
+    SuspectHeavyDays,
+    SuspectSameMinute,
+    SuspectMarker,
+    SuspectWarning,
+    ListSeparator,
+    Comma,
+    NightOwls,
+    ChillAuthors,
+    BelowMinCommits,
+    AllAuthors,
+    NightCommits,
+    AfterHoursCommits,
+    WeekendCommits,
+    CommitListing,
+    BreakdownTerm,
+    BreakdownColumns,
+    TermAfterHours,
+    TermWeekend,
+    TermNight,
+    TermOvertimeDays,
+    TermStreak,
+    BadgeLabel,
+    MdTitle,
+    MdHead,
+    MdWindow,
+    MdRange,
+    MdNote,
+    MdKeyMetrics,
+    MdSeverity,
+    MdScoreValue,
+    MdAnalyzed,
+    MdAnalyzedValue,
+    MdCountShare,
+    MdNightCommits,
+    MdOvertimeDays,
+    MdDays,
+    MdLongestStreak,
+    MdBusiestDay,
+    MdAuthorTable,
+    MdBelowMinCommits,
+    MdSection,
+    HourChart,
+    WeekdayChart,
+    ChartMarksAfterHours,
+    ChartMarksWeekend,
+    HtmlLang,
+    HtmlTitle,
+    HtmlWindow,
+    HtmlRange,
+    HtmlNote,
+    HtmlScoreLabel,
+    HtmlCommitsCard,
+    HtmlLongestStreak,
+    HtmlCalendar,
+    HtmlDayTitle,
+    HtmlCalendarLegend,
+    HtmlAuthorHeader,
+    HtmlFooter,
+    AuthorReportTitle,
+    AuthorCommitsLine,
+    AuthorFirstLast,
+    AuthorDaysLine,
+    AuthorOvertimeHours,
+    AuthorPerRepo,
+    AuthorRepoRow,
+    AuthorMostAfterHoursRepo,
+    AuthorVsRepo,
+    AuthorRatioRow,
+    PushLongGap,
+    PushShortGap,
+    PushFirst,
+}
+
+impl Msg {
+    /// `(中文, English)`
+    fn texts(self) -> (&'static str, &'static str) {
+        match self {
+            Msg::Heading => ("\n{}：", "\n{}:"),
+            Msg::Note => ("注意：{}", "Note: {}"),
+            Msg::LowConfidence => (
+                "分析窗口内只有 {} 个工作日，下班后/周末占比不具参考意义",
+                "only {} working days in the window; the after-hours and weekend ratios mean little",
+            ),
+            Msg::LowConfidenceSuffix => ("（样本不足）", " (small sample)"),
+            Msg::ReposOverview => ("各仓库概览（共 {} 个）：", "Repositories ({}):"),
+            Msg::Combined => ("\n合计：", "\nCombined:"),
+            Msg::FailedRepos => (
+                "\n分析失败、未计入合计的仓库（{} 个）：",
+                "\nRepositories that failed and are not in the totals ({}):",
+            ),
+            Msg::FailedRepoRow => ("  - {} [{}]：{}", "  - {} [{}]: {}"),
+            Msg::ScoredRow => (
+                "  - {} -> {} 次提交 | {}% 下班后 | 牛马指数 {}",
+                "  - {} -> {} commits | {}% after hours | severity {}",
+            ),
+            Msg::AuthorRow => (
+                "  - {} -> {} 次提交 | {}% 下班后 | {} 次周末 | {} 次深夜{}",
+                "  - {} -> {} commits | {}% after hours | {} weekend | {} night{}",
+            ),
+            Msg::Repo => ("仓库：{}", "Repository: {}"),
+            Msg::RepoWithHead => ("仓库：{}（HEAD：{}）", "Repository: {} (HEAD: {})"),
+            Msg::Branches => ("分支：{}", "Branches: {}"),
+            Msg::AllBranches => ("分支：全部引用（--all-branches）", "Branches: all refs (--all-branches)"),
+            Msg::Window => ("窗口：{}", "Window: {}"),
+            Msg::WindowWithRange => (
+                "窗口：{} / 实际提交范围：{}  ->  {}",
+                "Window: {} / commits from {}  ->  {}",
+            ),
+            Msg::Timezone => ("时区：所有提交按 {} 判定", "Time zone: every commit read in {}"),
+            Msg::TimezoneViews => (
+                "  按提交者本地时间，下班后占 {}%；按 {} 时间则为 {}%",
+                "  In each committer's local time {}% is after hours; in {} time it is {}%",
+            ),
+            Msg::WorkHours => ("工作时间：{}", "Working hours: {}"),
+            Msg::Coverage => (
+                "覆盖目标：{} 个提交日，实际覆盖 {} 天（{}）",
+                "Coverage goal: {} commit days, {} covered ({})",
+            ),
+            Msg::CoverageMet => ("已达成", "met"),
+            Msg::CoverageMissed => ("历史不足，未达成", "not met, history too short"),
+            Msg::UserConfig => ("已应用用户配置：{}", "User config applied: {}"),
+            Msg::RepoConfig => ("已应用仓库配置：{}", "Repository config applied: {}"),
+            Msg::AuthorFilter => ("作者过滤：{}", "Author filter: {}"),
+            Msg::PathFilter => ("路径过滤：{}", "Path filter: {}"),
+            Msg::IgnoredDefault => ("默认忽略", "Ignored by default"),
+            Msg::IgnoredUser => ("忽略作者", "Ignored authors"),
+            Msg::IgnoredPattern => ("忽略模式", "Ignore patterns"),
+            Msg::IgnoredBot => ("排除机器人", "Bots excluded"),
+            Msg::IgnoredLine => ("{}：{}（共剔除 {} 次提交）", "{}: {} ({} commits dropped)"),
+            Msg::ExcludedSpan => ("{} {}（{} 次）", "{} {} ({} commits)"),
+            Msg::ExcludedSpans => ("排除时段：{}", "Excluded spans: {}"),
+            Msg::SkippedMerges => ("排除合并提交：{} 次", "Merge commits excluded: {}"),
+            Msg::AliasesMerged => ("别名合并：{}", "Aliases merged: {}"),
+            Msg::Analyzed => (
+                "分析提交：{}（作者：{} 人，活跃天数：{} 天）",
+                "Commits analyzed: {} ({} authors, {} active days)",
+            ),
+            Msg::Score => ("牛马指数：{}/100 -> {}{}", "Severity: {}/100 -> {}{}"),
+            Msg::BandJustEntered => ("（刚过本档下限 {} 分）", " (only {} points into this band)"),
+            Msg::BandBelowNext => ("（距下一档还差 {} 分）", " ({} points below the next band)"),
+            Msg::BandAboveFloor => ("（高出本档下限 {} 分）", " ({} points above this band's floor)"),
+            Msg::CustomWeights => (
+                "  （使用自定义权重：{}，与默认权重的分数不可直接比较）",
+                "  (custom weights: {}; not comparable with scores under the default weights)",
+            ),
+            Msg::Forecast => (
+                "【预测】截至 {}：牛马指数 {}/100 -> {}；预计提交 {} 次，其中下班后 {} 次（剩余 {} 个工作日、{} 个周末日）",
+                "[Forecast] By {}: severity {}/100 -> {}; about {} commits, {} of them after hours ({} weekdays and {} weekend days left)",
+            ),
+            Msg::AfterHoursLine => ("下班后提交：{}（{}%）", "After-hours commits: {} ({}%)"),
+            Msg::WeekendNotCounted => (
+                "周末提交：不统计（--weekend none）",
+                "Weekend commits: not counted (--weekend none)",
+            ),
+            Msg::WeekendLine => ("周末提交{}：{}（{}%）", "Weekend commits{}: {} ({}%)"),
+            Msg::HolidayLine => (
+                "节假日提交（已计入周末）：{}（{}%）",
+                "Holiday commits (counted as weekend): {} ({}%)",
+            ),
+            Msg::NightLine => ("深夜提交 ({})：{}（{}%）", "Night commits ({}): {} ({}%)"),
+            Msg::Deduplicated => (
+                "指数按去重口径计算（每次提交只计入 深夜 > 周末 > 下班后 中的一项）：深夜 {} / 周末 {} / 下班后 {}",
+                "Score uses deduplicated buckets (each commit counts once, night > weekend > after hours): night {} / weekend {} / after hours {}",
+            ),
+            Msg::OvertimeDaysLine => ("加班天数：{} / {} 天", "Overtime days: {} / {}"),
+            Msg::EstimatedOvertimeLine => (
+                "估算加班时长：约 {} 人·小时（估算值：间隔不到 {} 分钟的提交算同一段工作，每段从首次提交前 {} 分钟算起）",
+                "Estimated overtime: about {} person-hours (an estimate: commits less than {} minutes apart form one session, starting {} minutes before its first commit)",
+            ),
+            Msg::WorkdaySpanLine => (
+                "平均在岗跨度：{} 小时（中位数 {} / P90 {}），超过 {} 小时的天数：{} 天",
+                "Average workday span: {} hours (median {} / p90 {}), days over {} hours: {}",
+            ),
+            Msg::Weeks996Line => (
+                "疑似 996 周数：{} / {}（一周至少 6 天有提交，首次提交中位数不晚于 10:30，最后一次不早于 21:00）",
+                "Weeks that look like 996: {} / {} (commits on 6+ days, median first commit by 10:30, median last at 21:00 or later)",
+            ),
+            Msg::Worst996Weeks => ("  最严重的几周：{}", "  Worst weeks: {}"),
+            Msg::Week996Entry => ("{}（{} 天，{} → {}）", "{} ({} days, {} → {})"),
+            Msg::Authors996 => ("  涉及作者：{}", "  Authors: {}"),
+            Msg::Author996Entry => ("{}（{} 周）", "{} ({} weeks)"),
+            Msg::LongestStreakLine => ("最长连续工作天数：{} 天", "Longest streak: {} days"),
+            Msg::OvertimeStreakLine => (
+                "最长连续加班天数：{} 天（{} ~ {}，每天都有下班后提交）",
+                "Longest overtime streak: {} days ({} to {}, each with an after-hours commit)",
+            ),
+            Msg::CurrentStreakLine => (
+                "当前连续工作天数：{} 天（仍在继续）",
+                "Current streak: {} days (ongoing)",
+            ),
+            Msg::AllNighters => ("通宵事件：{} 次", "All-nighters: {}"),
+            Msg::WorstAllNighter => (
+                "最狠的一次通宵：{} 于 {} 起，持续 {} 小时，{} 次提交",
+                "Worst all-nighter: {} from {}, {} hours, {} commits",
+            ),
+            Msg::BusiestDayLine => (
+                "最忙的一天：{} -> {} 次提交（{} 次下班后，约 {} 分钟下班后工作）",
+                "Busiest day: {} -> {} commits ({} after hours, about {} after-hours minutes)",
+            ),
+            Msg::WeeklySparkline => (
+                "近{}周加班占比：{}（{}%→{}%）",
+                "After-hours share, last {} weeks: {} ({}%→{}%)",
+            ),
+            Msg::TrendLine => (
+                "趋势：{}（近半段加班占比 {} 个百分点）",
+                "Trend: {} (after-hours share in the recent half {} points)",
+            ),
+            Msg::TrendInsufficient => (
+                "趋势：数据不足（提交跨度不到两周）",
+                "Trend: insufficient data (commits span less than two weeks)",
+            ),
+            Msg::TrendImproving => ("好转中", "improving"),
+            Msg::TrendStable => ("平稳", "stable"),
+            Msg::TrendWorsening => ("恶化中", "worsening"),
+            Msg::CrunchPeriods => ("冲刺/赶工时段", "Crunch periods"),
+            Msg::CrunchRow => (
+                "  - {} ~ {}：{} 次提交，下班后占比 {}%",
+                "  - {} to {}: {} commits, {}% after hours",
+            ),
+            Msg::CrunchLegend => (
+                "  （14 天滑动窗口：提交量高于中位数且下班后占比超过 {}%）",
+                "  (14-day sliding windows busier than the median with more than {}% after hours)",
+            ),
+            Msg::Breaks => ("休假/空档", "Breaks"),
+            Msg::BreakRow => (
+                "  - {}（{}）：休息 {} 次，最长 {} 天",
+                "  - {} ({}): {} breaks, longest {} days",
+            ),
+            Msg::NoBreakRow => (
+                "  - {}（{}）：没有连续 {} 天以上的空档",
+                "  - {} ({}): no break of {}+ days",
+            ),
+            Msg::BreaksOmitted => (
+                "  （另有 {} 位作者未列出，见 JSON 的 breaks）",
+                "  ({} more authors in the JSON `breaks`)",
+            ),
+            Msg::DaysSinceBreak => (
+                "距离最近一次有人连休 {} 天以上：{} 天",
+                "Days since anyone took a {}+ day break: {}",
+            ),
+            Msg::NobodyBreaks => (
+                "窗口内没有人连休 {} 天以上",
+                "Nobody took a {}+ day break in the window",
+            ),
+            Msg::PunchCard => ("提交时间分布（按星期 x 小时）", "Commits by weekday x hour"),
+            Msg::PunchCardLegend => (
+                "  每格为该小时的提交数（{}，最多 {} 次）；^ 标记下班后时段",
+                "  Each cell is the commits in that hour ({}, at most {}); ^ marks after-hours slots",
+            ),
+            Msg::Calendar => ("提交日历", "Commit calendar"),
+            Msg::CalendarOlderWeeks => (
+                "  更早的 {} 周未画出：共 {} 次提交，{} 天有提交，其中 {} 天以下班后为主",
+                "  {} older weeks not drawn: {} commits on {} days, {} of them mostly after hours",
+            ),
+            Msg::CalendarLegend => (
+                "  · 无提交，░▒▓█ 提交越多颜色越深（单日最多 {} 次）{}",
+                "  · no commits, ░▒▓█ darker means more commits (at most {} a day){}",
+            ),
+            Msg::CalendarLegendColor => (
+                "；红色表示当天一半以上提交在下班后",
+                "; red marks days with over half the commits after hours",
+            ),
+            Msg::CalendarLegendShape => (
+                "；{} 表示当天一半以上提交在下班后",
+                "; {} marks days with over half the commits after hours",
+            ),
+            Msg::RecentWeeks => ("最近 {} 周", "Last {} weeks"),
+            Msg::WeeklyHeader => (
+                "  周        | 提交 | 下班后 | 占比   | 周末 | 深夜",
+                "  Week      | Commits | After hours | Share  | Weekend | Night",
+            ),
+            Msg::Submodules => ("子模块分布", "By submodule"),
+            Msg::ComponentRow => (
+                "  - {} -> {} 次提交 | {}% 下班后",
+                "  - {} -> {} commits | {}% after hours",
+            ),
+            Msg::Directories => ("目录分布", "By directory"),
+            Msg::SkippedSubmodules => (
+                "未初始化的子模块（已跳过）：{}",
+                "Uninitialized submodules (skipped): {}",
+            ),
+            Msg::Sensitivity => (
+                "下班时间敏感性（上班时间固定 {}）",
+                "Work-end sensitivity (work start fixed at {})",
+            ),
+            Msg::SensitivityHeader => (
+                "  下班时间 | 下班后提交 | 占比   | 加班天数 | 牛马指数",
+                "  Work end | After hours | Share  | Overtime days | Severity",
+            ),
+            Msg::DriftWarnings => ("作息漂移预警", "Schedule drift"),
+            Msg::DriftRow => (
+                "  - {} 的常见提交时间在窗口内推迟了约 {} 分钟",
+                "  - {}: typical commit time moved about {} minutes later over the window",
+            ),
+            Msg::PolicyChecks => ("策略检查", "Policy checks"),
+            Msg::PolicyRow => ("  [{}] {} {} {} （实际 {}）", "  [{}] {} {} {} (actual {})"),
+            Msg::PolicyPassed => ("通过", "pass"),
+            Msg::PolicyFailed => ("未通过", "FAIL"),
+            Msg::Attribution => ("加班贡献构成", "Where the overtime comes from"),
+            Msg::AttributionAfterHours => ("下班后", "After hours"),
+            Msg::AttributionWeekend => ("周末", "Weekend"),
+            Msg::AttributionNight => ("深夜", "Night"),
+            Msg::AttributionLegend => (
+                "  {} {} -> 下班后 {}% | 周末 {}% | 深夜 {}%",
+                "  {} {} -> after hours {}% | weekend {}% | night {}%",
+            ),
+            Msg::AttributionOthers => ("其他", "Others"),
+            Msg::SuspectIdentities => ("疑似多人合并的身份", "Identities that may be several people"),
+            Msg::SuspectRow => ("  - {}（来源：{}）：{}", "  - {} (from: {}): {}"),
+            Msg::SuspectHeavyDays => (
+                "{} 天的提交数超过每日上限 {}",
+                "{} days above the daily limit of {} commits",
+            ),
+            Msg::SuspectSameMinute => (
+                "{} 次在同一分钟内出现 {}+ 次来自不同原始名字的提交",
+                "{} minutes with {}+ commits from different raw names",
+            ),
+            Msg::SuspectMarker => (" ⚠ 疑似多人合并", " ⚠ may be several people"),
+            Msg::SuspectWarning => (
+                "警告：{} 可能合并了多个人（{}），来源名字：{}。请检查 --alias / .cowhorse.toml 的别名规则。",
+                "Warning: {} may be several people ({}), merged from: {}. Check the aliases in --alias / .cowhorse.toml.",
+            ),
+            Msg::ListSeparator => ("；", "; "),
+            Msg::Comma => ("、", ", "),
+            Msg::NightOwls => ("夜猫子榜单", "Night owls"),
+            Msg::ChillAuthors => ("摸鱼榜单", "Most relaxed"),
+            Msg::BelowMinCommits => (
+                "\n（另有 {} 位作者提交少于 {} 次，未列入榜单，但仍计入总数；可用 --min-commits 调整）",
+                "\n({} more authors with fewer than {} commits are left off the leaderboards but still count toward the totals; see --min-commits)",
+            ),
+            Msg::AllAuthors => ("全部作者（共 {} 位）", "All authors ({})"),
+            Msg::NightCommits => ("深夜提交", "Night commits"),
+            Msg::AfterHoursCommits => ("下班后提交", "After-hours commits"),
+            Msg::WeekendCommits => ("周末提交", "Weekend commits"),
+            Msg::CommitListing => (
+                "\n{}明细（共 {} 次，显示最近 {} 次）：",
+                "\n{} ({} in total, latest {} shown):",
+            ),
+            Msg::BreakdownTerm => ("构成", "Term"),
+            Msg::BreakdownColumns => ("   占比    权重   得分", "  Share  Weight Points"),
+            Msg::TermAfterHours => ("下班后提交占比", "After-hours share"),
+            Msg::TermWeekend => ("周末提交占比", "Weekend share"),
+            Msg::TermNight => ("深夜提交占比", "Night share"),
+            Msg::TermOvertimeDays => ("加班天数占比", "Overtime-day share"),
+            Msg::TermStreak => ("最长连续提交", "Longest streak"),
+            Msg::BadgeLabel => ("牛马指数", "workhorse index"),
+            Msg::MdTitle => ("# 牛马报告：{}\n\n", "# Workhorse report: {}\n\n"),
+            Msg::MdHead => ("- HEAD：{}\n", "- HEAD: {}\n"),
+            Msg::MdWindow => ("- 窗口：{}\n", "- Window: {}\n"),
+            Msg::MdRange => ("- 实际提交范围：{} -> {}\n", "- Commits from: {} -> {}\n"),
+            Msg::MdNote => ("- 注意：{}\n", "- Note: {}\n"),
+            Msg::MdKeyMetrics => (
+                "\n## 关键指标\n\n| 指标 | 数值 |\n| --- | --- |\n",
+                "\n## Key metrics\n\n| Metric | Value |\n| --- | --- |\n",
+            ),
+            Msg::MdSeverity => ("牛马指数", "Severity"),
+            Msg::MdScoreValue => ("{}/100（{}）", "{}/100 ({})"),
+            Msg::MdAnalyzed => ("分析提交", "Commits analyzed"),
+            Msg::MdAnalyzedValue => (
+                "{}（作者 {} 人，活跃 {} 天）",
+                "{} ({} authors, {} active days)",
+            ),
+            Msg::MdCountShare => ("{}（{}%）", "{} ({}%)"),
+            Msg::MdNightCommits => ("深夜提交（{}）", "Night commits ({})"),
+            Msg::MdOvertimeDays => ("加班天数", "Overtime days"),
+            Msg::MdDays => ("{} 天", "{} days"),
+            Msg::MdLongestStreak => ("最长连续工作天数", "Longest streak"),
+            Msg::MdBusiestDay => (
+                "| 最忙的一天 | {}：{} 次提交（{} 次下班后） |\n",
+                "| Busiest day | {}: {} commits ({} after hours) |\n",
+            ),
+            Msg::MdAuthorTable => (
+                "\n## {}\n\n| 作者 | 提交 | 下班后占比 | 周末 | 深夜 |\n| --- | ---: | ---: | ---: | ---: |\n",
+                "\n## {}\n\n| Author | Commits | After-hours share | Weekend | Night |\n| --- | ---: | ---: | ---: | ---: |\n",
+            ),
+            Msg::MdBelowMinCommits => (
+                "\n另有 {} 位作者提交少于 {} 次，未列入榜单。\n",
+                "\n{} more authors with fewer than {} commits are not listed.\n",
+            ),
+            Msg::MdSection => ("\n## {}\n\n", "\n## {}\n\n"),
+            Msg::HourChart => ("提交时间分布", "Commits by hour"),
+            Msg::WeekdayChart => ("星期分布", "Commits by weekday"),
+            Msg::ChartMarksAfterHours => ("{} 标记下班后时段", "{} marks after-hours slots"),
+            Msg::ChartMarksWeekend => ("{} 标记周末", "{} marks weekend days"),
+            Msg::HtmlLang => ("zh-CN", "en"),
+            Msg::HtmlTitle => ("牛马报告：{}", "Workhorse report: {}"),
+            Msg::HtmlWindow => ("窗口：{}", "Window: {}"),
+            Msg::HtmlRange => (" / 实际提交范围：{} -> {}", " / commits from {} -> {}"),
+            Msg::HtmlNote => ("注意：{}", "Note: {}"),
+            Msg::HtmlScoreLabel => ("牛马指数 · {}", "Severity · {}"),
+            Msg::HtmlCommitsCard => ("提交（{} 位作者）", "Commits ({} authors)"),
+            Msg::HtmlLongestStreak => ("最长连续工作", "Longest streak"),
+            Msg::HtmlCalendar => ("提交日历", "Commit calendar"),
+            Msg::HtmlDayTitle => ("{} {} 次", "{}: {} commits"),
+            Msg::HtmlCalendarLegend => (
+                "颜色越深提交越多；红框表示当天一半以上提交在下班后。",
+                "Darker days have more commits; a red outline means at least half of the day's commits were after hours.",
+            ),
+            Msg::HtmlAuthorHeader => (
+                "<tr><th>作者</th><th>提交</th><th>下班后占比</th><th>周末</th><th>深夜</th></tr>",
+                "<tr><th>Author</th><th>Commits</th><th>After-hours share</th><th>Weekend</th><th>Night</th></tr>",
+            ),
+            Msg::HtmlFooter => ("生成于 {}，命令：{}", "Generated at {} by {}"),
+            Msg::AuthorReportTitle => ("作者报告：{}", "Author report: {}"),
+            Msg::AuthorCommitsLine => ("提交数：{}（占仓库 {}%）", "Commits: {} ({}% of the repository)"),
+            Msg::AuthorFirstLast => (
+                "首次提交：{} / 最后一次提交：{}",
+                "First commit: {} / last commit: {}",
+            ),
+            Msg::AuthorDaysLine => (
+                "提交天数：{} 天，最长连续工作 {} 天",
+                "Commit days: {}, longest streak {} days",
+            ),
+            Msg::AuthorOvertimeHours => (
+                "估算加班时长：约 {} 小时（按提交会话估算）",
+                "Estimated overtime: about {} hours (from commit sessions)",
+            ),
+            Msg::AuthorPerRepo => ("各仓库", "Per repository"),
+            Msg::AuthorRepoRow => (
+                "  {}：{} 次提交，下班后 {} 次，估算加班约 {} 小时",
+                "  {}: {} commits, {} after hours, about {} overtime hours",
+            ),
+            Msg::AuthorMostAfterHoursRepo => (
+                "  下班后提交最多的仓库：{}",
+                "  Most after-hours commits in: {}",
+            ),
+            Msg::AuthorVsRepo => ("与仓库平均对比", "Compared with the repository"),
+            Msg::AuthorRatioRow => (
+                "  {} {} 次 | 本人 {}% / 仓库 {}%",
+                "  {} {} | author {}% / repo {}%",
+            ),
+            Msg::PushLongGap => (
+                "距离上一次 git push 已经过了 {} 分钟，出去走走喝杯水再回来继续吧！",
+                "{} minutes since your last git push. Take a walk and grab some water before you carry on!",
+            ),
+            Msg::PushShortGap => (
+                "距上次 push 仅 {} 分钟（提醒阈值 {} 分钟）。继续保持，但别忘了补水~",
+                "Only {} minutes since the last push (reminder after {}). Keep going, but stay hydrated~",
+            ),
+            Msg::PushFirst => (
+                "第一次记录 push，完成后我会提醒你注意休息。",
+                "First push recorded; I'll remind you to take breaks from now on.",
+            ),
+        }
+    }
+}
+
+/// Labels of the severity bands, lowest first (see `metrics::severity_band`).
+const BAND_LABELS: [(&str, &str); 5] = [
+    ("轻松自在", "Relaxed"),
+    ("基本健康", "Mostly healthy"),
+    ("持续加班", "Regular overtime"),
+    ("半牛马状态", "Half workhorse"),
+    ("全面牛马预警", "Full workhorse alert"),
+];
+
+/// Encouragement / roast lines per severity band; every band has the same
+/// number of lines in both languages so a seed picks the same slot.
+const QUIPS: [&[(&str, &str)]; 5] = [
+    &[
+        ("节奏健康，继续保持准点下班的好习惯。", "Healthy pace. Keep leaving on time."),
+        (
+            "这么轻松，是不是该多接两个需求了？",
+            "This relaxed? Maybe pick up a couple more tickets.",
+        ),
+        ("下班就是下班，值得表扬。", "Off the clock means off the clock. Well done."),
+    ],
+    &[
+        (
+            "偶尔加个班，问题不大，记得按时吃饭。",
+            "A late night now and then is fine. Don't skip meals.",
+        ),
+        ("整体健康，别让偶尔变成经常。", "Mostly healthy. Don't let now and then become always."),
+        (
+            "还算体面，周末记得出门晒晒太阳。",
+            "Still respectable. Get some sun this weekend.",
+        ),
+    ],
+    &[
+        (
+            "加班已成习惯，该和排期好好谈谈了。",
+            "Overtime is a habit now. Time to talk about the schedule.",
+        ),
+        ("咖啡续命不是长久之计。", "Running on coffee is not a plan."),
+        ("工位的灯比你更需要休息。", "Even your desk lamp needs a break."),
+    ],
+    &[
+        (
+            "半只脚已经踏进牛马圈，赶紧刹车。",
+            "One foot in the workhorse stable already. Hit the brakes.",
+        ),
+        ("你的 commit 时间在替你喊累。", "Your commit times are saying you're tired."),
+        (
+            "建议把“明天再说”写进 README。",
+            "Consider adding \"it can wait until tomorrow\" to the README.",
+        ),
+    ],
+    &[
+        (
+            "全面牛马预警：请立即放下键盘，出门走走。",
+            "Full workhorse alert: put the keyboard down and go outside.",
+        ),
+        ("这不是敏捷，这是极限生存。", "This isn't agile, it's survival mode."),
+        ("git log 看了都心疼。", "Even git log feels sorry for you."),
+    ],
+];
+
+const WEEKDAYS: [(&str, &str); 7] = [
+    ("周一", "Mon"),
+    ("周二", "Tue"),
+    ("周三", "Wed"),
+    ("周四", "Thu"),
+    ("周五", "Fri"),
+    ("周六", "Sat"),
+    ("周日", "Sun"),
+];
+
+/// Weekday labels of the calendar rows, Monday first.
+const WEEKDAYS_SHORT: [(&str, &str); 7] = [
+    ("一", "Mo"),
+    ("二", "Tu"),
+    ("三", "We"),
+    ("四", "Th"),
+    ("五", "Fr"),
+    ("六", "Sa"),
+    ("日", "Su"),
+];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Lines available for `band`, the same in every language.
+pub fn quip_count(band: usize) -> usize {
+    QUIPS[band.min(QUIPS.len() - 1)].len()
+}
+
+impl Lang {
+    /// `zh` or `en` depending on the language.
+    pub fn pick<T>(self, zh: T, en: T) -> T {
+        match self {
+            Lang::Zh => zh,
+            Lang::En => en,
+        }
+    }
+
+    pub fn text(self, msg: Msg) -> &'static str {
+        let (zh, en) = msg.texts();
+        self.pick(zh, en)
+    }
+
+    /// The message with each `{}` replaced by the next of `args`; widths
+    /// and precision are up to the caller.
+    pub fn format(self, msg: Msg, args: &[&dyn Display]) -> String {
+        let mut out = String::new();
+        let mut args = args.iter();
+        for (i, part) in self.text(msg).split("{}").enumerate() {
+            if i > 0
+                && let Some(arg) = args.next()
+            {
+                out.push_str(&arg.to_string());
+            }
+            out.push_str(part);
+        }
+        out
+    }
+
+    pub fn band_label(self, index: usize) -> &'static str {
+        let (zh, en) = BAND_LABELS[index.min(BAND_LABELS.len() - 1)];
+        self.pick(zh, en)
+    }
+
+    pub fn quip(self, band: usize, index: usize) -> &'static str {
+        let (zh, en) = QUIPS[band.min(QUIPS.len() - 1)][index];
+        self.pick(zh, en)
+    }
+
+    /// Monday first.
+    pub fn weekday(self, index: usize) -> &'static str {
+        let (zh, en) = WEEKDAYS[index];
+        self.pick(zh, en)
+    }
+
+    /// Two columns wide in either language, Monday first.
+    pub fn weekday_short(self, index: usize) -> &'static str {
+        let (zh, en) = WEEKDAYS_SHORT[index];
+        self.pick(zh, en)
+    }
+
+    /// `month` is 1-based.
+    pub fn month(self, month: u32) -> String {
+        match self {
+            Lang::Zh => format!("{month}月"),
+            Lang::En => MONTHS[month as usize - 1].to_string(),
+        }
+    }
+}
+
+/// Terminal columns `text` takes: CJK and full-width characters count as
+/// two.
+pub fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// `text` followed by spaces up to `width` columns.
+pub fn pad_to(text: &str, width: usize) -> String {
+    format!("{text}{}", " ".repeat(width.saturating_sub(display_width(text))))
+}
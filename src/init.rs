@@ -0,0 +1,142 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use clap::Parser;
+
+use crate::config::REPO_CONFIG_FILE;
+use crate::gitlog::{fetch_commits, Commit, FetchOptions};
+use crate::metrics::WorkSchedule;
+use crate::progress::{NoProgress, Progress};
+
+#[derive(Parser, Debug)]
+#[command(about = "在仓库根目录生成一份带注释的 .cowhorse.toml", name = "init")]
+pub struct InitCli {
+    /// 要生成配置的仓库
+    #[arg(long, default_value = ".", value_name = "PATH")]
+    pub path: PathBuf,
+
+    /// 参考最近多少天的提交来推断默认值
+    #[arg(long, default_value_t = 90, value_name = "DAYS")]
+    pub days: u32,
+
+    /// 覆盖已存在的 .cowhorse.toml
+    #[arg(long)]
+    pub force: bool,
+}
+
+pub fn run_init(args: &InitCli) -> Result<()> {
+    let path = args.path.join(REPO_CONFIG_FILE);
+    if path.exists() && !args.force {
+        bail!("{} 已存在，如需覆盖请加上 --force", path.display());
+    }
+
+    let options = FetchOptions {
+        since: Some(Utc::now() - Duration::days(args.days as i64)),
+        early_stop_slack: Some(100),
+        mailmap: true,
+        ..FetchOptions::default()
+    };
+    let commits = fetch_commits(&args.path, &options, &Progress::new(&NoProgress))?.commits;
+    let contents = render_config(&commits, args.days);
+
+    fs::write(&path, &contents).with_context(|| format!("无法写入 {}", path.display()))?;
+    println!("已写入 {}：\n", path.display());
+    print!("{contents}");
+    Ok(())
+}
+
+/// The offset most commits were made in; ties go to the smaller offset.
+fn most_common_offset(commits: &[Commit]) -> Option<FixedOffset> {
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for commit in commits {
+        *counts.entry(commit.timestamp.offset().local_minus_utc()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(offset, count)| (*count, -offset))
+        .and_then(|(offset, _)| FixedOffset::east_opt(offset))
+}
+
+/// An IANA name for a whole-hour offset. `Etc/GMT` names have the sign
+/// flipped: UTC+8 is `Etc/GMT-8`.
+fn zone_for_offset(offset: FixedOffset) -> Option<String> {
+    let seconds = offset.local_minus_utc();
+    match (seconds % 3600, seconds / 3600) {
+        (0, 0) => Some("UTC".to_string()),
+        (0, hours) => Some(format!("Etc/GMT{:+}", -hours)),
+        _ => None,
+    }
+}
+
+/// `old name -> current name` for emails committed under several names; the
+/// most recently used name is kept, as the analysis does.
+fn suggested_aliases(commits: &[Commit]) -> BTreeMap<String, String> {
+    let mut by_email: HashMap<&str, Vec<(DateTime<FixedOffset>, &str)>> = HashMap::new();
+    for commit in commits.iter().filter(|commit| !commit.email.is_empty()) {
+        by_email
+            .entry(commit.email.as_str())
+            .or_default()
+            .push((commit.timestamp, commit.author.as_str()));
+    }
+
+    let mut aliases = BTreeMap::new();
+    for uses in by_email.into_values() {
+        let Some(&(_, latest)) = uses.iter().max() else {
+            continue;
+        };
+        for (_, name) in uses {
+            if name != latest {
+                aliases.insert(name.to_string(), latest.to_string());
+            }
+        }
+    }
+    aliases
+}
+
+fn render_config(commits: &[Commit], days: u32) -> String {
+    let quote = |raw: &str| toml::Value::String(raw.to_string()).to_string();
+    let mut toml = String::new();
+    toml.push_str("# cow-horse 仓库配置，由 `init` 根据");
+    toml.push_str(&format!("最近 {days} 天的 {} 次提交生成。\n", commits.len()));
+    toml.push_str("# 命令行参数优先于本文件，本文件优先于用户级配置。\n\n");
+
+    toml.push_str("# 工作时间，之外的提交算作下班后（默认值）\n");
+    toml.push_str(&format!(
+        "work_hours = {}\n\n",
+        quote(&WorkSchedule::default().describe())
+    ));
+
+    toml.push_str("# 周末，可改为如 [\"fri\", \"sat\"]\n");
+    toml.push_str("weekend_days = [\"sat\", \"sun\"]\n\n");
+
+    match most_common_offset(commits) {
+        Some(offset) => {
+            toml.push_str(&format!("# 最常见的提交时区偏移：{offset}。\n"));
+            toml.push_str("# 设置后所有提交都按该时区判定；删掉这一行则按每次提交自己的时区。\n");
+            match zone_for_offset(offset) {
+                Some(zone) => toml.push_str(&format!("timezone = {}\n\n", quote(&zone))),
+                None => toml.push_str("# timezone = \"Asia/Kolkata\"\n\n"),
+            }
+        }
+        None => toml.push_str("# timezone = \"Asia/Shanghai\"\n\n"),
+    }
+
+    toml.push_str("# 不计入统计的作者（机器人、共享账号等）\n");
+    toml.push_str("ignore_authors = []\n\n");
+
+    toml.push_str("# 别名：旧名 = 统一名\n");
+    toml.push_str("[aliases]\n");
+    let aliases = suggested_aliases(commits);
+    if aliases.is_empty() {
+        toml.push_str("# \"zhangsan\" = \"张三\"\n");
+    } else {
+        toml.push_str("# 以下作者用同一个邮箱提交过不同的名字，统一为最近使用的名字：\n");
+        for (from, to) in aliases {
+            toml.push_str(&format!("{} = {}\n", quote(&from), quote(&to)));
+        }
+    }
+    toml
+}
@@ -0,0 +1,40 @@
+//! Library side of `zzh`: reading git history, computing the overtime
+//! metrics and rendering reports. The binary is a thin command-line layer
+//! over these modules.
+//!
+//! Embedders usually plug in a [`Classifier`] for their own after-hours
+//! rules and a [`ProgressSink`] for progress reporting.
+
+pub mod alias;
+pub mod alias_suggest;
+pub mod analysis;
+pub mod attendance;
+pub mod cache;
+pub mod cli;
+pub mod compare;
+pub mod config;
+pub mod defaults;
+pub mod diff;
+pub mod digest;
+pub mod error;
+pub mod examples;
+pub mod exclude;
+pub mod forecast;
+pub mod gitlog;
+pub mod holidays;
+pub mod i18n;
+pub mod init;
+pub mod metrics;
+pub mod output;
+pub mod policy;
+pub mod progress;
+pub mod push_check;
+pub mod report;
+pub mod submodule;
+#[cfg(test)]
+mod testutil;
+pub mod time_filter;
+pub mod timestamp;
+
+pub use metrics::{compute_metrics, Classification, Classifier, DefaultClassifier, MetricsOptions};
+pub use progress::{ProgressEvent, ProgressSink};
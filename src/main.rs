@@ -1,162 +1,730 @@
-mod alias;
-mod cli;
-mod gitlog;
-mod metrics;
-mod push_check;
-mod report;
-mod time_filter;
-mod timestamp;
-
-use std::collections::HashSet;
 use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Instant;
 
 use anyhow::{bail, Result};
-use chrono::{Duration, Utc};
-use clap::Parser;
+use chrono::{DateTime, Local, Utc};
+use clap::{CommandFactory, Parser};
 
-use crate::alias::parse_aliases;
-use crate::cli::Cli;
-use crate::gitlog::fetch_commits;
-use crate::metrics::{compute_metrics, AliasRule};
-use crate::push_check::{run_push_check, PushCheckCli};
-use crate::report::print_human_report;
-use crate::time_filter::parse_time_filter;
-use crate::timestamp::convert_unix_timestamp;
+use cow_horse::alias_suggest::{run_alias_suggest, AliasSuggestCli};
+use cow_horse::analysis::{resolve_window, Analysis, Analyzer};
+use cow_horse::attendance::{
+    build_attendance, write_attendance_csv, write_authors_csv, write_days_csv,
+};
+use cow_horse::cache::{run_cache, CacheCli};
+use cow_horse::cli::Cli;
+use cow_horse::compare::{baseline_window, compare, write_comparison};
+use cow_horse::defaults::print_default_ignores;
+use cow_horse::diff::{run_diff, DiffCli};
+use cow_horse::digest::{digest_windows, write_digest, DigestPeriod};
+use cow_horse::error::{classify, error_json, CodedError, ErrorCode};
+use cow_horse::examples::{print_examples, selected_example, ExamplesCli};
+use cow_horse::init::{run_init, InitCli};
+use cow_horse::metrics::{percentage, AggregateMetrics, RepoFailure, RepoMetrics};
+use cow_horse::output::{emit_aggregate, emit_all, outputs_for, write_to_file, Format};
+use cow_horse::progress;
+use cow_horse::push_check::{run_push_check, PushCheckCli};
+use cow_horse::report::{write_author_report, write_json};
+use cow_horse::time_filter::{parse_duration, parse_time_filter};
+use cow_horse::timestamp::{convert_unix_timestamp, UnixCli};
 
-const DEFAULT_IGNORED_AUTHORS: &[&str] = &["BitsAdmin"];
+const EXIT_GATE_FAILED: i32 = 2;
+/// Some repositories of a multi-repo run failed, the rest were reported.
+const EXIT_SOME_REPOS_FAILED: i32 = 6;
+const MAX_ATTENDANCE_DAYS: usize = 366;
 
 fn main() {
-    if let Err(err) = run() {
-        eprintln!("Error: {err:?}");
-        std::process::exit(1);
+    let command = parse_command();
+    let json_errors = matches!(&command, AppCommand::CowHorse(cli) if cli.json_errors);
+    let status_line = matches!(&command, AppCommand::CowHorse(cli) if !cli.quiet);
+    match run(command) {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            if json_errors {
+                println!("{}", error_json(&err));
+            } else {
+                eprintln!("Error: {err:?}");
+            }
+            if status_line {
+                eprintln!("cow-horse: failed ({})", classify(&err).0);
+            }
+            std::process::exit(1);
+        }
     }
 }
 
-fn run() -> Result<()> {
-    match parse_command()? {
+/// Returns the process exit code for runs that completed normally.
+fn run(command: AppCommand) -> Result<i32> {
+    match command {
         AppCommand::Unix(ts) => {
             let conversion = convert_unix_timestamp(ts)?;
             let fmt = "%Y-%m-%d %H:%M:%S %:z";
             println!("Unix 时间戳：{}", conversion.timestamp);
             println!("UTC  时间：{}", conversion.utc.format(fmt));
             println!("本地时间：{}", conversion.local.format(fmt));
-            Ok(())
+            Ok(0)
+        }
+        AppCommand::PrintDefaultIgnores => {
+            print_default_ignores();
+            Ok(0)
+        }
+        AppCommand::PushCheck(cfg) => run_push_check(&cfg).map(|_| 0),
+        AppCommand::Init(cfg) => run_init(&cfg).map(|_| 0),
+        AppCommand::AliasSuggest(cfg) => run_alias_suggest(&cfg).map(|_| 0),
+        AppCommand::Cache(cfg) => run_cache(&cfg).map(|_| 0),
+        AppCommand::Diff(cfg) => {
+            run_diff(&cfg).map(|worse| if worse { EXIT_GATE_FAILED } else { 0 })
         }
-        AppCommand::PushCheck(cfg) => run_push_check(&cfg),
-        AppCommand::CowHorse(cli) => run_cow_horse(cli),
+        AppCommand::Examples(cfg) => match cfg.run {
+            Some(n) => run_cow_horse(selected_example(n, &cfg.path.to_string_lossy())?),
+            None => {
+                print_examples();
+                Ok(0)
+            }
+        },
+        AppCommand::CowHorse(cli) => run_cow_horse(*cli),
     }
 }
 
-fn run_cow_horse(cli: Cli) -> Result<()> {
-    let repo_path = cli
-        .path
-        .canonicalize()
-        .unwrap_or_else(|_| cli.path.clone());
-    let now = Utc::now();
+/// What the stderr status line reports about a finished run.
+struct RunSummary {
+    commits: usize,
+    repo: PathBuf,
+    severity: f64,
+}
 
-    let since = if let Some(ref raw) = cli.since {
-        Some(parse_time_filter(raw, now)?)
-    } else if cli.window_days > 0 {
-        Some(now - Duration::days(cli.window_days as i64))
-    } else {
-        None
+impl RunSummary {
+    fn new(analyzer: &Analyzer, metrics: Option<&RepoMetrics>) -> Self {
+        RunSummary {
+            commits: metrics.map_or(0, |m| m.total_commits),
+            repo: analyzer.repo_path.clone(),
+            severity: metrics.map_or(0.0, |m| m.severity_score),
+        }
+    }
+}
+
+/// Frozen format, parsed by wrapper scripts: one line on stderr per run,
+/// `cow-horse: analyzed <N> commits in <repo> (severity <S>) in <T>ms` on
+/// success and `cow-horse: failed (<ERROR_CODE>)` on failure.
+fn run_cow_horse(cli: Cli) -> Result<i32> {
+    let started = Instant::now();
+    let (code, summary) = match cli.digest {
+        Some(period) => run_digest(&cli, period)?,
+        None if cli.compare.is_some() || cli.compare_since.is_some() => run_compare(&cli)?,
+        None if let Some(name) = &cli.author_report => run_author_report(&cli, name)?,
+        None => run_report(&cli)?,
     };
+    if !cli.quiet {
+        eprintln!(
+            "cow-horse: analyzed {} commits in {} (severity {:.1}) in {}ms",
+            summary.commits,
+            summary.repo.display(),
+            summary.severity,
+            started.elapsed().as_millis()
+        );
+    }
+    Ok(code)
+}
 
-    let until = if let Some(ref raw) = cli.until {
-        Some(parse_time_filter(raw, now)?)
-    } else {
-        None
+fn reference_now(cli: &Cli) -> Result<DateTime<Utc>> {
+    match &cli.now {
+        Some(raw) => parse_time_filter(raw, Utc::now()),
+        None => Ok(Utc::now()),
+    }
+}
+
+fn run_report(cli: &Cli) -> Result<(i32, RunSummary)> {
+    let now = reference_now(cli)?;
+
+    let window = resolve_window(cli, now)?;
+    let expect_fresh = match &cli.expect_fresh {
+        Some(raw) => Some(parse_duration(raw).ok_or_else(|| {
+            CodedError::new(
+                ErrorCode::BadTimeFilter,
+                format!("--expect-fresh 需要形如 7d、2w、12h 的时长，当前为：{raw}"),
+            )
+        })?),
+        None => None,
+    };
+    let paths = cli.repo_paths()?;
+    let multi_repo = paths.len() > 1;
+    // With several repositories one broken checkout should not sink the
+    // rest, unless --strict asks for that.
+    let soft_fail = multi_repo && !cli.strict;
+    let mut analyzers = Vec::new();
+    let mut repos = Vec::new();
+    let mut commits = Vec::new();
+    let mut failures = Vec::new();
+    for path in &paths {
+        let analysis = Analyzer::new(cli, path, now, progress::cli_sink(cli))
+            .and_then(|analyzer| Ok((analyzer.analyze(&window)?, analyzer)));
+        let (analysis, analyzer) = match analysis {
+            Ok(done) => done,
+            Err(err) if soft_fail => {
+                eprintln!("警告：无法分析 {}：{err:#}", path.display());
+                failures.push(RepoFailure::new(path, err));
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        match analysis {
+            Analysis::Empty(message) if multi_repo => eprintln!("跳过：{message}"),
+            Analysis::Empty(message) => {
+                println!("{message}");
+                return Ok((0, RunSummary::new(&analyzer, None)));
+            }
+            Analysis::Done {
+                metrics,
+                commits: mut repo_commits,
+            } => {
+                repos.push(metrics);
+                commits.append(&mut repo_commits);
+            }
+        }
+        analyzers.push(analyzer);
+    }
+    if analyzers.is_empty() {
+        let mut failures = failures.into_iter();
+        let first = failures.next().map(|failure| failure.error);
+        return Err(first
+            .unwrap_or_else(|| anyhow::anyhow!("没有可分析的仓库"))
+            .context(format!("所有 {} 个仓库都分析失败", paths.len())));
+    }
+    let analyzer = &analyzers[0];
+    let (metrics, commits) = match repos.len() {
+        0 => {
+            println!("所有仓库中都没有找到符合过滤条件的提交。");
+            return Ok((partial_failure_code(&failures), RunSummary::new(analyzer, None)));
+        }
+        1 if !multi_repo => (repos.remove(0), commits),
+        _ => analyzer.aggregate(&repos, commits, &window),
     };
 
-    if let (Some(s), Some(u)) = (since, until) {
-        if s >= u {
-            bail!("`since` must be earlier than `until`");
+    let attendance = match &cli.attendance_csv {
+        Some(path) => {
+            let start = window.since.unwrap_or(now).date_naive();
+            let end = window.until.unwrap_or(now).date_naive();
+            let attendance = build_attendance(&commits, start, end);
+            if attendance.dates.len() > MAX_ATTENDANCE_DAYS && !cli.allow_wide_attendance {
+                bail!(
+                    "出勤矩阵将包含 {} 列日期（超过一年），如确需导出请加上 --allow-wide-attendance",
+                    attendance.dates.len()
+                );
+            }
+            Some((path, attendance))
         }
+        None => None,
+    };
+
+    if multi_repo {
+        emit_aggregate(
+            cli,
+            &AggregateMetrics {
+                repos: &repos,
+                aggregate: &metrics,
+                failures: &failures,
+            },
+        )?;
+    } else {
+        emit_all(&outputs_for(cli), &metrics)?;
     }
 
-    let alias_map = parse_aliases(&cli.alias)?;
+    if let Some((path, attendance)) = attendance {
+        write_to_file(path, |writer| Ok(write_attendance_csv(&attendance, writer)?))?;
+    }
+    if let Some(path) = &cli.csv_authors {
+        write_to_file(path, |writer| Ok(write_authors_csv(&metrics, writer)?))?;
+    }
+    if let Some(path) = &cli.csv_days {
+        write_to_file(path, |writer| Ok(write_days_csv(&metrics, writer)?))?;
+    }
 
-    let mut commits = fetch_commits(
-        &repo_path,
-        since,
-        until,
-        cli.author.as_deref(),
-        cli.limit,
-    )?;
+    let mut stale = false;
+    if let (Some(max_age), Some(newest)) = (expect_fresh, metrics.analysis_end)
+        && now - newest.with_timezone(&Utc) > max_age
+    {
+        stale = true;
+        eprintln!(
+            "警告：最新一次提交是 {}（{:.1} 天前），早于 --expect-fresh {} 的要求。这可能是过期的镜像，请先 git fetch 再分析。",
+            newest.format("%Y-%m-%d %H:%M %:z"),
+            metrics.newest_commit_age_days.unwrap_or_default(),
+            cli.expect_fresh.as_deref().unwrap_or_default()
+        );
+    }
 
-    let mut ignored: HashSet<String> = DEFAULT_IGNORED_AUTHORS
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-    ignored.extend(cli.ignore_author.iter().cloned());
+    let mut summary = RunSummary::new(analyzer, Some(&metrics));
+    if multi_repo {
+        summary.repo = metrics.repo_path.clone();
+    }
+    let policy_failed = metrics.policy_results.iter().any(|result| !result.passed);
+    let threshold_failed = exceeded_thresholds(cli, &metrics);
+    if (cli.fail_on_policy && policy_failed) || (cli.strict && stale) || threshold_failed {
+        return Ok((EXIT_GATE_FAILED, summary));
+    }
+    Ok((partial_failure_code(&failures), summary))
+}
+
+/// [`EXIT_SOME_REPOS_FAILED`] when a multi-repo run skipped repositories
+/// that failed; gate failures take precedence.
+fn partial_failure_code(failures: &[RepoFailure]) -> i32 {
+    if failures.is_empty() { 0 } else { EXIT_SOME_REPOS_FAILED }
+}
 
-    if !ignored.is_empty() {
-        commits.retain(|commit| !ignored.contains(&commit.author));
+/// Checks `--fail-above` and `--fail-after-hours-ratio`, explaining each
+/// exceeded threshold on stderr.
+fn exceeded_thresholds(cli: &Cli, metrics: &RepoMetrics) -> bool {
+    let mut exceeded = false;
+    if let Some(limit) = cli.fail_above
+        && metrics.severity_score > limit
+    {
+        eprintln!(
+            "门禁未通过：牛马指数 {:.1} 高于 --fail-above {limit}",
+            metrics.severity_score
+        );
+        exceeded = true;
     }
+    if let Some(limit) = cli.fail_after_hours_ratio {
+        let ratio = percentage(metrics.after_hours_commits, metrics.total_commits) * 100.0;
+        if ratio > limit {
+            eprintln!("门禁未通过：下班后提交占比 {ratio:.1}% 高于 --fail-after-hours-ratio {limit}%");
+            exceeded = true;
+        }
+    }
+    exceeded
+}
+
+fn run_digest(cli: &Cli, period: DigestPeriod) -> Result<(i32, RunSummary)> {
+    let now = reference_now(cli)?;
+    let windows = digest_windows(period, now.with_timezone(&Local));
+    let analyzer = Analyzer::new(cli, &cli.single_repo("--digest")?, now, progress::cli_sink(cli))?;
+
+    let current = match analyzer.analyze(&windows.current)? {
+        Analysis::Done { metrics, .. } => metrics,
+        Analysis::Empty(_) => {
+            println!("{} 没有任何提交，本期无需汇报。", windows.label);
+            return Ok((0, RunSummary::new(&analyzer, None)));
+        }
+    };
+    let previous = match analyzer.analyze(&windows.previous)? {
+        Analysis::Done { metrics, .. } => Some(metrics),
+        Analysis::Empty(_) => None,
+    };
 
-    let mut alias_rules: Vec<AliasRule> = alias_map
-        .iter()
-        .map(|(from, to)| AliasRule {
-            from: from.clone(),
-            to: to.clone(),
-        })
-        .collect();
-    alias_rules.sort_by(|a, b| a.from.cmp(&b.from));
-    if !alias_map.is_empty() {
-        for commit in &mut commits {
-            if let Some(mapped) = alias_map.get(commit.author.as_str()) {
-                commit.author = mapped.clone();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    write_digest(&mut out, period, &windows.label, &current, previous.as_deref())?;
+    Ok((0, RunSummary::new(&analyzer, Some(&current))))
+}
+
+fn run_author_report(cli: &Cli, name: &str) -> Result<(i32, RunSummary)> {
+    let format = cli.report_format();
+    if !matches!(format, Format::Human | Format::Json) {
+        bail!("--author-report 只支持 --format human 或 json");
+    }
+    let now = reference_now(cli)?;
+    let window = resolve_window(cli, now)?;
+    let paths = cli.repo_paths()?;
+    let multi_repo = paths.len() > 1;
+
+    // Each repository applies its own aliases and email identities before
+    // the author's commits are merged.
+    let mut analyzers = Vec::new();
+    let mut repos = Vec::new();
+    let mut commits = Vec::new();
+    let mut per_repo = Vec::new();
+    for path in &paths {
+        let analyzer = Analyzer::new(cli, path, now, progress::cli_sink(cli))?;
+        match analyzer.analyze(&window)? {
+            Analysis::Empty(message) if multi_repo => eprintln!("跳过：{message}"),
+            Analysis::Empty(message) => {
+                println!("{message}");
+                return Ok((0, RunSummary::new(&analyzer, None)));
             }
+            Analysis::Done {
+                metrics,
+                commits: mut repo_commits,
+            } => {
+                if multi_repo {
+                    per_repo.extend(analyzer.find_author(name, &metrics, &repo_commits));
+                }
+                repos.push(metrics);
+                commits.append(&mut repo_commits);
+            }
+        }
+        analyzers.push(analyzer);
+    }
+    let analyzer = &analyzers[0];
+    let (metrics, commits) = match repos.len() {
+        0 => {
+            println!("所有仓库中都没有找到符合过滤条件的提交。");
+            return Ok((0, RunSummary::new(analyzer, None)));
+        }
+        1 if !multi_repo => (repos.remove(0), commits),
+        _ => analyzer.aggregate(&repos, commits, &window),
+    };
+    let mut report = analyzer.author_report(name, &metrics, &commits)?;
+    if multi_repo {
+        report.add_repo_breakdown(&per_repo);
+    }
+    let render = |out: &mut dyn Write| -> Result<()> {
+        match format {
+            Format::Json => write_json(&report, out),
+            _ => Ok(write_author_report(
+                &report,
+                cli.lang,
+                cli.color.enabled(cli.output.is_some()),
+                out,
+            )?),
+        }
+    };
+    match &cli.output {
+        Some(path) => write_to_file(path, render)?,
+        None => {
+            let stdout = io::stdout();
+            render(&mut stdout.lock())?;
         }
     }
+    Ok((0, RunSummary::new(analyzer, Some(&metrics))))
+}
 
-    if commits.is_empty() {
-        println!(
-            "在 {} 中没有找到符合过滤条件的提交。",
-            repo_path.display()
-        );
-        return Ok(());
+fn run_compare(cli: &Cli) -> Result<(i32, RunSummary)> {
+    let format = cli.report_format();
+    if !matches!(format, Format::Human | Format::Json) {
+        bail!("窗口对比只支持 --format human 或 json");
     }
+    let now = reference_now(cli)?;
+    let window = resolve_window(cli, now)?;
+    let Some(baseline_window) = baseline_window(cli, &window, now)? else {
+        bail!("缺少基线窗口：请指定 --compare previous 或 --compare-since");
+    };
+    let analyzer = Analyzer::new(cli, &cli.single_repo("窗口对比")?, now, progress::cli_sink(cli))?;
 
-    let mut ignored_list: Vec<String> = ignored.into_iter().collect();
-    ignored_list.sort();
-    let metrics = compute_metrics(&repo_path, &commits, ignored_list, alias_rules);
+    let current = match analyzer.analyze(&window)? {
+        Analysis::Done { metrics, .. } => metrics,
+        Analysis::Empty(message) => {
+            println!("{message}");
+            return Ok((0, RunSummary::new(&analyzer, None)));
+        }
+    };
+    let baseline = match analyzer.analyze(&baseline_window)? {
+        Analysis::Done { metrics, .. } => metrics,
+        Analysis::Empty(_) => {
+            println!("基线窗口（{}）没有任何提交，无法对比。", baseline_window.description);
+            return Ok((0, RunSummary::new(&analyzer, Some(&current))));
+        }
+    };
 
-    if cli.json {
-        println!("{}", serde_json::to_string_pretty(&metrics)?);
-    } else {
-        print_human_report(&metrics, &cli);
+    let comparison = compare(&current, &baseline);
+    let render = |out: &mut dyn Write| -> Result<()> {
+        match format {
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut *out, &comparison)?;
+                writeln!(out)?;
+            }
+            _ => write_comparison(out, &comparison)?,
+        }
+        Ok(())
+    };
+    match &cli.output {
+        Some(path) => write_to_file(path, render)?,
+        None => {
+            let stdout = io::stdout();
+            render(&mut stdout.lock())?;
+        }
     }
-
-    Ok(())
+    Ok((0, RunSummary::new(&analyzer, Some(&current))))
 }
 
 enum AppCommand {
     Unix(i64),
+    PrintDefaultIgnores,
     PushCheck(PushCheckCli),
-    CowHorse(Cli),
+    Diff(DiffCli),
+    Init(InitCli),
+    AliasSuggest(AliasSuggestCli),
+    Cache(CacheCli),
+    Examples(ExamplesCli),
+    CowHorse(Box<Cli>),
 }
 
-fn parse_command() -> Result<AppCommand> {
-    let mut args: Vec<String> = env::args().collect();
-    if args.len() > 1 && args[1] == "push-check" {
+fn parse_command() -> AppCommand {
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 2 && args[1] == "aliases" && args[2] == "suggest" {
+        let mut sub_args = vec![args[0].clone()];
+        sub_args.extend_from_slice(&args[3..]);
+        return AppCommand::AliasSuggest(AliasSuggestCli::parse_from(sub_args));
+    }
+    if args.len() > 1
+        && matches!(args[1].as_str(), "push-check" | "examples" | "diff" | "init" | "unix" | "cache")
+    {
         let mut sub_args = Vec::with_capacity(args.len() - 1);
         sub_args.push(args[0].clone());
         sub_args.extend_from_slice(&args[2..]);
-        let cfg = PushCheckCli::parse_from(sub_args);
-        return Ok(AppCommand::PushCheck(cfg));
+        return match args[1].as_str() {
+            "examples" => AppCommand::Examples(ExamplesCli::parse_from(sub_args)),
+            "diff" => AppCommand::Diff(DiffCli::parse_from(sub_args)),
+            "init" => AppCommand::Init(InitCli::parse_from(sub_args)),
+            "unix" => AppCommand::Unix(UnixCli::parse_from(sub_args).timestamp),
+            "cache" => AppCommand::Cache(CacheCli::parse_from(sub_args)),
+            _ => AppCommand::PushCheck(PushCheckCli::parse_from(sub_args)),
+        };
     }
 
-    for arg in args.iter_mut().skip(1) {
-        if arg == "-unix" {
-            *arg = "--unix".to_string();
-        }
+    let cli = Cli::parse_from(rewrite_legacy_unix_flag(args));
+    if cli.json {
+        eprintln!("警告：--json 已废弃，请改用 --format json");
     }
-
-    let cli = Cli::parse_from(args);
     if let Some(ts) = cli.unix {
-        Ok(AppCommand::Unix(ts))
+        AppCommand::Unix(ts)
+    } else if cli.print_default_ignores {
+        AppCommand::PrintDefaultIgnores
     } else {
-        Ok(AppCommand::CowHorse(cli))
+        AppCommand::CowHorse(Box::new(cli))
+    }
+}
+
+/// Accepts the old single-dash `-unix` spelling by rewriting it to `--unix`,
+/// but only in flag position: as the value of a preceding option (e.g.
+/// `--ignore-author -unix`) or after `--` it is left alone.
+fn rewrite_legacy_unix_flag(mut args: Vec<String>) -> Vec<String> {
+    let command = Cli::command();
+    let takes_value = |flag: &str| {
+        let Some(long) = flag.strip_prefix("--") else {
+            return flag.len() == 2
+                && command.get_arguments().any(|arg| {
+                    arg.get_short().is_some_and(|s| flag.ends_with(s))
+                        && arg.get_action().takes_values()
+                });
+        };
+        !long.contains('=')
+            && command.get_arguments().any(|arg| {
+                arg.get_long() == Some(long) && arg.get_action().takes_values()
+            })
+    };
+
+    for idx in 1..args.len() {
+        if args[idx] == "--" {
+            break;
+        }
+        if args[idx] == "-unix" && !takes_value(&args[idx - 1]) {
+            eprintln!("警告：-unix 写法已废弃，请改用 unix 子命令：zzh unix <TIMESTAMP>");
+            args[idx] = "--unix".to_string();
+        } else if let Some(value) = args[idx].strip_prefix("-unix=")
+            && !takes_value(&args[idx - 1])
+        {
+            eprintln!("警告：-unix 写法已废弃，请改用 unix 子命令：zzh unix <TIMESTAMP>");
+            args[idx] = format!("--unix={value}");
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn rewrite(args: &[&str]) -> Vec<String> {
+        let args = std::iter::once("zzh")
+            .chain(args.iter().copied())
+            .map(String::from)
+            .collect();
+        rewrite_legacy_unix_flag(args)
+    }
+
+    #[test]
+    fn legacy_unix_flag_is_rewritten_in_flag_position() {
+        assert_eq!(rewrite(&["-unix", "1700000000"]), ["zzh", "--unix", "1700000000"]);
+        assert_eq!(rewrite(&["-unix=1700000000"]), ["zzh", "--unix=1700000000"]);
+        assert_eq!(
+            rewrite(&["--quiet", "-unix", "1700000000"]),
+            ["zzh", "--quiet", "--unix", "1700000000"]
+        );
+        let cli = Cli::parse_from(rewrite(&["-unix", "1700000000"]));
+        assert_eq!(cli.unix, Some(1700000000));
+    }
+
+    #[test]
+    fn legacy_unix_flag_is_left_alone_as_a_value_or_after_double_dash() {
+        assert_eq!(
+            rewrite(&["--ignore-author", "-unix"]),
+            ["zzh", "--ignore-author", "-unix"]
+        );
+        assert_eq!(
+            rewrite(&["--ignore-author", "-unix=1"]),
+            ["zzh", "--ignore-author", "-unix=1"]
+        );
+        assert_eq!(rewrite(&["--", "-unix"]), ["zzh", "--", "-unix"]);
+        let cli = Cli::parse_from(rewrite(&["--ignore-author", "-unix"]));
+        assert_eq!(cli.unix, None);
+        assert!(cli.ignore_author.iter().any(|name| name == "-unix"));
+    }
+
+    #[test]
+    fn unix_subcommand_accepts_negative_timestamps() {
+        assert_eq!(UnixCli::parse_from(["unix", "-5"]).timestamp, -5);
+    }
+
+    /// A scratch directory holding a repository with one commit and a plain
+    /// directory that git refuses.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let root = env::temp_dir().join(format!(
+                "cowhorse-main-test-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(root.join("broken")).unwrap();
+            let fixture = Fixture(root);
+            fixture.commit("ok", "A", "2026-09-01T21:00:00+08:00");
+            fixture
+        }
+
+        /// Commits as `author` in the repository `repo`, creating it first.
+        fn commit(&self, repo: &str, author: &str, date: &str) {
+            let path = self.0.join(repo);
+            let email = format!("{}@example.com", author.to_lowercase());
+            let git = |args: &[&str]| {
+                let status = std::process::Command::new("git")
+                    .arg("-C")
+                    .arg(&path)
+                    .args(args)
+                    .env("GIT_AUTHOR_NAME", author)
+                    .env("GIT_AUTHOR_EMAIL", &email)
+                    .env("GIT_COMMITTER_NAME", author)
+                    .env("GIT_COMMITTER_EMAIL", &email)
+                    .env("GIT_AUTHOR_DATE", date)
+                    .env("GIT_COMMITTER_DATE", date)
+                    .status()
+                    .unwrap();
+                assert!(status.success());
+            };
+            if !path.exists() {
+                std::fs::create_dir_all(&path).unwrap();
+                git(&["init", "-q"]);
+            }
+            git(&["commit", "-q", "--allow-empty", "-m", "work"]);
+        }
+
+        fn cli(&self, paths: &[&str], extra: &[&str]) -> Cli {
+            let mut args = vec!["zzh".to_string()];
+            for path in paths {
+                args.push("--path".to_string());
+                args.push(self.0.join(path).display().to_string());
+            }
+            let output = self.0.join("report.json").display().to_string();
+            for arg in ["--all-history", "-q", "--format", "json", "--output", &output] {
+                args.push(arg.to_string());
+            }
+            args.extend(extra.iter().map(|arg| arg.to_string()));
+            Cli::parse_from(args)
+        }
+
+        fn report(&self) -> serde_json::Value {
+            serde_json::from_str(&std::fs::read_to_string(self.0.join("report.json")).unwrap()).unwrap()
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn failing_repo_is_listed_and_the_rest_still_reported() {
+        let fixture = Fixture::new();
+
+        let cli = |paths: &[&str], extra: &[&str]| {
+            fixture.cli(paths, &[extra, &["--no-config"]].concat())
+        };
+        let (code, _) = run_report(&cli(&["ok", "broken"], &[])).unwrap();
+        assert_eq!(code, EXIT_SOME_REPOS_FAILED);
+        let report = fixture.report();
+        assert_eq!(report["aggregate"]["total_commits"], 1);
+        let failures = report["failures"].as_array().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0]["code"], "NOT_A_REPO");
+        assert!(failures[0]["path"].as_str().unwrap().ends_with("broken"));
+
+        let (code, _) = run_report(&cli(&["ok", "ok/."], &[])).unwrap();
+        assert_eq!(code, 0);
+        assert!(run_report(&cli(&["ok", "broken"], &["--strict"])).is_err());
+        assert!(run_report(&cli(&["broken", "missing"], &[])).is_err());
+    }
+
+    #[test]
+    fn failed_policy_rules_set_the_exit_code_only_with_fail_on_policy() {
+        let fixture = Fixture::new();
+        let policy = ["--no-config", "--policy", "after_hours<=0.10,weekend<=0.05"];
+
+        let (code, _) = run_report(&fixture.cli(&["ok"], &policy)).unwrap();
+        assert_eq!(code, 0);
+        let results = fixture.report()["policy_results"].clone();
+        assert_eq!(results[0]["passed"], false);
+        assert_eq!(results[0]["actual"], 1.0);
+        assert_eq!(results[1]["passed"], true);
+
+        let gated = fixture.cli(&["ok"], &[&policy[..], &["--fail-on-policy"]].concat());
+        assert_eq!(run_report(&gated).unwrap().0, EXIT_GATE_FAILED);
+        let passing = fixture.cli(&["ok"], &["--no-config", "--policy", "weekend<=0.05", "--fail-on-policy"]);
+        assert_eq!(run_report(&passing).unwrap().0, 0);
+    }
+
+    #[test]
+    fn thresholds_exit_with_the_gate_code_after_writing_the_report() {
+        // The only commit in "ok" is after hours: a 100% ratio.
+        let fixture = Fixture::new();
+        for (flags, expected) in [
+            (["--fail-above", "0"], EXIT_GATE_FAILED),
+            (["--fail-above", "100"], 0),
+            (["--fail-after-hours-ratio", "50"], EXIT_GATE_FAILED),
+            (["--fail-after-hours-ratio", "100"], 0),
+        ] {
+            let _ = std::fs::remove_file(fixture.0.join("report.json"));
+            let cli = fixture.cli(&["ok"], &[&["--no-config"][..], &flags].concat());
+            assert_eq!(run_report(&cli).unwrap().0, expected, "{flags:?}");
+            assert_eq!(fixture.report()["total_commits"], 1, "{flags:?}");
+        }
+    }
+
+    #[test]
+    fn author_report_merges_one_person_across_repos() {
+        let fixture = Fixture::new();
+        fixture.commit("one", "Alice", "2026-09-01T10:00:00+08:00");
+        fixture.commit("one", "Alice", "2026-09-02T10:00:00+08:00");
+        fixture.commit("one", "Bob", "2026-09-02T11:00:00+08:00");
+        // Known as alice-w in the second repository only.
+        fixture.commit("two", "alice-w", "2026-09-03T22:00:00+08:00");
+        fixture.commit("two", "alice-w", "2026-09-03T23:00:00+08:00");
+        std::fs::write(
+            fixture.0.join("two/.cowhorse.toml"),
+            "[aliases]\n\"alice-w\" = \"Alice\"\n",
+        )
+        .unwrap();
+
+        let cli = fixture.cli(&["one", "two"], &["--author-report", "Alice"]);
+        run_author_report(&cli, "Alice").unwrap();
+        let report = fixture.report();
+        assert_eq!(report["total_commits"], 4);
+        assert_eq!(report["longest_streak_days"], 3, "a day counts if any repo has a commit");
+        assert_eq!(report["after_hours"]["commits"], 2);
+        let repos = report["repos"].as_array().unwrap();
+        assert_eq!(repos.len(), 2);
+        assert_eq!(repos[0]["total_commits"], 2);
+        assert_eq!(repos[1]["after_hours_commits"], 2);
+        assert!(report["most_after_hours_repo"].as_str().unwrap().ends_with("two"));
+        assert_eq!(report["punch_card"][3][22], 1);
+
+        let single = fixture.cli(&["one"], &["--author-report", "Alice"]);
+        run_author_report(&single, "Alice").unwrap();
+        assert!(fixture.report().get("repos").is_none());
     }
 }
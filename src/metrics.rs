@@ -1,47 +1,447 @@
-use std::collections::{BTreeMap, HashMap};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::f64::consts::TAU;
 use std::path::{Path, PathBuf};
 
-use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Timelike, Weekday};
-use serde::Serialize;
+use anyhow::{anyhow, bail, Result};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, IsoWeek, Local, NaiveDate, NaiveTime, Timelike, Utc, Weekday,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
-use crate::gitlog::Commit;
+use crate::error::{classify, ErrorCode};
+use crate::forecast::Forecast;
+use crate::gitlog::{Commit, DateSource, HeadRef};
+use crate::holidays::HolidayCalendar;
+use crate::i18n::{Lang, Msg};
+use crate::policy::PolicyResult;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RepoMetrics {
     pub repo_path: PathBuf,
+    /// What HEAD pointed at when the log was read; `None` if it could not be resolved.
+    #[serde(default)]
+    pub head: Option<HeadRef>,
+    /// Human-readable form of the requested window (rolling, explicit, or all history).
+    #[serde(default)]
+    pub window_description: String,
+    /// Requested (resolved) window bounds; `None` means unbounded on that side.
+    #[serde(default)]
+    pub window_start: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub window_end: Option<DateTime<Utc>>,
+    /// First and last observed commits inside the window.
     pub analysis_start: Option<DateTime<FixedOffset>>,
     pub analysis_end: Option<DateTime<FixedOffset>>,
+    /// `--min-coverage-days` goal and what the fetch achieved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageGoal>,
+    /// Age of the newest analyzed commit relative to the run's `now`.
+    #[serde(default)]
+    pub newest_commit_age_days: Option<f64>,
+    /// `--timezone` every commit was converted to before classification;
+    /// `None` means each commit was judged in its own author offset.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// With `--timezone`, the hour distribution in each commit's own offset
+    /// next to the one in that zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone_views: Option<TimezoneViews>,
+    /// Working hours used to judge after-hours commits.
+    #[serde(default)]
+    pub work_schedule: WorkSchedule,
+    #[serde(default)]
+    pub night_window: NightWindow,
+    #[serde(default)]
+    pub weekend_days: WeekendDays,
+    #[serde(default)]
+    pub severity_overlap: SeverityOverlap,
+    /// The bucket counts behind `severity_score`; equal to the raw
+    /// after-hours, weekend and night counts unless overlap is deduplicated.
+    #[serde(default)]
+    pub severity_buckets: SeverityBuckets,
+    /// Commits per tag returned by a custom [`Classifier`]; empty with the
+    /// default rules.
+    #[serde(default)]
+    pub custom_tag_counts: BTreeMap<String, usize>,
     pub total_commits: usize,
     pub unique_authors: usize,
     pub after_hours_commits: usize,
+    /// Includes holiday commits.
     pub weekend_commits: usize,
+    /// Commits on `--holidays` dates; also counted as weekend commits.
+    #[serde(default)]
+    pub holiday_commits: usize,
     pub night_commits: usize,
     pub commit_days: usize,
     pub overtime_days: usize,
+    /// Person-hours outside work hours estimated from commit sessions,
+    /// summed over authors; see [`session_overtime_minutes`].
+    #[serde(default)]
+    pub estimated_overtime_hours: f64,
+    /// `--session-gap` and `--session-padding` behind that estimate.
+    #[serde(default)]
+    pub session_gap_minutes: u32,
+    #[serde(default)]
+    pub session_padding_minutes: u32,
+    #[serde(default)]
+    pub workday_span: WorkdaySpan,
+    /// ISO weeks that look like a 996 schedule (see [`find_996_weeks`]),
+    /// out of the weeks with commits; the worst weeks and authors are listed.
+    #[serde(default)]
+    pub weeks_996: usize,
+    #[serde(default)]
+    pub weeks_with_commits: usize,
+    #[serde(default)]
+    pub worst_996_weeks: Vec<Week996>,
+    #[serde(default)]
+    pub authors_996: Vec<Author996>,
+    /// Stretches of heavy, mostly after-hours work; see [`crunch_periods`].
+    #[serde(default)]
+    pub crunch_periods: Vec<CrunchPeriod>,
+    /// `--crunch-threshold`.
+    #[serde(default)]
+    pub crunch_threshold: f64,
+    /// Per-author breaks of at least `vacation_days` days, fewest and
+    /// shortest first; see [`author_breaks`].
+    #[serde(default)]
+    pub breaks: Vec<AuthorBreaks>,
+    #[serde(default)]
+    pub vacation_days: u32,
+    /// Days from the end of the latest break by anyone to the last commit
+    /// day; `None` when nobody took one.
+    #[serde(default)]
+    pub days_since_last_break: Option<i64>,
     pub longest_streak_days: usize,
+    /// Longest run of consecutive days that each had an after-hours commit,
+    /// and its first and last day.
+    #[serde(default)]
+    pub longest_overtime_streak_days: usize,
+    #[serde(default)]
+    pub longest_overtime_streak_start: Option<NaiveDate>,
+    #[serde(default)]
+    pub longest_overtime_streak_end: Option<NaiveDate>,
+    /// Consecutive commit days ending at the last commit day; `active` when
+    /// that day is today or yesterday relative to the run's `now`.
+    #[serde(default)]
+    pub current_streak_days: usize,
+    #[serde(default)]
+    pub current_streak_active: bool,
+    #[serde(default)]
+    pub all_nighters: usize,
+    #[serde(default)]
+    pub worst_all_nighter: Option<AllNighterEvent>,
+    /// Every all-nighter event; only kept with `--detailed-authors`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub all_nighter_events: Vec<AllNighterEvent>,
     pub busiest_day: Option<BusiestDay>,
+    /// Per-day counts and estimated after-hours minutes, oldest first.
+    #[serde(default)]
+    pub daily: Vec<DailyStats>,
+    /// Per ISO week (`2024-W11`) and per month (`2024-03`), oldest first;
+    /// periods without commits are left out.
+    #[serde(default)]
+    pub weekly: Vec<PeriodStats>,
+    #[serde(default)]
+    pub monthly: Vec<PeriodStats>,
+    /// After-hours share of the later half of the window against the earlier.
+    #[serde(default)]
+    pub trend: Trend,
+    /// Commits by weekday (Monday first) and hour of day, in each commit's
+    /// local time.
+    #[serde(default)]
+    pub punch_card: [[usize; 24]; 7],
     pub severity_score: f64,
+    /// The terms adding up to `severity_score`.
+    #[serde(default)]
+    pub severity_breakdown: SeverityBreakdown,
+    /// `--weights`, normalized; reports with different weights are not
+    /// comparable.
+    #[serde(default)]
+    pub severity_weights: SeverityWeights,
     pub severity_label: String,
+    #[serde(default)]
+    pub severity_band: SeverityBand,
+    /// Days inside the analysis window that are not rest days: the
+    /// configured weekend and calendar holidays are left out, makeup
+    /// workdays counted.
+    #[serde(default)]
+    pub expected_working_days: usize,
+    /// Too few expected working days for the ratios to mean much; the
+    /// report marks the severity band accordingly.
+    #[serde(default)]
+    pub low_confidence: bool,
+    /// Why `low_confidence` is set. Stored in Chinese like
+    /// `severity_label`; `--lang` only changes what is printed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_confidence_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forecast: Option<Forecast>,
     pub top_after_hours_authors: Vec<AuthorSummary>,
     pub chill_authors: Vec<AuthorSummary>,
+    /// `--min-commits`, and how many authors it kept off the leaderboards.
+    #[serde(default)]
+    pub leaderboard_min_commits: usize,
+    #[serde(default)]
+    pub below_min_commits_authors: usize,
+    /// Every author, regardless of leaderboard size or `--min-commits`, in
+    /// `--sort-authors` order; feeds `--csv-authors`.
+    #[serde(default)]
+    pub authors: Vec<AuthorSummary>,
+    #[serde(default)]
+    pub attribution: Vec<AttributionEntry>,
+    /// Exact names only; kept for older consumers, prefer `ignored_author_stats`.
     pub ignored_authors: Vec<String>,
+    #[serde(default)]
+    pub ignored_author_stats: Vec<IgnoredAuthorStat>,
     pub alias_rules: Vec<AliasRule>,
+    /// Canonical identities whose commit pattern suggests aliases merged
+    /// several people.
+    #[serde(default)]
+    pub suspect_identities: Vec<SuspectIdentity>,
+    /// `--exclude` spans and how many commits each dropped.
+    #[serde(default)]
+    pub excluded_spans: Vec<ExcludedSpan>,
+    /// `--rev-range`/`--since-tag` range analyzed instead of a date window.
+    #[serde(default)]
+    pub rev_range: Option<String>,
+    /// Which commit timestamp was analyzed.
+    #[serde(default)]
+    pub date_source: DateSource,
+    /// With `--warn-rewritten`: commits whose author and committer dates
+    /// differ by more than an hour.
+    #[serde(default)]
+    pub rewritten_commits: Option<usize>,
+    /// `--pathspec` filters; only commits touching them were read.
+    #[serde(default)]
+    pub pathspecs: Vec<String>,
+    /// Refs read instead of HEAD (`--all` for every ref); empty means HEAD.
+    #[serde(default)]
+    pub branches: Vec<String>,
+    /// Merge commits dropped by `--no-merges`; `None` when merges were kept.
+    #[serde(default)]
+    pub skipped_merge_commits: Option<usize>,
+    /// `.cowhorse.toml` that contributed aliases/ignores, if any.
+    #[serde(default)]
+    pub repo_config_path: Option<PathBuf>,
+    /// User-level config file that was applied, if any.
+    #[serde(default)]
+    pub user_config_path: Option<PathBuf>,
+    #[serde(default)]
+    pub policy_results: Vec<PolicyResult>,
+    #[serde(default)]
+    pub drift_warnings: Vec<DriftWarning>,
+    #[serde(default)]
+    pub components: Vec<ComponentStats>,
+    #[serde(default)]
+    pub directories: Vec<DirectoryStats>,
+    #[serde(default)]
+    pub skipped_submodules: Vec<String>,
+    /// Work-end boundary sweep, filled under `--sensitivity`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitivity: Vec<SensitivityPoint>,
+    /// Commits listed by `--show-commits`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shown_commits: Option<CommitListing>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Several repositories analyzed in one run: each one's own metrics, plus
+/// one set computed from all of their commits together.
+#[derive(Debug, Serialize)]
+pub struct AggregateMetrics<'a> {
+    pub repos: &'a [Box<RepoMetrics>],
+    pub aggregate: &'a RepoMetrics,
+    /// Repositories left out because they could not be analyzed.
+    pub failures: &'a [RepoFailure],
+}
+
+/// A repository of a multi-repo run that failed; the others are still
+/// reported.
+#[derive(Debug, Serialize)]
+pub struct RepoFailure {
+    pub path: PathBuf,
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip)]
+    pub error: anyhow::Error,
+}
+
+impl RepoFailure {
+    pub fn new(path: &Path, error: anyhow::Error) -> Self {
+        RepoFailure {
+            path: path.to_path_buf(),
+            code: classify(&error).0,
+            message: format!("{error:#}"),
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AliasRule {
     pub from: String,
     pub to: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Where an ignore rule came from. A name listed by the user as well as in
+/// the defaults counts as a user rule.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IgnoreSource {
+    Default,
+    User,
+    /// An `--ignore-author-pattern` regex; the stat's `name` is the pattern.
+    Pattern,
+    /// Dropped by bot detection (see `--include-bots`).
+    Bot,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IgnoredAuthorStat {
+    pub name: String,
+    pub commits: usize,
+    pub source: IgnoreSource,
+}
+
+/// Why [`find_suspect_identities`] flagged an identity; the report words it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuspectReason {
+    /// `days` days with more than `limit` commits.
+    HeavyDays { days: usize, limit: usize },
+    /// `minutes` single minutes with `min_commits` or more commits from
+    /// different raw names.
+    SameMinuteBursts { minutes: usize, min_commits: usize },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SuspectIdentity {
+    pub name: String,
+    pub reasons: Vec<SuspectReason>,
+    /// Raw git names that alias rules folded into `name`.
+    pub merged_from: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CoverageGoal {
+    pub goal_days: usize,
+    /// Distinct commit dates read, before author filters.
+    pub covered_days: usize,
+    pub met: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExcludedSpan {
+    pub author: String,
+    pub range: String,
+    pub commits: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BusiestDay {
     pub date: NaiveDate,
     pub total_commits: usize,
     pub after_hours_commits: usize,
+    #[serde(default)]
+    pub after_hours_minutes: i64,
+}
+
+/// One author's figures next to the repository's, for `--author-report`.
+#[derive(Debug, Serialize, Clone)]
+pub struct AuthorMetrics {
+    /// Canonical name, after aliasing.
+    pub name: String,
+    pub repo_path: PathBuf,
+    pub window_description: String,
+    pub total_commits: usize,
+    /// Share of the repository's commits in the window.
+    pub commit_share: f64,
+    pub first_commit: Option<DateTime<FixedOffset>>,
+    pub last_commit: Option<DateTime<FixedOffset>>,
+    pub commit_days: usize,
+    pub longest_streak_days: usize,
+    pub busiest_day: Option<BusiestDay>,
+    /// Session estimate, as [`RepoMetrics::estimated_overtime_hours`].
+    pub estimated_overtime_hours: f64,
+    pub after_hours: RatioComparison,
+    pub weekend: RatioComparison,
+    pub night: RatioComparison,
+    /// Commits by weekday (Monday first) and hour of day.
+    pub punch_card: [[usize; 24]; 7],
+    pub work_schedule: WorkSchedule,
+    /// The author's days with commits, oldest first, for the calendar.
+    pub daily: Vec<DailyStats>,
+    /// First and last day the calendar covers: the window, in local dates,
+    /// widened to the author's commits if they fall outside it.
+    pub calendar_start: NaiveDate,
+    pub calendar_end: NaiveDate,
+    /// With several `--path`s, the author's figures in each repository that
+    /// has their commits, in argument order. A commit reachable from several
+    /// repositories counts in each row but once in the totals above.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub repos: Vec<AuthorRepoBreakdown>,
+    /// The repository holding most of the author's after-hours commits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub most_after_hours_repo: Option<PathBuf>,
+}
+
+/// One repository's row in a multi-repo `--author-report`.
+#[derive(Debug, Serialize, Clone)]
+pub struct AuthorRepoBreakdown {
+    pub repo_path: PathBuf,
+    pub total_commits: usize,
+    pub after_hours_commits: usize,
+    pub estimated_overtime_hours: f64,
+}
+
+impl AuthorMetrics {
+    /// Attaches the per-repository reports of the same author to this one,
+    /// computed from their commits in all of them.
+    pub fn add_repo_breakdown(&mut self, per_repo: &[AuthorMetrics]) {
+        self.repos = per_repo
+            .iter()
+            .map(|report| AuthorRepoBreakdown {
+                repo_path: report.repo_path.clone(),
+                total_commits: report.total_commits,
+                after_hours_commits: report.after_hours.commits,
+                estimated_overtime_hours: report.estimated_overtime_hours,
+            })
+            .collect();
+        // First in argument order on a tie.
+        self.most_after_hours_repo = self
+            .repos
+            .iter()
+            .filter(|repo| repo.after_hours_commits > 0)
+            .rev()
+            .max_by_key(|repo| repo.after_hours_commits)
+            .map(|repo| repo.repo_path.clone());
+    }
+}
+
+/// Commits by hour of day read two ways. Both after-hours shares use the
+/// work schedule alone, so they differ only by the clock they are read on.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TimezoneViews {
+    /// By the hour each commit's author saw, from the offset git recorded.
+    pub local_hours: [usize; 24],
+    /// By the hour in the `--timezone` zone.
+    pub reference_hours: [usize; 24],
+    pub local_after_hours_ratio: f64,
+    pub reference_after_hours_ratio: f64,
 }
 
+/// An author's count and ratio in one bucket, and the repository's ratio.
 #[derive(Debug, Serialize, Clone)]
+pub struct RatioComparison {
+    pub commits: usize,
+    pub ratio: f64,
+    pub repo_ratio: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AuthorSummary {
     pub name: String,
     pub total_commits: usize,
@@ -49,244 +449,2375 @@ pub struct AuthorSummary {
     pub weekend_commits: usize,
     pub night_commits: usize,
     pub after_hours_ratio: f64,
+    /// Fitted change of the typical commit time across the window; `None`
+    /// when the author has too little data for a trend.
+    #[serde(default)]
+    pub commit_time_drift_minutes: Option<f64>,
+    #[serde(default)]
+    pub all_nighters: usize,
+    /// See [`composite_score`]; always filled so `--rank-by composite`
+    /// orderings can be checked from JSON.
+    #[serde(default)]
+    pub composite_score: f64,
+    #[serde(default)]
+    pub current_streak_days: usize,
+    #[serde(default)]
+    pub current_streak_active: bool,
+    /// Estimated hours outside work hours; see [`session_overtime_minutes`].
+    #[serde(default)]
+    pub estimated_overtime_hours: f64,
+    /// Weeks of this author's commits that look like 996, see
+    /// [`find_996_weeks`].
+    #[serde(default)]
+    pub weeks_996: usize,
+    /// Other names this author's commits were recorded under, before email
+    /// unification and aliases; only filled with `--detailed-authors`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_names: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Default, Clone)]
-struct DayStats {
-    total_commits: usize,
-    after_hours_commits: usize,
+/// How the nightowl and chill leaderboards are ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RankBy {
+    /// Raw after-hours ratio.
+    #[default]
+    Ratio,
+    /// Sample-size aware score, see [`composite_score`].
+    Composite,
 }
 
-#[derive(Default)]
-struct AuthorAccumulator {
+/// Ordering of [`RepoMetrics::authors`]; counts and ratios go highest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortAuthors {
+    Commits,
+    AfterHours,
+    Ratio,
+    #[default]
+    Name,
+}
+
+/// How commits falling into several "badness" buckets feed the severity
+/// score.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeverityOverlap {
+    /// Every bucket a commit is in counts; a Saturday-night commit scores as
+    /// night, weekend and after hours at once.
+    #[default]
+    Independent,
+    /// Each commit counts once, in the first of night, weekend/holiday,
+    /// after hours.
+    Deduplicated,
+}
+
+/// After-hours, weekend and night counts as fed into [`severity_score`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SeverityBuckets {
+    pub after_hours: usize,
+    pub weekend: usize,
+    pub night: usize,
+}
+
+impl SeverityBuckets {
+    pub fn add(&mut self, overlap: SeverityOverlap, after_hours: bool, weekend: bool, night: bool) {
+        match overlap {
+            SeverityOverlap::Independent => {
+                self.after_hours += usize::from(after_hours);
+                self.weekend += usize::from(weekend);
+                self.night += usize::from(night);
+            }
+            SeverityOverlap::Deduplicated => {
+                if night {
+                    self.night += 1;
+                } else if weekend {
+                    self.weekend += 1;
+                } else if after_hours {
+                    self.after_hours += 1;
+                }
+            }
+        }
+    }
+}
+
+/// One author's share (0-1) of the repo-level after-hours, weekend and night
+/// counts. Small contributors are folded into a single `其他` entry.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttributionEntry {
+    pub author: String,
+    pub after_hours_share: f64,
+    pub weekend_share: f64,
+    pub night_share: f64,
+}
+
+/// A cluster of one author's night commits, each within 90 minutes of the
+/// previous one, that spans 3+ hours or contains 5+ commits.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AllNighterEvent {
+    pub author: String,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub commits: usize,
+    pub duration_minutes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComponentStats {
+    pub path: String,
+    pub total_commits: usize,
+    pub after_hours_commits: usize,
+    pub after_hours_ratio: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DirectoryStats {
+    pub path: String,
+    pub total_commits: usize,
+    pub after_hours_commits: usize,
+    pub after_hours_ratio: f64,
+    /// Severity score computed from this directory's commits alone.
+    pub severity_score: f64,
+}
+
+/// Headline numbers if the working day ended at `work_end` instead of the
+/// configured end.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SensitivityPoint {
+    pub work_end: String,
+    pub after_hours_commits: usize,
+    pub after_hours_ratio: f64,
+    pub overtime_days: usize,
+    pub severity_score: f64,
+}
+
+/// Which commits `--show-commits` lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowCommits {
+    Night,
+    AfterHours,
+    /// Includes holidays, like the weekend count.
+    Weekend,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommitRef {
+    pub hash: String,
+    pub timestamp: DateTime<FixedOffset>,
+    pub author: String,
+    pub subject: String,
+}
+
+/// The newest commits of one kind, capped at `--show-limit`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommitListing {
+    pub kind: ShowCommits,
+    /// All matching commits, before the cap.
+    pub total: usize,
+    pub commits: Vec<CommitRef>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriftWarning {
+    pub name: String,
+    pub drift_minutes: f64,
+}
+
+#[derive(Debug, Serialize, Default, Clone)]
+struct DayStats {
     total_commits: usize,
     after_hours_commits: usize,
+    /// Only tracked for the repo-level days.
     weekend_commits: usize,
     night_commits: usize,
+    /// First and last commit of the day, as seconds from local midnight.
+    first_second: Option<u32>,
+    last_second: u32,
 }
 
-pub fn compute_metrics(
-    repo_path: &Path,
-    commits: &[Commit],
-    ignored_authors: Vec<String>,
-    alias_rules: Vec<AliasRule>,
-) -> RepoMetrics {
-    let mut after_hours = 0usize;
-    let mut weekend = 0usize;
-    let mut night = 0usize;
-    let mut day_stats: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
-    let mut author_stats: HashMap<String, AuthorAccumulator> = HashMap::new();
-    let mut analysis_start = None;
-    let mut analysis_end = None;
-
-    for commit in commits {
-        if analysis_start.map_or(true, |s| commit.timestamp < s) {
-            analysis_start = Some(commit.timestamp);
-        }
-        if analysis_end.map_or(true, |e| commit.timestamp > e) {
-            analysis_end = Some(commit.timestamp);
+impl DayStats {
+    fn add(&mut self, second: u32, is_after_hours: bool) {
+        self.total_commits += 1;
+        if is_after_hours {
+            self.after_hours_commits += 1;
         }
+        self.first_second = Some(self.first_second.map_or(second, |first| first.min(second)));
+        self.last_second = self.last_second.max(second);
+    }
 
-        let date = commit.timestamp.date_naive();
-        let weekday = commit.timestamp.weekday();
-        let hour = commit.timestamp.hour();
-        let is_weekend = matches!(weekday, Weekday::Sat | Weekday::Sun);
-        let is_after_hours = hour < 10 || hour >= 18;
-        let is_night = hour < 6 || hour >= 23;
+    /// Estimated minutes worked outside the schedule: the part of the span
+    /// from the day's first to last commit that falls before work start or
+    /// after work end. A lone commit just after work end counts as 0 minutes.
+    fn after_hours_minutes(&self, schedule: &WorkSchedule) -> i64 {
+        let Some(first) = self.first_second else {
+            return 0;
+        };
+        let last = self.last_second;
+        let (start, end) = (schedule.start_second(), schedule.end_second());
+        let early = if first < start { last.min(start) - first } else { 0 };
+        let late = if last >= end { last - first.max(end) } else { 0 };
+        i64::from(early + late) / 60
+    }
 
-        if is_after_hours {
-            after_hours += 1;
-        }
+    fn is_overtime(&self, schedule: &WorkSchedule, min_minutes: i64) -> bool {
+        self.after_hours_commits > 0 && self.after_hours_minutes(schedule) >= min_minutes
+    }
 
-        if is_weekend {
-            weekend += 1;
-        }
+    /// [`DayStats::is_overtime`] with `schedule` alone deciding whether the
+    /// day has after-hours commits, for sweeping the work-end boundary.
+    fn is_overtime_under(&self, schedule: &WorkSchedule, min_minutes: i64) -> bool {
+        let outside = self.first_second.is_some_and(|first| first < schedule.start_second())
+            || self.last_second >= schedule.end_second();
+        outside && self.after_hours_minutes(schedule) >= min_minutes
+    }
+}
 
-        if is_night {
-            night += 1;
-        }
+/// Working hours in each commit's own local time; commits outside
+/// `[start, end)` are after hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkSchedule {
+    #[serde(serialize_with = "serialize_hh_mm", deserialize_with = "deserialize_hh_mm")]
+    pub start: NaiveTime,
+    #[serde(serialize_with = "serialize_hh_mm", deserialize_with = "deserialize_hh_mm")]
+    pub end: NaiveTime,
+}
 
-        let entry = day_stats.entry(date).or_default();
-        entry.total_commits += 1;
-        if is_after_hours {
-            entry.after_hours_commits += 1;
-        }
+fn serialize_hh_mm<S: serde::Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&time.format("%H:%M"))
+}
 
-        let author_entry = author_stats
-            .entry(commit.author.clone())
-            .or_default();
-        author_entry.total_commits += 1;
-        if is_after_hours {
-            author_entry.after_hours_commits += 1;
-        }
-        if is_weekend {
-            author_entry.weekend_commits += 1;
+fn deserialize_hh_mm<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&raw, "%H:%M").map_err(serde::de::Error::custom)
+}
+
+impl Default for WorkSchedule {
+    fn default() -> Self {
+        WorkSchedule {
+            start: NaiveTime::from_hms_opt(10, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(18, 0, 0).unwrap_or_default(),
         }
-        if is_night {
-            author_entry.night_commits += 1;
+    }
+}
+
+impl WorkSchedule {
+    /// Parses `HH:MM` bounds. The window must lie within one day; spans
+    /// like 22:00-06:00 are rejected.
+    pub fn parse(start: &str, end: &str) -> Result<Self> {
+        let parse = |raw: &str| {
+            NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+                .map_err(|_| anyhow!("工作时间应为 HH:MM 格式，当前为：{raw}"))
+        };
+        let schedule = WorkSchedule {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if schedule.start >= schedule.end {
+            bail!(
+                "工作时间 {start}-{end} 无效：上班时间必须早于下班时间，且不支持跨越午夜的时段"
+            );
         }
+        Ok(schedule)
     }
 
-    let commit_days = day_stats.len();
-    let overtime_days = day_stats
-        .values()
-        .filter(|stats| stats.after_hours_commits > 0)
-        .count();
-    let longest_streak_days = longest_streak(day_stats.keys().copied());
-    let busiest_day = day_stats
-        .iter()
-        .max_by(|(_, a), (_, b)| {
-            a.total_commits
-                .cmp(&b.total_commits)
-                .then(a.after_hours_commits.cmp(&b.after_hours_commits))
-        })
-        .map(|(date, stats)| BusiestDay {
-            date: *date,
-            total_commits: stats.total_commits,
-            after_hours_commits: stats.after_hours_commits,
-        });
+    pub fn is_after_hours(&self, timestamp: &DateTime<FixedOffset>) -> bool {
+        let time = timestamp.time();
+        time < self.start || time >= self.end
+    }
 
-    let unique_authors = author_stats.len();
+    pub fn start_second(&self) -> u32 {
+        self.start.num_seconds_from_midnight()
+    }
 
-    let mut author_summaries: Vec<AuthorSummary> = author_stats
-        .into_iter()
-        .map(|(name, stats)| {
-            let ratio = percentage(stats.after_hours_commits, stats.total_commits);
-            AuthorSummary {
-                name,
-                total_commits: stats.total_commits,
-                after_hours_commits: stats.after_hours_commits,
-                weekend_commits: stats.weekend_commits,
-                night_commits: stats.night_commits,
-                after_hours_ratio: ratio,
-            }
-        })
-        .collect();
+    pub fn end_second(&self) -> u32 {
+        self.end.num_seconds_from_midnight()
+    }
 
-    let mut nightowls = author_summaries.clone();
-    nightowls.sort_by(|a, b| {
-        b.after_hours_ratio
-            .partial_cmp(&a.after_hours_ratio)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then(b.after_hours_commits.cmp(&a.after_hours_commits))
-    });
-    nightowls.truncate(3);
+    pub fn describe(&self) -> String {
+        format!("{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
 
-    author_summaries.sort_by(|a, b| {
-        a.after_hours_ratio
-            .partial_cmp(&b.after_hours_ratio)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then(b.total_commits.cmp(&a.total_commits))
-    });
-    let mut chill_authors = author_summaries;
-    chill_authors.truncate(3);
+/// What a [`Classifier`] says about one commit. The flags feed the
+/// built-in buckets; `tags` are counted per name in
+/// [`RepoMetrics::custom_tag_counts`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Classification {
+    pub after_hours: bool,
+    pub weekend: bool,
+    pub night: bool,
+    /// Counted together with weekend commits.
+    pub holiday: bool,
+    pub tags: Vec<String>,
+}
 
-    let total_commits = commits.len();
-    let severity_score = severity_score(
-        total_commits,
-        after_hours,
-        weekend,
-        night,
-        overtime_days,
-        commit_days,
-        longest_streak_days,
-    );
-    let severity_label = severity_label(severity_score).to_string();
+/// Decides which buckets a commit falls into, so callers with their own
+/// rules (regional holidays, on-call rotations, ...) can plug them into
+/// [`compute_metrics`] without touching the aggregation.
+///
+/// A classifier that tags commits made during declared on-call windows on
+/// top of the default rules:
+///
+/// ```
+/// use std::path::Path;
+///
+/// use chrono::{DateTime, FixedOffset};
+/// use cow_horse::gitlog::Commit;
+/// use cow_horse::{compute_metrics, Classification, Classifier, DefaultClassifier, MetricsOptions};
+///
+/// struct OnCall {
+///     base: DefaultClassifier,
+///     windows: Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)>,
+/// }
+///
+/// impl Classifier for OnCall {
+///     fn classify(&self, commit: &Commit) -> Classification {
+///         let mut classification = self.base.classify(commit);
+///         let on_call = self
+///             .windows
+///             .iter()
+///             .any(|(start, end)| (*start..*end).contains(&commit.timestamp));
+///         if on_call {
+///             // Paid on-call work is not overtime, but keep a count of it.
+///             classification.after_hours = false;
+///             classification.tags.push("on_call".to_string());
+///         }
+///         classification
+///     }
+/// }
+///
+/// let at = |timestamp| DateTime::parse_from_rfc3339(timestamp).unwrap();
+/// let on_call = OnCall {
+///     base: DefaultClassifier::default(),
+///     windows: vec![(at("2026-09-01T18:00:00+08:00"), at("2026-09-02T09:00:00+08:00"))],
+/// };
+/// let commits: Vec<Commit> = ["2026-09-01T22:30:00+08:00", "2026-09-03T22:30:00+08:00"]
+///     .into_iter()
+///     .map(|timestamp| Commit {
+///         author: "Wang".to_string(),
+///         timestamp: at(timestamp),
+///         ..Commit::default()
+///     })
+///     .collect();
+///
+/// let options = MetricsOptions::default();
+/// let metrics = compute_metrics(Path::new("."), &commits, vec![], vec![], &options, &on_call);
+/// assert_eq!(metrics.custom_tag_counts.get("on_call"), Some(&1));
+/// assert_eq!(metrics.after_hours_commits, 1);
+/// ```
+pub trait Classifier {
+    fn classify(&self, commit: &Commit) -> Classification;
 
-    RepoMetrics {
-        repo_path: repo_path.to_path_buf(),
-        analysis_start,
-        analysis_end,
-        total_commits,
-        unique_authors,
-        after_hours_commits: after_hours,
-        weekend_commits: weekend,
-        night_commits: night,
-        commit_days,
-        overtime_days,
-        longest_streak_days,
-        busiest_day,
-        severity_score,
-        severity_label,
-        top_after_hours_authors: nightowls,
-        chill_authors,
-        ignored_authors,
-        alias_rules,
+    /// Whether `date` as a whole is a rest day, for day-level figures such
+    /// as expected working days. Saturday and Sunday unless overridden.
+    fn is_rest_day(&self, date: NaiveDate) -> bool {
+        matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
     }
 }
 
-fn longest_streak<I>(dates: I) -> usize
-where
-    I: IntoIterator<Item = NaiveDate>,
-{
-    let mut prev: Option<NaiveDate> = None;
-    let mut current = 0usize;
-    let mut best = 0usize;
+/// The built-in rules: outside the work schedule is after hours, the
+/// configured weekend days are weekend unless the calendar makes them a
+/// makeup workday, calendar holidays are holidays, inside the night window
+/// is night. No tags.
+#[derive(Debug, Clone, Default)]
+pub struct DefaultClassifier {
+    pub schedule: WorkSchedule,
+    pub night: NightWindow,
+    pub weekend: WeekendDays,
+    pub holidays: HolidayCalendar,
+}
 
-    for date in dates {
-        match prev {
-            None => {
-                current = 1;
-            }
-            Some(prev_date) => {
-                let diff = date.signed_duration_since(prev_date).num_days();
-                if diff == 1 {
-                    current += 1;
-                } else {
-                    current = 1;
-                }
-            }
+impl Classifier for DefaultClassifier {
+    fn classify(&self, commit: &Commit) -> Classification {
+        let timestamp = &commit.timestamp;
+        let date = timestamp.date_naive();
+        Classification {
+            after_hours: self.schedule.is_after_hours(timestamp),
+            weekend: self.weekend.contains(timestamp.weekday())
+                && !self.holidays.is_makeup_workday(date),
+            night: self.night.contains(timestamp),
+            holiday: self.holidays.is_holiday(date),
+            tags: Vec::new(),
         }
+    }
 
-        best = best.max(current);
-        prev = Some(date);
+    fn is_rest_day(&self, date: NaiveDate) -> bool {
+        self.holidays.is_holiday(date)
+            || (self.weekend.contains(date.weekday()) && !self.holidays.is_makeup_workday(date))
     }
+}
 
-    best
+/// Days of the week that count as weekend; Saturday and Sunday unless
+/// configured. Empty disables weekend accounting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekendDays {
+    /// Bit `n` set for `Weekday::num_days_from_monday() == n`.
+    mask: u8,
 }
 
-fn severity_score(
-    total: usize,
-    after_hours: usize,
-    weekend: usize,
-    night: usize,
-    overtime_days: usize,
-    commit_days: usize,
-    longest_streak: usize,
-) -> f64 {
-    if total == 0 {
-        return 0.0;
+impl Default for WeekendDays {
+    fn default() -> Self {
+        WeekendDays::from_days(&[Weekday::Sat, Weekday::Sun])
     }
+}
 
-    let after_hours_ratio = percentage(after_hours, total);
-    let weekend_ratio = percentage(weekend, total);
-    let night_ratio = percentage(night, total);
-    let overtime_day_ratio = if commit_days == 0 {
-        0.0
-    } else {
-        percentage(overtime_days, commit_days)
-    };
-    let streak_factor = (longest_streak.min(14) as f64) / 14.0;
+impl WeekendDays {
+    pub fn from_days(days: &[Weekday]) -> Self {
+        let mask = days
+            .iter()
+            .fold(0, |mask, day| mask | 1 << day.num_days_from_monday());
+        WeekendDays { mask }
+    }
+
+    /// Parses a comma list of day names such as `fri,sat`; `none` (or an
+    /// empty value) means no weekend at all.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() || raw.eq_ignore_ascii_case("none") {
+            return Ok(WeekendDays { mask: 0 });
+        }
+        let days = raw
+            .split(',')
+            .map(|name| {
+                name.trim().parse::<Weekday>().map_err(|_| {
+                    anyhow!("无法识别的星期：{name:?}（可用 mon,tue,wed,thu,fri,sat,sun 或 none）")
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(WeekendDays::from_days(&days))
+    }
 
-    let score = after_hours_ratio * 40.0
-        + weekend_ratio * 20.0
-        + night_ratio * 20.0
-        + overtime_day_ratio * 10.0
-        + streak_factor * 10.0;
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.mask & (1 << day.num_days_from_monday()) != 0
+    }
 
-    score.min(100.0)
-}
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        const NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+        NAMES
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| self.mask & (1 << idx) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
 
-fn severity_label(score: f64) -> &'static str {
-    match score as u32 {
-        0..=20 => "轻松自在",
-        21..=40 => "基本健康",
-        41..=60 => "持续加班",
-        61..=80 => "半牛马状态",
-        _ => "全面牛马预警",
+    /// `fri,sat`, or `none`.
+    pub fn describe(&self) -> String {
+        if self.is_empty() {
+            "none".to_string()
+        } else {
+            self.names().join(",")
+        }
     }
 }
 
+impl Serialize for WeekendDays {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.names())
+    }
+}
+
+impl<'de> Deserialize<'de> for WeekendDays {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        if names.is_empty() {
+            return Ok(WeekendDays::from_days(&[]));
+        }
+        WeekendDays::parse(&names.join(",")).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The night window; unlike [`WorkSchedule`] it always wraps past
+/// midnight: a commit is at night from `start` until `end` the next morning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NightWindow {
+    #[serde(serialize_with = "serialize_hh_mm", deserialize_with = "deserialize_hh_mm")]
+    pub start: NaiveTime,
+    #[serde(serialize_with = "serialize_hh_mm", deserialize_with = "deserialize_hh_mm")]
+    pub end: NaiveTime,
+}
+
+impl Default for NightWindow {
+    fn default() -> Self {
+        NightWindow {
+            start: NaiveTime::from_hms_opt(23, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+impl NightWindow {
+    /// Parses `HH:MM` bounds; `end` is exclusive and must be earlier in the
+    /// day than `start`, e.g. 22:00-06:00.
+    pub fn parse(start: &str, end: &str) -> Result<Self> {
+        let parse = |raw: &str| {
+            NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+                .map_err(|_| anyhow!("深夜时段应为 HH:MM 格式，当前为：{raw}"))
+        };
+        let window = NightWindow {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if window.start <= window.end {
+            bail!(
+                "深夜时段 {start}-{end} 无效：深夜时段必须跨越午夜，开始时间应晚于结束时间（例如 22:00-06:00）"
+            );
+        }
+        Ok(window)
+    }
+
+    pub fn contains(&self, timestamp: &DateTime<FixedOffset>) -> bool {
+        let time = timestamp.time();
+        time >= self.start || time < self.end
+    }
+
+    /// Inclusive form for labels, e.g. `23:00-05:59`.
+    pub fn describe(&self) -> String {
+        let last = self.end - Duration::minutes(1);
+        format!("{}-{}", self.start.format("%H:%M"), last.format("%H:%M"))
+    }
+}
+
+/// Knobs for [`compute_metrics`] that come from the command line.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsOptions {
+    pub rank_by: RankBy,
+    /// Authors with fewer commits stay out of the leaderboards.
+    pub min_commits: usize,
+    /// Entries per leaderboard.
+    pub top: usize,
+    pub sort_authors: SortAuthors,
+    pub weights: SeverityWeights,
+    /// Reference instant for "still active" checks.
+    pub now: DateTime<Utc>,
+    /// A day with after-hours commits only counts as an overtime day once
+    /// its estimated after-hours minutes reach this.
+    pub overtime_day_min_minutes: i64,
+    /// Days whose first-to-last commit span exceeds this are long days.
+    pub long_day_hours: f64,
+    /// After-hours ratio a busy window must exceed to count as crunch.
+    pub crunch_threshold: f64,
+    /// Shortest run of days without commits that counts as a break.
+    pub vacation_days: u32,
+    /// Commits closer than this form one session for the overtime-hours
+    /// estimate; each session starts this padding before its first commit.
+    pub session_gap_minutes: u32,
+    pub session_padding_minutes: u32,
+    pub busiest_by: BusiestBy,
+    pub schedule: WorkSchedule,
+    /// Echoed into the report; classification itself is up to the classifier.
+    pub night: NightWindow,
+    pub weekend: WeekendDays,
+    pub severity_overlap: SeverityOverlap,
+}
+
+/// The command line's defaults, with `now` taken when called.
+impl Default for MetricsOptions {
+    fn default() -> Self {
+        MetricsOptions {
+            rank_by: RankBy::default(),
+            min_commits: 5,
+            top: 3,
+            sort_authors: SortAuthors::default(),
+            weights: SeverityWeights::default(),
+            now: Utc::now(),
+            overtime_day_min_minutes: 0,
+            long_day_hours: 10.0,
+            crunch_threshold: 0.4,
+            vacation_days: 5,
+            session_gap_minutes: 60,
+            session_padding_minutes: 30,
+            busiest_by: BusiestBy::default(),
+            schedule: WorkSchedule::default(),
+            night: NightWindow::default(),
+            weekend: WeekendDays::default(),
+            severity_overlap: SeverityOverlap::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BusiestBy {
+    #[default]
+    Commits,
+    OvertimeMinutes,
+}
+
+/// One calendar day of the window with at least one commit.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub total_commits: usize,
+    pub after_hours_commits: usize,
+    pub after_hours_minutes: i64,
+    /// First and last commit of the day, in local time.
+    #[serde(default)]
+    pub first_commit: NaiveTime,
+    #[serde(default)]
+    pub last_commit: NaiveTime,
+    /// From the first to the last commit, by any author; a rough lower
+    /// bound on the workday.
+    #[serde(default)]
+    pub span_hours: f64,
+}
+
+/// An ISO week that looks like 996, with its median first and last
+/// commit times.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Week996 {
+    /// `2024-W11`
+    pub week: String,
+    pub days: usize,
+    pub median_first_commit: NaiveTime,
+    pub median_last_commit: NaiveTime,
+}
+
+/// Consecutive days covered by crunch windows, with their combined counts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrunchPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub total_commits: usize,
+    pub after_hours_commits: usize,
+    pub after_hours_ratio: f64,
+}
+
+/// Gaps without commits inside one author's own active range.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthorBreaks {
+    pub name: String,
+    /// First and last commit day; gaps outside them are not breaks, so
+    /// joining or leaving mid-window does not count.
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+    pub breaks: usize,
+    pub longest_break_days: usize,
+    /// Last day of the latest break.
+    pub last_break_end: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Author996 {
+    pub name: String,
+    pub weeks: usize,
+}
+
+/// First-to-last commit spans of the days with at least two commits.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorkdaySpan {
+    /// Days with at least two commits; single-commit days have no span.
+    pub days: usize,
+    pub average_hours: f64,
+    pub p50_hours: f64,
+    pub p90_hours: f64,
+    /// `--long-day-hours`, and the days whose span exceeds it.
+    pub long_day_hours: f64,
+    pub long_days: Vec<NaiveDate>,
+}
+
+/// Commit counts of one calendar period.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PeriodStats {
+    pub label: String,
+    pub total_commits: usize,
+    pub after_hours_commits: usize,
+    pub weekend_commits: usize,
+    pub night_commits: usize,
+}
+
+/// A change in after-hours share smaller than this many percentage points
+/// between the two halves of the window counts as stable.
+pub const TREND_STABLE_POINTS: f64 = 5.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendDirection {
+    Improving,
+    Stable,
+    Worsening,
+    /// The commits span less than two calendar weeks.
+    #[default]
+    InsufficientData,
+}
+
+/// Whether after-hours work is growing over the window.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Trend {
+    pub direction: TrendDirection,
+    /// Calendar weeks from the first to the last commit.
+    pub weeks: usize,
+    /// After-hours share of the later half of the window minus the earlier
+    /// half, in percentage points; decides `direction`.
+    pub half_delta_points: Option<f64>,
+    /// Least-squares slope of the weekly after-hours share, in percentage
+    /// points per week; weeks without commits are left out.
+    pub slope_points_per_week: Option<f64>,
+}
+
+/// Splits the days between the first and last commit in half and compares
+/// their after-hours shares; also fits a line through the weekly shares.
+fn trend(day_stats: &BTreeMap<NaiveDate, DayStats>) -> Trend {
+    let (Some(first), Some(last)) = (day_stats.keys().next(), day_stats.keys().next_back()) else {
+        return Trend::default();
+    };
+    let monday = |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_monday().into());
+    let weeks = ((monday(*last) - monday(*first)).num_days() / 7 + 1) as usize;
+    if weeks < 2 {
+        return Trend {
+            weeks,
+            ..Trend::default()
+        };
+    }
+
+    let middle = *first + Duration::days((*last - *first).num_days() / 2);
+    let mut halves = [(0usize, 0usize); 2];
+    let mut weekly: BTreeMap<NaiveDate, (usize, usize)> = BTreeMap::new();
+    for (date, day) in day_stats {
+        let half = &mut halves[usize::from(*date > middle)];
+        half.0 += day.total_commits;
+        half.1 += day.after_hours_commits;
+        let week = weekly.entry(monday(*date)).or_default();
+        week.0 += day.total_commits;
+        week.1 += day.after_hours_commits;
+    }
+    let share = |(total, after_hours): (usize, usize)| percentage(after_hours, total) * 100.0;
+    let half_delta = share(halves[1]) - share(halves[0]);
+
+    let points: Vec<(f64, f64)> = weekly
+        .into_iter()
+        .filter(|(_, (total, _))| *total > 0)
+        .map(|(week, counts)| ((week - monday(*first)).num_days() as f64 / 7.0, share(counts)))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        (cov + (x - mean_x) * (y - mean_y), var + (x - mean_x).powi(2))
+    });
+
+    let direction = if half_delta >= TREND_STABLE_POINTS {
+        TrendDirection::Worsening
+    } else if half_delta <= -TREND_STABLE_POINTS {
+        TrendDirection::Improving
+    } else {
+        TrendDirection::Stable
+    };
+    Trend {
+        direction,
+        weeks,
+        half_delta_points: Some(half_delta),
+        slope_points_per_week: (var > 0.0).then(|| cov / var),
+    }
+}
+
+/// Sums `day_stats` into one entry per `label` of the day, in date order.
+fn period_stats(
+    day_stats: &BTreeMap<NaiveDate, DayStats>,
+    label: impl Fn(NaiveDate) -> String,
+) -> Vec<PeriodStats> {
+    let mut periods: Vec<PeriodStats> = Vec::new();
+    for (date, day) in day_stats {
+        let label = label(*date);
+        if periods.last().is_none_or(|period| period.label != label) {
+            periods.push(PeriodStats {
+                label,
+                ..PeriodStats::default()
+            });
+        }
+        if let Some(period) = periods.last_mut() {
+            period.total_commits += day.total_commits;
+            period.after_hours_commits += day.after_hours_commits;
+            period.weekend_commits += day.weekend_commits;
+            period.night_commits += day.night_commits;
+        }
+    }
+    periods
+}
+
+#[derive(Default)]
+struct AuthorAccumulator {
+    total_commits: usize,
+    after_hours_commits: usize,
+    weekend_commits: usize,
+    night_commits: usize,
+    timestamps: Vec<DateTime<FixedOffset>>,
+    night_timestamps: Vec<DateTime<FixedOffset>>,
+}
+
+#[derive(Default)]
+struct DirectoryAccumulator {
+    total_commits: usize,
+    after_hours_commits: usize,
+    weekend_commits: usize,
+    night_commits: usize,
+    scored: SeverityBuckets,
+    days: BTreeMap<NaiveDate, DayStats>,
+}
+
+/// Bucket for commits without changed files (merges) or without a single
+/// dominant directory.
+pub const MIXED_DIRECTORY: &str = "(mixed)";
+const ROOT_DIRECTORY: &str = "(root)";
+
+const ALL_NIGHTER_GAP_MINUTES: i64 = 90;
+const ALL_NIGHTER_MIN_MINUTES: i64 = 180;
+const ALL_NIGHTER_MIN_COMMITS: usize = 5;
+
+const ATTRIBUTION_MIN_SHARE: f64 = 0.05;
+const ATTRIBUTION_MAX_ENTRIES: usize = 6;
+pub const ATTRIBUTION_OTHERS: &str = "其他";
+
+const DRIFT_MIN_COMMITS: usize = 20;
+const DRIFT_MIN_SPAN_DAYS: f64 = 42.0;
+const DRIFT_WARNING_MINUTES: f64 = 90.0;
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+/// Pseudo-commits at the repo mean added to every author before scoring.
+const COMPOSITE_PRIOR_COMMITS: f64 = 25.0;
+/// 95% two-sided normal quantile for the Wilson bound.
+const COMPOSITE_Z: f64 = 1.96;
+const COMPOSITE_NIGHT_WEIGHT: f64 = 0.02;
+const COMPOSITE_WEEKEND_WEIGHT: f64 = 0.01;
+
+/// Aggregates `commits` into the report. Each commit is classified once by
+/// `classifier`; pass [`DefaultClassifier`] for the built-in rules.
+pub fn compute_metrics<C: Classifier + ?Sized>(
+    repo_path: &Path,
+    commits: &[Commit],
+    ignored_author_stats: Vec<IgnoredAuthorStat>,
+    alias_rules: Vec<AliasRule>,
+    options: &MetricsOptions,
+    classifier: &C,
+) -> RepoMetrics {
+    let MetricsOptions {
+        rank_by,
+        min_commits,
+        top,
+        sort_authors,
+        weights,
+        now,
+        overtime_day_min_minutes,
+        long_day_hours,
+        crunch_threshold,
+        vacation_days,
+        session_gap_minutes,
+        session_padding_minutes,
+        busiest_by,
+        schedule,
+        night: night_window,
+        weekend: weekend_days,
+        severity_overlap,
+    } = *options;
+    let mut after_hours = 0usize;
+    let mut weekend = 0usize;
+    let mut night = 0usize;
+    let mut holiday = 0usize;
+    let mut scored = SeverityBuckets::default();
+    let mut custom_tag_counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut day_stats: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+    // BTreeMap so every per-author list below starts from the same order.
+    let mut author_stats: BTreeMap<String, AuthorAccumulator> = BTreeMap::new();
+    // Authors of commits proper, without co-author credits, so shares of
+    // the repo totals add up.
+    let mut own_buckets: BTreeMap<String, SeverityBuckets> = BTreeMap::new();
+    let mut component_stats: BTreeMap<String, DayStats> = BTreeMap::new();
+    let mut directory_stats: BTreeMap<String, DirectoryAccumulator> = BTreeMap::new();
+    let mut punch_card = [[0usize; 24]; 7];
+    // Commits re-expressed by `--timezone`, and their after-hours counts in
+    // the recorded offset and in the zone.
+    let mut zoned = 0usize;
+    let mut views = TimezoneViews::default();
+    let (mut local_after_hours, mut reference_after_hours) = (0usize, 0usize);
+    let mut analysis_start = None;
+    let mut analysis_end = None;
+
+    for commit in commits {
+        let date = commit.timestamp.date_naive();
+        let classification = classifier.classify(commit);
+        let is_after_hours = classification.after_hours;
+        // Holidays are rest days, so they share the weekend bucket.
+        let is_weekend = classification.weekend || classification.holiday;
+        let is_night = classification.night;
+
+        let author_entry = author_stats
+            .entry(commit.author.clone())
+            .or_default();
+        author_entry.total_commits += 1;
+        if is_after_hours {
+            author_entry.after_hours_commits += 1;
+        }
+        if is_weekend {
+            author_entry.weekend_commits += 1;
+        }
+        if is_night {
+            author_entry.night_commits += 1;
+            author_entry.night_timestamps.push(commit.timestamp);
+        }
+        author_entry.timestamps.push(commit.timestamp);
+
+        // A co-author credit counts for that person only; the commit itself
+        // is already in every repo-level figure under its author.
+        if commit.co_author {
+            continue;
+        }
+        own_buckets.entry(commit.author.clone()).or_default().add(
+            SeverityOverlap::Independent,
+            is_after_hours,
+            is_weekend,
+            is_night,
+        );
+
+        if analysis_start.is_none_or(|s| commit.timestamp < s) {
+            analysis_start = Some(commit.timestamp);
+        }
+        if analysis_end.is_none_or(|e| commit.timestamp > e) {
+            analysis_end = Some(commit.timestamp);
+        }
+        if classification.holiday {
+            holiday += 1;
+        }
+        let mut tags = classification.tags;
+        tags.sort_unstable();
+        tags.dedup();
+        for tag in tags {
+            *custom_tag_counts.entry(tag).or_insert(0) += 1;
+        }
+        let second = commit.timestamp.num_seconds_from_midnight();
+        punch_card[commit.timestamp.weekday().num_days_from_monday() as usize]
+            [commit.timestamp.hour() as usize] += 1;
+        if let Some(offset) = commit.recorded_offset {
+            let local = commit.timestamp.with_timezone(&offset);
+            zoned += 1;
+            views.local_hours[local.hour() as usize] += 1;
+            views.reference_hours[commit.timestamp.hour() as usize] += 1;
+            local_after_hours += usize::from(schedule.is_after_hours(&local));
+            reference_after_hours += usize::from(schedule.is_after_hours(&commit.timestamp));
+        }
+
+        if is_after_hours {
+            after_hours += 1;
+        }
+
+        if is_weekend {
+            weekend += 1;
+        }
+
+        if is_night {
+            night += 1;
+        }
+
+        scored.add(severity_overlap, is_after_hours, is_weekend, is_night);
+        let day = day_stats.entry(date).or_default();
+        day.add(second, is_after_hours);
+        day.weekend_commits += usize::from(is_weekend);
+        day.night_commits += usize::from(is_night);
+
+        if let Some(component) = &commit.component {
+            component_stats
+                .entry(component.clone())
+                .or_default()
+                .add(second, is_after_hours);
+        }
+
+        if let Some(directory) = &commit.directory {
+            let entry = directory_stats.entry(directory.clone()).or_default();
+            entry.total_commits += 1;
+            if is_after_hours {
+                entry.after_hours_commits += 1;
+            }
+            if is_weekend {
+                entry.weekend_commits += 1;
+            }
+            if is_night {
+                entry.night_commits += 1;
+            }
+            entry
+                .scored
+                .add(severity_overlap, is_after_hours, is_weekend, is_night);
+            entry.days.entry(date).or_default().add(second, is_after_hours);
+        }
+    }
+
+    let commit_days = day_stats.len();
+    let overtime_days = day_stats
+        .values()
+        .filter(|stats| stats.is_overtime(&schedule, overtime_day_min_minutes))
+        .count();
+    let longest_streak_days = longest_streak(day_stats.keys().copied());
+    let longest_overtime_streak = longest_streak_range(
+        day_stats
+            .iter()
+            .filter(|(_, day)| day.after_hours_commits > 0)
+            .map(|(date, _)| *date),
+    );
+    let (current_streak_days, current_streak_active) =
+        current_streak(day_stats.keys().copied(), analysis_end, now);
+    let busiest_day = day_stats
+        .iter()
+        .max_by(|(_, a), (_, b)| match busiest_by {
+            BusiestBy::Commits => a
+                .total_commits
+                .cmp(&b.total_commits)
+                .then(a.after_hours_commits.cmp(&b.after_hours_commits)),
+            BusiestBy::OvertimeMinutes => a
+                .after_hours_minutes(&schedule)
+                .cmp(&b.after_hours_minutes(&schedule))
+                .then(a.total_commits.cmp(&b.total_commits)),
+        })
+        .map(|(date, stats)| BusiestDay {
+            date: *date,
+            total_commits: stats.total_commits,
+            after_hours_commits: stats.after_hours_commits,
+            after_hours_minutes: stats.after_hours_minutes(&schedule),
+        });
+    let daily = day_stats
+        .iter()
+        .map(|(date, stats)| {
+            let first = stats.first_second.unwrap_or_default();
+            let time = |second| NaiveTime::from_num_seconds_from_midnight_opt(second, 0);
+            DailyStats {
+                date: *date,
+                total_commits: stats.total_commits,
+                after_hours_commits: stats.after_hours_commits,
+                after_hours_minutes: stats.after_hours_minutes(&schedule),
+                first_commit: time(first).unwrap_or_default(),
+                last_commit: time(stats.last_second).unwrap_or_default(),
+                span_hours: f64::from(stats.last_second.saturating_sub(first)) / 3600.0,
+            }
+        })
+        .collect::<Vec<_>>();
+    let workday_span = workday_span(&daily, long_day_hours);
+    let repo_days: BTreeMap<NaiveDate, (u32, u32)> = day_stats
+        .iter()
+        .map(|(date, day)| (*date, (day.first_second.unwrap_or_default(), day.last_second)))
+        .collect();
+    let (weeks_with_commits, mut worst_996_weeks) = find_996_weeks(&repo_days);
+    let weeks_996 = worst_996_weeks.len();
+    worst_996_weeks.sort_by_key(|week| Reverse((week.days, week.median_last_commit)));
+    worst_996_weeks.truncate(WORST_996_ENTRIES);
+    let crunch_periods = crunch_periods(&day_stats, crunch_threshold);
+    let weekly = period_stats(&day_stats, |date| {
+        let week = date.iso_week();
+        format!("{}-W{:02}", week.year(), week.week())
+    });
+    let monthly = period_stats(&day_stats, |date| date.format("%Y-%m").to_string());
+    let trend = trend(&day_stats);
+
+    let unique_authors = own_buckets.len();
+    let components = component_stats
+        .into_iter()
+        .map(|(path, stats)| ComponentStats {
+            path,
+            total_commits: stats.total_commits,
+            after_hours_commits: stats.after_hours_commits,
+            after_hours_ratio: percentage(stats.after_hours_commits, stats.total_commits),
+        })
+        .collect();
+    let directories = directory_stats
+        .into_iter()
+        .map(|(path, stats)| {
+            let overtime_days = stats
+                .days
+                .values()
+                .filter(|day| day.is_overtime(&schedule, overtime_day_min_minutes))
+                .count();
+            DirectoryStats {
+                path,
+                total_commits: stats.total_commits,
+                after_hours_commits: stats.after_hours_commits,
+                after_hours_ratio: percentage(stats.after_hours_commits, stats.total_commits),
+                severity_score: severity_score(
+                    &SeverityCounts {
+                        total: stats.total_commits,
+                        after_hours: stats.scored.after_hours,
+                        weekend: stats.scored.weekend,
+                        night: stats.scored.night,
+                        overtime_days,
+                        commit_days: stats.days.len(),
+                        longest_streak: longest_streak(stats.days.keys().copied()),
+                    },
+                    &weights,
+                ),
+            }
+        })
+        .collect();
+
+    let total_commits = commits.iter().filter(|commit| !commit.co_author).count();
+    let repo_after_hours_ratio = percentage(after_hours, total_commits);
+    let mut all_nighter_events: Vec<AllNighterEvent> = Vec::new();
+    let mut breaks: Vec<AuthorBreaks> = Vec::new();
+    let mut author_summaries: Vec<AuthorSummary> = author_stats
+        .into_iter()
+        .map(|(name, mut stats)| {
+            // Float sums in the drift fit depend on order; input order must not leak.
+            stats.timestamps.sort();
+            let ratio = percentage(stats.after_hours_commits, stats.total_commits);
+            let events = all_nighters(&name, stats.night_timestamps);
+            let all_nighters = events.len();
+            all_nighter_events.extend(events);
+            let newest = stats.timestamps.iter().max().copied();
+            let days: Vec<NaiveDate> = stats
+                .timestamps
+                .iter()
+                .map(|ts| ts.date_naive())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            breaks.extend(author_breaks(&name, &days, vacation_days));
+            let (current_streak_days, current_streak_active) = current_streak(days, newest, now);
+            let mut own_days: BTreeMap<NaiveDate, (u32, u32)> = BTreeMap::new();
+            for ts in &stats.timestamps {
+                let second = ts.num_seconds_from_midnight();
+                let day = own_days.entry(ts.date_naive()).or_insert((second, second));
+                *day = (day.0.min(second), day.1.max(second));
+            }
+            let (_, author_996) = find_996_weeks(&own_days);
+            let overtime_minutes = session_overtime_minutes(
+                &stats.timestamps,
+                session_gap_minutes,
+                session_padding_minutes,
+                &schedule,
+                classifier,
+            );
+            let composite_score = composite_score(
+                stats.total_commits,
+                stats.after_hours_commits,
+                stats.night_commits,
+                stats.weekend_commits,
+                repo_after_hours_ratio,
+            );
+            AuthorSummary {
+                name,
+                total_commits: stats.total_commits,
+                after_hours_commits: stats.after_hours_commits,
+                weekend_commits: stats.weekend_commits,
+                night_commits: stats.night_commits,
+                after_hours_ratio: ratio,
+                commit_time_drift_minutes: commit_time_drift(&stats.timestamps),
+                all_nighters,
+                composite_score,
+                current_streak_days,
+                current_streak_active,
+                estimated_overtime_hours: overtime_minutes as f64 / 60.0,
+                weeks_996: author_996.len(),
+                alternate_names: Vec::new(),
+            }
+        })
+        .collect();
+    let estimated_overtime_hours = author_summaries
+        .iter()
+        .map(|author| author.estimated_overtime_hours)
+        .sum();
+    let mut authors_996: Vec<Author996> = author_summaries
+        .iter()
+        .filter(|author| author.weeks_996 > 0)
+        .map(|author| Author996 {
+            name: author.name.clone(),
+            weeks: author.weeks_996,
+        })
+        .collect();
+    authors_996.sort_by_key(|author| Reverse(author.weeks));
+    authors_996.truncate(WORST_996_ENTRIES);
+    let days_since_last_break = breaks
+        .iter()
+        .filter_map(|author| author.last_break_end)
+        .max()
+        .zip(analysis_end)
+        .map(|(end, last)| (last.date_naive() - end).num_days());
+    breaks.sort_by(|a, b| {
+        (a.breaks, a.longest_break_days, &a.name).cmp(&(b.breaks, b.longest_break_days, &b.name))
+    });
+
+    let mut drift_warnings: Vec<DriftWarning> = author_summaries
+        .iter()
+        .filter_map(|author| {
+            author
+                .commit_time_drift_minutes
+                .filter(|drift| *drift > DRIFT_WARNING_MINUTES)
+                .map(|drift| DriftWarning {
+                    name: author.name.clone(),
+                    drift_minutes: drift,
+                })
+        })
+        .collect();
+    drift_warnings.sort_by(|a, b| {
+        b.drift_minutes
+            .partial_cmp(&a.drift_minutes)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.name.cmp(&b.name))
+    });
+
+    all_nighter_events.sort_by(|a, b| a.start.cmp(&b.start).then(a.author.cmp(&b.author)));
+    let worst_all_nighter = all_nighter_events
+        .iter()
+        .max_by(|a, b| {
+            a.duration_minutes
+                .cmp(&b.duration_minutes)
+                .then(a.commits.cmp(&b.commits))
+        })
+        .cloned();
+
+    let attribution = attribution(&own_buckets, after_hours, weekend, night);
+    // Still in name order from the BTreeMap, so ties below stay by name.
+    let mut authors = author_summaries.clone();
+    match sort_authors {
+        SortAuthors::Commits => authors.sort_by_key(|author| Reverse(author.total_commits)),
+        SortAuthors::AfterHours => authors.sort_by_key(|author| Reverse(author.after_hours_commits)),
+        SortAuthors::Ratio => authors.sort_by(|a, b| {
+            b.after_hours_ratio
+                .partial_cmp(&a.after_hours_ratio)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        SortAuthors::Name => {}
+    }
+
+    let rank_key = |author: &AuthorSummary| match rank_by {
+        RankBy::Ratio => author.after_hours_ratio,
+        RankBy::Composite => author.composite_score,
+    };
+    // Too few commits make the ratios meaningless; such authors still count
+    // toward the repo totals above.
+    author_summaries.retain(|author| author.total_commits >= min_commits);
+    let below_min_commits_authors = authors.len() - author_summaries.len();
+    let mut nightowls = author_summaries.clone();
+    nightowls.sort_by(|a, b| {
+        rank_key(b)
+            .partial_cmp(&rank_key(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.after_hours_commits.cmp(&a.after_hours_commits))
+            .then(a.name.cmp(&b.name))
+    });
+    nightowls.truncate(top);
+
+    author_summaries.sort_by(|a, b| {
+        rank_key(a)
+            .partial_cmp(&rank_key(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.total_commits.cmp(&a.total_commits))
+            .then(a.name.cmp(&b.name))
+    });
+    let mut chill_authors = author_summaries;
+    chill_authors.truncate(top);
+
+    let severity_breakdown = severity_breakdown(
+        &SeverityCounts {
+            total: total_commits,
+            after_hours: scored.after_hours,
+            weekend: scored.weekend,
+            night: scored.night,
+            overtime_days,
+            commit_days,
+            longest_streak: longest_streak_days,
+        },
+        &weights,
+    );
+    let severity_score = severity_breakdown.score();
+    let severity_label = severity_label(severity_score).to_string();
+
+    RepoMetrics {
+        repo_path: repo_path.to_path_buf(),
+        head: None,
+        window_description: String::new(),
+        window_start: None,
+        window_end: None,
+        analysis_start,
+        analysis_end,
+        coverage: None,
+        newest_commit_age_days: None,
+        timezone: None,
+        timezone_views: (zoned > 0).then(|| TimezoneViews {
+            local_after_hours_ratio: percentage(local_after_hours, zoned),
+            reference_after_hours_ratio: percentage(reference_after_hours, zoned),
+            ..views
+        }),
+        work_schedule: schedule,
+        night_window,
+        weekend_days,
+        severity_overlap,
+        severity_buckets: scored,
+        custom_tag_counts,
+        total_commits,
+        unique_authors,
+        after_hours_commits: after_hours,
+        weekend_commits: weekend,
+        holiday_commits: holiday,
+        night_commits: night,
+        commit_days,
+        overtime_days,
+        estimated_overtime_hours,
+        session_gap_minutes,
+        session_padding_minutes,
+        workday_span,
+        weeks_996,
+        weeks_with_commits,
+        worst_996_weeks,
+        authors_996,
+        crunch_periods,
+        crunch_threshold,
+        breaks,
+        vacation_days,
+        days_since_last_break,
+        longest_streak_days,
+        longest_overtime_streak_days: longest_overtime_streak.map_or(0, |(start, end)| {
+            (end - start).num_days() as usize + 1
+        }),
+        longest_overtime_streak_start: longest_overtime_streak.map(|(start, _)| start),
+        longest_overtime_streak_end: longest_overtime_streak.map(|(_, end)| end),
+        current_streak_days,
+        current_streak_active,
+        all_nighters: all_nighter_events.len(),
+        worst_all_nighter,
+        all_nighter_events,
+        busiest_day,
+        daily,
+        weekly,
+        monthly,
+        trend,
+        punch_card,
+        severity_score,
+        severity_breakdown,
+        severity_weights: weights,
+        severity_label,
+        severity_band: severity_band(severity_score),
+        expected_working_days: 0,
+        low_confidence: false,
+        low_confidence_reason: None,
+        quip: None,
+        forecast: None,
+        top_after_hours_authors: nightowls,
+        chill_authors,
+        leaderboard_min_commits: min_commits,
+        below_min_commits_authors,
+        authors,
+        attribution,
+        ignored_authors: ignored_author_stats
+            .iter()
+            .filter(|stat| stat.source != IgnoreSource::Pattern && stat.source != IgnoreSource::Bot)
+            .map(|stat| stat.name.clone())
+            .collect(),
+        ignored_author_stats,
+        alias_rules,
+        suspect_identities: Vec::new(),
+        excluded_spans: Vec::new(),
+        rev_range: None,
+        branches: Vec::new(),
+        pathspecs: Vec::new(),
+        date_source: DateSource::Author,
+        rewritten_commits: None,
+        skipped_merge_commits: None,
+        repo_config_path: None,
+        user_config_path: None,
+        policy_results: Vec::new(),
+        drift_warnings,
+        components,
+        directories,
+        skipped_submodules: Vec::new(),
+        sensitivity: Vec::new(),
+        shown_commits: None,
+    }
+}
+
+/// The `--author-report` figures for `name`, taken from the author's own
+/// commits in `commits` (co-author credits are left out, as in the
+/// repository figures). `None` when the author has no such commit.
+pub fn compute_author_metrics<C: Classifier + ?Sized>(
+    name: &str,
+    repo: &RepoMetrics,
+    commits: &[Commit],
+    options: &MetricsOptions,
+    classifier: &C,
+) -> Option<AuthorMetrics> {
+    let own: Vec<Commit> = commits
+        .iter()
+        .filter(|commit| !commit.co_author && commit.author == name)
+        .cloned()
+        .collect();
+    if own.is_empty() {
+        return None;
+    }
+    let author = compute_metrics(&repo.repo_path, &own, Vec::new(), Vec::new(), options, classifier);
+    let (first_day, last_day) = match (author.daily.first(), author.daily.last()) {
+        (Some(first), Some(last)) => (first.date, last.date),
+        _ => return None,
+    };
+    // Window bounds are local midnights; `window_end` is exclusive.
+    let local_date = |instant: DateTime<Utc>| instant.with_timezone(&Local).date_naive();
+    let calendar_start = repo.window_start.map_or(first_day, local_date).min(first_day);
+    let calendar_end = repo
+        .window_end
+        .map_or(last_day, |end| local_date(end.min(options.now) - Duration::seconds(1)))
+        .max(last_day);
+    let compare = |commits: usize, repo_commits: usize| RatioComparison {
+        commits,
+        ratio: percentage(commits, author.total_commits),
+        repo_ratio: percentage(repo_commits, repo.total_commits),
+    };
+    Some(AuthorMetrics {
+        name: name.to_string(),
+        repo_path: repo.repo_path.clone(),
+        window_description: repo.window_description.clone(),
+        total_commits: author.total_commits,
+        commit_share: percentage(author.total_commits, repo.total_commits),
+        first_commit: author.analysis_start,
+        last_commit: author.analysis_end,
+        commit_days: author.commit_days,
+        longest_streak_days: author.longest_streak_days,
+        busiest_day: author.busiest_day.clone(),
+        estimated_overtime_hours: author.estimated_overtime_hours,
+        after_hours: compare(author.after_hours_commits, repo.after_hours_commits),
+        weekend: compare(author.weekend_commits, repo.weekend_commits),
+        night: compare(author.night_commits, repo.night_commits),
+        punch_card: author.punch_card,
+        work_schedule: author.work_schedule,
+        daily: author.daily,
+        calendar_start,
+        calendar_end,
+        repos: Vec::new(),
+        most_after_hours_repo: None,
+    })
+}
+
+/// Lists the commits `classifier` puts in `kind`, newest first, keeping at
+/// most `limit` of them.
+pub fn list_commits<C: Classifier + ?Sized>(
+    commits: &[Commit],
+    kind: ShowCommits,
+    limit: usize,
+    classifier: &C,
+) -> CommitListing {
+    let mut matching: Vec<&Commit> = commits
+        .iter()
+        .filter(|commit| {
+            let classification = classifier.classify(commit);
+            match kind {
+                ShowCommits::Night => classification.night,
+                ShowCommits::AfterHours => classification.after_hours,
+                ShowCommits::Weekend => classification.weekend || classification.holiday,
+            }
+        })
+        .collect();
+    matching.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(a.hash.cmp(&b.hash)));
+    CommitListing {
+        kind,
+        total: matching.len(),
+        commits: matching
+            .into_iter()
+            .take(limit)
+            .map(|commit| CommitRef {
+                hash: commit.hash.clone(),
+                timestamp: commit.timestamp,
+                author: commit.author.clone(),
+                subject: commit.subject.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Picks the directory prefix (first `depth` directory components) that holds
+/// the most of `paths`. Files at the top level count as `(root)`; no paths or
+/// a tie for first place yield `(mixed)`.
+pub fn attribute_directory(paths: &[String], depth: u32) -> String {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for path in paths {
+        let components: Vec<&str> = path.split('/').collect();
+        let dirs = &components[..components.len() - 1];
+        let prefix = if dirs.is_empty() {
+            ROOT_DIRECTORY.to_string()
+        } else {
+            dirs[..dirs.len().min(depth as usize)].join("/")
+        };
+        *counts.entry(prefix).or_default() += 1;
+    }
+
+    let best = counts.values().copied().max().unwrap_or(0);
+    let mut leaders = counts.into_iter().filter(|(_, count)| *count == best);
+    match (leaders.next(), leaders.next()) {
+        (Some((dir, _)), None) => dir,
+        _ => MIXED_DIRECTORY.to_string(),
+    }
+}
+
+/// A burst this dense from more than one raw name cannot be one person.
+const SUSPECT_SAME_MINUTE_COMMITS: usize = 5;
+
+/// Flags aliased identities that look like several people folded into one:
+/// more than `max_commits_per_day` commits on at least two days, or
+/// [`SUSPECT_SAME_MINUTE_COMMITS`] commits within one minute coming from
+/// different raw names. Only identities that an alias actually fed are
+/// checked, since a single raw name cannot be over-merged.
+pub fn find_suspect_identities(
+    commits: &[Commit],
+    max_commits_per_day: usize,
+) -> Vec<SuspectIdentity> {
+    #[derive(Default)]
+    struct Identity<'a> {
+        raw_names: BTreeSet<&'a str>,
+        per_day: BTreeMap<NaiveDate, usize>,
+        /// Minute bucket -> raw names seen in it, with repeats.
+        per_minute: BTreeMap<i64, Vec<&'a str>>,
+    }
+
+    let mut identities: BTreeMap<&str, Identity> = BTreeMap::new();
+    for commit in commits {
+        let identity = identities.entry(commit.author.as_str()).or_default();
+        let raw = commit.raw_author.as_deref().unwrap_or(&commit.author);
+        identity.raw_names.insert(raw);
+        *identity.per_day.entry(commit.timestamp.date_naive()).or_default() += 1;
+        identity
+            .per_minute
+            .entry(commit.timestamp.timestamp().div_euclid(60))
+            .or_default()
+            .push(raw);
+    }
+
+    identities
+        .into_iter()
+        .filter(|(_, identity)| identity.raw_names.len() > 1)
+        .filter_map(|(name, identity)| {
+            let mut reasons = Vec::new();
+            let heavy_days = identity
+                .per_day
+                .values()
+                .filter(|count| **count > max_commits_per_day)
+                .count();
+            if heavy_days >= 2 {
+                reasons.push(SuspectReason::HeavyDays {
+                    days: heavy_days,
+                    limit: max_commits_per_day,
+                });
+            }
+            let bursts = identity
+                .per_minute
+                .values()
+                .filter(|raw| {
+                    raw.len() >= SUSPECT_SAME_MINUTE_COMMITS
+                        && raw.iter().collect::<BTreeSet<_>>().len() > 1
+                })
+                .count();
+            if bursts > 0 {
+                reasons.push(SuspectReason::SameMinuteBursts {
+                    minutes: bursts,
+                    min_commits: SUSPECT_SAME_MINUTE_COMMITS,
+                });
+            }
+            (!reasons.is_empty()).then(|| SuspectIdentity {
+                name: name.to_string(),
+                reasons,
+                merged_from: identity.raw_names.iter().map(|raw| raw.to_string()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Below this many expected working days the ratios say more about the
+/// calendar than about the team.
+const LOW_CONFIDENCE_WORKING_DAYS: usize = 3;
+
+/// Counts the days in the inclusive range `start..=end` that are not rest
+/// days for `classifier`.
+pub fn working_days_between<C: Classifier + ?Sized>(
+    start: NaiveDate,
+    end: NaiveDate,
+    classifier: &C,
+) -> usize {
+    start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| !classifier.is_rest_day(*d))
+        .count()
+}
+
+/// Runs of at least `min_days` calendar days without commits between an
+/// author's sorted, distinct commit `days`. `None` with fewer than two
+/// commit days.
+fn author_breaks(name: &str, days: &[NaiveDate], min_days: u32) -> Option<AuthorBreaks> {
+    let (first, last) = (*days.first()?, *days.last()?);
+    if first == last {
+        return None;
+    }
+    let mut breaks = 0;
+    let mut longest_break_days = 0;
+    let mut last_break_end = None;
+    for pair in days.windows(2) {
+        let gap = (pair[1] - pair[0]).num_days() - 1;
+        if gap >= i64::from(min_days) {
+            breaks += 1;
+            longest_break_days = longest_break_days.max(gap as usize);
+            last_break_end = Some(pair[1] - Duration::days(1));
+        }
+    }
+    Some(AuthorBreaks {
+        name: name.to_string(),
+        first_day: first,
+        last_day: last,
+        breaks,
+        longest_break_days,
+        last_break_end,
+    })
+}
+
+const CRUNCH_WINDOW_DAYS: i64 = 14;
+
+/// Slides a [`CRUNCH_WINDOW_DAYS`] window one day at a time from the first
+/// to the last commit day. A window is crunch when its after-hours ratio
+/// exceeds `threshold` and its commit count exceeds the median window's;
+/// overlapping or adjacent crunch windows merge into one period, trimmed
+/// to its first and last day with after-hours commits. Nothing is reported
+/// when the commits span less than one window.
+fn crunch_periods(day_stats: &BTreeMap<NaiveDate, DayStats>, threshold: f64) -> Vec<CrunchPeriod> {
+    let (Some(first), Some(last)) = (day_stats.keys().next(), day_stats.keys().next_back()) else {
+        return Vec::new();
+    };
+    let span = Duration::days(CRUNCH_WINDOW_DAYS - 1);
+    let counts = |start: NaiveDate, end: NaiveDate| {
+        day_stats
+            .range(start..=end)
+            .fold((0, 0), |(total, after_hours), (_, day)| {
+                (total + day.total_commits, after_hours + day.after_hours_commits)
+            })
+    };
+    let windows: Vec<(NaiveDate, usize, usize)> = first
+        .iter_days()
+        .take_while(|start| *start + span <= *last)
+        .map(|start| {
+            let (total, after_hours) = counts(start, start + span);
+            (start, total, after_hours)
+        })
+        .collect();
+    if windows.is_empty() {
+        return Vec::new();
+    }
+    let mut volumes: Vec<usize> = windows.iter().map(|window| window.1).collect();
+    volumes.sort_unstable();
+    let median = volumes[(volumes.len() - 1) / 2];
+
+    let mut spans: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for &(start, total, after_hours) in &windows {
+        if total <= median || percentage(after_hours, total) <= threshold {
+            continue;
+        }
+        let end = start + span;
+        match spans.last_mut() {
+            Some(last) if start <= last.1 + Duration::days(1) => last.1 = end,
+            _ => spans.push((start, end)),
+        }
+    }
+    spans
+        .into_iter()
+        .filter_map(|(start, end)| {
+            // Trim the quiet edges the windows drag in.
+            let mut busy = day_stats
+                .range(start..=end)
+                .filter(|(_, day)| day.after_hours_commits > 0)
+                .map(|(date, _)| *date);
+            let start = busy.next()?;
+            let end = busy.next_back().unwrap_or(start);
+            let (total, after_hours) = counts(start, end);
+            Some(CrunchPeriod {
+                start,
+                end,
+                total_commits: total,
+                after_hours_commits: after_hours,
+                after_hours_ratio: percentage(after_hours, total),
+            })
+        })
+        .collect()
+}
+
+/// A 996 week has commits on at least this many days, ...
+const WEEK_996_MIN_DAYS: usize = 6;
+/// ... a median first commit no later than this (9:00 plus slack) ...
+const WEEK_996_LATEST_FIRST_SECOND: u32 = 10 * 3600 + 30 * 60;
+/// ... and a median last commit no earlier than this.
+const WEEK_996_EARLIEST_LAST_SECOND: u32 = 21 * 3600;
+const WORST_996_ENTRIES: usize = 5;
+
+/// Groups `(first, last)` commit seconds per day into ISO weeks and returns
+/// how many weeks had commits and the ones that look like 996, oldest
+/// first. Medians take the lower middle day.
+fn find_996_weeks(days: &BTreeMap<NaiveDate, (u32, u32)>) -> (usize, Vec<Week996>) {
+    let mut weeks: BTreeMap<IsoWeek, Vec<(u32, u32)>> = BTreeMap::new();
+    for (date, span) in days {
+        weeks.entry(date.iso_week()).or_default().push(*span);
+    }
+    let checked = weeks.len();
+    let time = |second| NaiveTime::from_num_seconds_from_midnight_opt(second, 0).unwrap_or_default();
+    let flagged = weeks
+        .into_iter()
+        .filter(|(_, spans)| spans.len() >= WEEK_996_MIN_DAYS)
+        .filter_map(|(week, spans)| {
+            let median = |mut values: Vec<u32>| {
+                values.sort_unstable();
+                values[(values.len() - 1) / 2]
+            };
+            let first = median(spans.iter().map(|span| span.0).collect());
+            let last = median(spans.iter().map(|span| span.1).collect());
+            (first <= WEEK_996_LATEST_FIRST_SECOND && last >= WEEK_996_EARLIEST_LAST_SECOND).then(
+                || Week996 {
+                    week: format!("{}-W{:02}", week.year(), week.week()),
+                    days: spans.len(),
+                    median_first_commit: time(first),
+                    median_last_commit: time(last),
+                },
+            )
+        })
+        .collect();
+    (checked, flagged)
+}
+
+/// Average and nearest-rank p50/p90 of the daily spans, skipping days
+/// with a single commit.
+fn workday_span(daily: &[DailyStats], long_day_hours: f64) -> WorkdaySpan {
+    let spanned: Vec<&DailyStats> = daily.iter().filter(|day| day.total_commits > 1).collect();
+    let mut spans: Vec<f64> = spanned.iter().map(|day| day.span_hours).collect();
+    spans.sort_by(f64::total_cmp);
+    let percentile = |p: f64| {
+        let rank = (p * spans.len() as f64).ceil() as usize;
+        spans.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+    };
+    WorkdaySpan {
+        days: spans.len(),
+        average_hours: if spans.is_empty() {
+            0.0
+        } else {
+            spans.iter().sum::<f64>() / spans.len() as f64
+        },
+        p50_hours: percentile(0.5),
+        p90_hours: percentile(0.9),
+        long_day_hours,
+        long_days: spanned
+            .iter()
+            .filter(|day| day.span_hours > long_day_hours)
+            .map(|day| day.date)
+            .collect(),
+    }
+}
+
+/// Minutes outside work hours covered by one author's work sessions.
+/// Sorted `timestamps` closer than `gap_minutes` (see [`commit_gap`]) form
+/// one session, which is assumed to start `padding_minutes` before its first
+/// commit and to end at its last. Lengths are elapsed time, so a session
+/// across an offset change is not stretched or shrunk by it; only the split
+/// into days uses local dates, in the offset of the session's first commit.
+/// Rest days count in full; on working days only the time outside
+/// `schedule` counts.
+fn session_overtime_minutes<C: Classifier + ?Sized>(
+    timestamps: &[DateTime<FixedOffset>],
+    gap_minutes: u32,
+    padding_minutes: u32,
+    schedule: &WorkSchedule,
+    classifier: &C,
+) -> i64 {
+    let gap = Duration::minutes(gap_minutes.into());
+    let padding = Duration::minutes(padding_minutes.into());
+    let mut sessions: Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = Vec::new();
+    let mut previous: Option<DateTime<FixedOffset>> = None;
+    for timestamp in timestamps {
+        match sessions.last_mut() {
+            Some(session) if previous.is_some_and(|prev| commit_gap(prev, *timestamp) < gap) => {
+                session.1 = session.1.max(*timestamp);
+            }
+            _ => sessions.push((*timestamp - padding, *timestamp)),
+        }
+        previous = Some(*timestamp);
+    }
+
+    let mut minutes = 0;
+    for (start, end) in sessions {
+        let offset = *start.offset();
+        let at = |date: NaiveDate, time: NaiveTime| {
+            DateTime::<FixedOffset>::from_naive_utc_and_offset(date.and_time(time) - offset, offset)
+        };
+        let mut cursor = start;
+        while cursor < end {
+            let date = cursor.date_naive();
+            let until = end.min(at(date + Duration::days(1), NaiveTime::MIN));
+            let length = (until - cursor).num_minutes();
+            let working = if classifier.is_rest_day(date) {
+                0
+            } else {
+                let work_start = cursor.max(at(date, schedule.start));
+                let work_end = until.min(at(date, schedule.end));
+                (work_end - work_start).num_minutes().max(0)
+            };
+            minutes += length - working;
+            cursor = until;
+        }
+    }
+    minutes
+}
+
+/// Records the window's working-day count and marks the metrics as low
+/// confidence when there are too few working days to judge overtime.
+pub fn assess_confidence(metrics: &mut RepoMetrics, expected_working_days: usize) {
+    metrics.expected_working_days = expected_working_days;
+    metrics.low_confidence = expected_working_days < LOW_CONFIDENCE_WORKING_DAYS;
+    metrics.low_confidence_reason = metrics
+        .low_confidence
+        .then(|| Lang::Zh.format(Msg::LowConfidence, &[&expected_working_days]));
+}
+
+/// Length of the run of consecutive days ending at the last of the sorted
+/// `dates`, and whether it is still going: the last day is today or
+/// yesterday, judged in the offset of the `newest` commit so the day
+/// boundary matches how commits were bucketed.
+fn current_streak<I>(
+    dates: I,
+    newest: Option<DateTime<FixedOffset>>,
+    now: DateTime<Utc>,
+) -> (usize, bool)
+where
+    I: IntoIterator<Item = NaiveDate>,
+{
+    let dates: Vec<NaiveDate> = dates.into_iter().collect();
+    let Some(&last) = dates.last() else {
+        return (0, false);
+    };
+    let days = 1 + dates
+        .windows(2)
+        .rev()
+        .take_while(|pair| pair[1].signed_duration_since(pair[0]).num_days() == 1)
+        .count();
+    let active = newest.is_some_and(|newest| {
+        let today = now.with_timezone(newest.offset()).date_naive();
+        today.signed_duration_since(last).num_days() <= 1
+    });
+    (days, active)
+}
+
+fn longest_streak<I>(dates: I) -> usize
+where
+    I: IntoIterator<Item = NaiveDate>,
+{
+    longest_streak_range(dates).map_or(0, |(start, end)| {
+        end.signed_duration_since(start).num_days() as usize + 1
+    })
+}
+
+/// First and last day of the longest run of consecutive `dates` (sorted
+/// ascending); the earliest run wins a tie.
+fn longest_streak_range<I>(dates: I) -> Option<(NaiveDate, NaiveDate)>
+where
+    I: IntoIterator<Item = NaiveDate>,
+{
+    let mut prev: Option<NaiveDate> = None;
+    let mut start: Option<NaiveDate> = None;
+    let mut best: Option<(NaiveDate, NaiveDate)> = None;
+
+    for date in dates {
+        let continues = prev.is_some_and(|prev_date| {
+            date.signed_duration_since(prev_date).num_days() == 1
+        });
+        if !continues {
+            start = Some(date);
+        }
+        let run_start = start.unwrap_or(date);
+        if best.is_none_or(|(s, e)| date - run_start > e - s) {
+            best = Some((run_start, date));
+        }
+        prev = Some(date);
+    }
+
+    best
+}
+
+/// Splits the repo-level after-hours/weekend/night counts by the author of
+/// each commit; co-author credits are left out, as they are from the
+/// totals. Authors below 5% in every category (or beyond the first six)
+/// are summed into `其他`, so each category still adds up to 100%.
+fn attribution(
+    authors: &BTreeMap<String, SeverityBuckets>,
+    after_hours: usize,
+    weekend: usize,
+    night: usize,
+) -> Vec<AttributionEntry> {
+    let mut entries: Vec<AttributionEntry> = authors
+        .iter()
+        .map(|(name, buckets)| AttributionEntry {
+            author: name.clone(),
+            after_hours_share: percentage(buckets.after_hours, after_hours),
+            weekend_share: percentage(buckets.weekend, weekend),
+            night_share: percentage(buckets.night, night),
+        })
+        .collect();
+    let weight = |e: &AttributionEntry| e.after_hours_share + e.weekend_share + e.night_share;
+    entries.sort_by(|a, b| {
+        weight(b)
+            .partial_cmp(&weight(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.author.cmp(&b.author))
+    });
+
+    let mut kept = Vec::new();
+    let mut others = AttributionEntry {
+        author: ATTRIBUTION_OTHERS.to_string(),
+        after_hours_share: 0.0,
+        weekend_share: 0.0,
+        night_share: 0.0,
+    };
+    let mut folded = 0usize;
+    for entry in entries {
+        let significant = entry.after_hours_share >= ATTRIBUTION_MIN_SHARE
+            || entry.weekend_share >= ATTRIBUTION_MIN_SHARE
+            || entry.night_share >= ATTRIBUTION_MIN_SHARE;
+        if significant && kept.len() < ATTRIBUTION_MAX_ENTRIES {
+            kept.push(entry);
+        } else {
+            others.after_hours_share += entry.after_hours_share;
+            others.weekend_share += entry.weekend_share;
+            others.night_share += entry.night_share;
+            folded += 1;
+        }
+    }
+    if folded > 0 && weight(&others) > 0.0 {
+        kept.push(others);
+    }
+    kept
+}
+
+/// Elapsed time between two commits at full (second) precision. Offsets
+/// are part of each timestamp, so the result is exact across offset
+/// changes; compare it against a `Duration` rather than truncated minutes,
+/// otherwise a 90m59s gap would pass a 90-minute threshold. Commits belong
+/// together while their gap is strictly below a threshold: a gap of exactly
+/// the threshold starts a new session or all-nighter.
+fn commit_gap(earlier: DateTime<FixedOffset>, later: DateTime<FixedOffset>) -> Duration {
+    later - earlier
+}
+
+/// Clusters an author's night commits into all-nighter events. Gaps are
+/// measured on full timestamps, so a session running from 23:40 into 02:10
+/// the next calendar day stays one event.
+fn all_nighters(author: &str, mut night: Vec<DateTime<FixedOffset>>) -> Vec<AllNighterEvent> {
+    night.sort();
+    let mut events = Vec::new();
+    let mut cluster: Vec<DateTime<FixedOffset>> = Vec::new();
+
+    let mut flush = |cluster: &mut Vec<DateTime<FixedOffset>>| {
+        if let (Some(first), Some(last)) = (cluster.first(), cluster.last()) {
+            let duration = commit_gap(*first, *last);
+            if duration >= Duration::minutes(ALL_NIGHTER_MIN_MINUTES)
+                || cluster.len() >= ALL_NIGHTER_MIN_COMMITS
+            {
+                events.push(AllNighterEvent {
+                    author: author.to_string(),
+                    start: *first,
+                    end: *last,
+                    commits: cluster.len(),
+                    duration_minutes: duration.num_minutes(),
+                });
+            }
+        }
+        cluster.clear();
+    };
+
+    for ts in night {
+        if let Some(prev) = cluster.last()
+            && commit_gap(*prev, ts) >= Duration::minutes(ALL_NIGHTER_GAP_MINUTES)
+        {
+            flush(&mut cluster);
+        }
+        cluster.push(ts);
+    }
+    flush(&mut cluster);
+
+    events
+}
+
+/// Least-squares slope of commit time-of-day against date, scaled to the
+/// author's whole span. Minutes are unwrapped around the circular mean so a
+/// habit straddling midnight (23:50, 00:10) does not look like a 23-hour jump.
+fn commit_time_drift(timestamps: &[DateTime<FixedOffset>]) -> Option<f64> {
+    if timestamps.len() < DRIFT_MIN_COMMITS {
+        return None;
+    }
+
+    let first = timestamps.iter().min()?;
+    let last = timestamps.iter().max()?;
+    let span_days = commit_gap(*first, *last).num_seconds() as f64 / 86_400.0;
+    if span_days < DRIFT_MIN_SPAN_DAYS {
+        return None;
+    }
+
+    let minutes: Vec<f64> = timestamps
+        .iter()
+        .map(|ts| ts.num_seconds_from_midnight() as f64 / 60.0)
+        .collect();
+    let (sin_sum, cos_sum) = minutes.iter().fold((0.0, 0.0), |(s, c), m| {
+        let angle = m / MINUTES_PER_DAY * TAU;
+        (s + angle.sin(), c + angle.cos())
+    });
+    let anchor = sin_sum.atan2(cos_sum).rem_euclid(TAU) / TAU * MINUTES_PER_DAY;
+
+    let points: Vec<(f64, f64)> = timestamps
+        .iter()
+        .zip(&minutes)
+        .map(|(ts, m)| {
+            let x = commit_gap(*first, *ts).num_seconds() as f64 / 86_400.0;
+            let mut y = m - anchor;
+            if y >= MINUTES_PER_DAY / 2.0 {
+                y -= MINUTES_PER_DAY;
+            } else if y < -MINUTES_PER_DAY / 2.0 {
+                y += MINUTES_PER_DAY;
+            }
+            (x, y)
+        })
+        .collect();
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        (cov + (x - mean_x) * (y - mean_y), var + (x - mean_x).powi(2))
+    });
+    if var == 0.0 {
+        return None;
+    }
+
+    Some(cov / var * span_days)
+}
+
+/// The counts a severity score is computed from.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeverityCounts {
+    pub total: usize,
+    pub after_hours: usize,
+    pub weekend: usize,
+    pub night: usize,
+    pub overtime_days: usize,
+    pub commit_days: usize,
+    pub longest_streak: usize,
+}
+
+pub fn severity_score(counts: &SeverityCounts, weights: &SeverityWeights) -> f64 {
+    severity_breakdown(counts, weights).score()
+}
+
+/// Streaks this long or longer get the full streak points.
+const SEVERITY_STREAK_CAP_DAYS: usize = 14;
+
+/// The terms of [`severity_score`], each as the ratio fed in, its weight and
+/// the points it contributes.
+pub fn severity_breakdown(counts: &SeverityCounts, weights: &SeverityWeights) -> SeverityBreakdown {
+    let SeverityCounts {
+        total,
+        after_hours,
+        weekend,
+        night,
+        overtime_days,
+        commit_days,
+        longest_streak,
+    } = *counts;
+    if total == 0 {
+        return SeverityBreakdown::default();
+    }
+
+    let term = |ratio: f64, weight: f64| SeverityTerm {
+        ratio,
+        weight,
+        points: ratio * weight,
+    };
+    let streak = longest_streak.min(SEVERITY_STREAK_CAP_DAYS) as f64;
+    SeverityBreakdown {
+        after_hours: term(percentage(after_hours, total), weights.after_hours),
+        weekend: term(percentage(weekend, total), weights.weekend),
+        night: term(percentage(night, total), weights.night),
+        overtime_days: term(percentage(overtime_days, commit_days), weights.overtime_days),
+        streak: term(streak / SEVERITY_STREAK_CAP_DAYS as f64, weights.streak),
+    }
+}
+
+/// Points each severity term is worth at a ratio of 1.0; they add up to 100.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct SeverityWeights {
+    pub after_hours: f64,
+    pub weekend: f64,
+    pub night: f64,
+    pub overtime_days: f64,
+    pub streak: f64,
+}
+
+impl Default for SeverityWeights {
+    fn default() -> Self {
+        SeverityWeights {
+            after_hours: 40.0,
+            weekend: 20.0,
+            night: 20.0,
+            overtime_days: 10.0,
+            streak: 10.0,
+        }
+    }
+}
+
+impl SeverityWeights {
+    /// Names accepted by `--weights`, with the field each one sets.
+    const KEYS: [&'static str; 5] = ["after", "weekend", "night", "overtime-days", "streak"];
+
+    fn slot(&mut self, key: &str) -> Option<&mut f64> {
+        match key {
+            "after" => Some(&mut self.after_hours),
+            "weekend" => Some(&mut self.weekend),
+            "night" => Some(&mut self.night),
+            "overtime-days" => Some(&mut self.overtime_days),
+            "streak" => Some(&mut self.streak),
+            _ => None,
+        }
+    }
+
+    /// Parses `after=30,weekend=40,...`. Components left out keep their
+    /// default weight; the result is scaled so the weights sum to 100.
+    pub fn parse(raw: &str) -> Result<SeverityWeights, String> {
+        let mut weights = SeverityWeights::default();
+        for entry in raw.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("权重需要写成 名称=数值，当前为：{entry}"))?;
+            let key = key.trim();
+            let slot = weights.slot(key).ok_or_else(|| {
+                format!("未知的权重项：{key}（可用：{}）", Self::KEYS.join(", "))
+            })?;
+            *slot = match value.trim().parse::<f64>() {
+                Ok(value) if value >= 0.0 && value.is_finite() => value,
+                _ => return Err(format!("权重需要是非负数，当前为：{entry}")),
+            };
+        }
+
+        let sum = weights.after_hours
+            + weights.weekend
+            + weights.night
+            + weights.overtime_days
+            + weights.streak;
+        if sum <= 0.0 {
+            return Err("权重之和必须大于 0".to_string());
+        }
+        let scale = 100.0 / sum;
+        for key in Self::KEYS {
+            if let Some(slot) = weights.slot(key) {
+                *slot *= scale;
+            }
+        }
+        Ok(weights)
+    }
+
+    /// In `--weights` syntax.
+    pub fn describe(&self) -> String {
+        format!(
+            "after={:.1},weekend={:.1},night={:.1},overtime-days={:.1},streak={:.1}",
+            self.after_hours, self.weekend, self.night, self.overtime_days, self.streak
+        )
+    }
+}
+
+/// One term of the severity score.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct SeverityTerm {
+    /// 0.0-1.0
+    pub ratio: f64,
+    pub weight: f64,
+    pub points: f64,
+}
+
+/// Where the severity score comes from: after-hours, weekend and night
+/// commits as shares of all commits, overtime days as a share of commit
+/// days, and the longest streak against a 14-day cap.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct SeverityBreakdown {
+    pub after_hours: SeverityTerm,
+    pub weekend: SeverityTerm,
+    pub night: SeverityTerm,
+    pub overtime_days: SeverityTerm,
+    pub streak: SeverityTerm,
+}
+
+impl SeverityBreakdown {
+    pub fn terms(&self) -> [(Msg, &SeverityTerm); 5] {
+        [
+            (Msg::TermAfterHours, &self.after_hours),
+            (Msg::TermWeekend, &self.weekend),
+            (Msg::TermNight, &self.night),
+            (Msg::TermOvertimeDays, &self.overtime_days),
+            (Msg::TermStreak, &self.streak),
+        ]
+    }
+
+    pub fn score(&self) -> f64 {
+        let total: f64 = self.terms().iter().map(|(_, term)| term.points).sum();
+        total.min(100.0)
+    }
+}
+
+/// Lower bound (inclusive) of each severity band, ascending. A band ends
+/// where the next one starts; the last one is open. Labels live in
+/// [`crate::i18n`].
+const SEVERITY_BANDS: [f64; 5] = [0.0, 21.0, 41.0, 61.0, 81.0];
+
+/// The severity band a score falls into.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct SeverityBand {
+    /// 0-4, lowest first.
+    #[serde(skip)]
+    pub index: usize,
+    #[serde(skip)]
+    pub label: &'static str,
+    pub lower: f64,
+    /// `None` for the top band.
+    pub upper: Option<f64>,
+    pub distance_to_upper: Option<f64>,
+}
+
+pub fn severity_band(score: f64) -> SeverityBand {
+    let index = SEVERITY_BANDS
+        .iter()
+        .rposition(|lower| score >= *lower)
+        .unwrap_or(0);
+    let lower = SEVERITY_BANDS[index];
+    let upper = SEVERITY_BANDS.get(index + 1).copied();
+    SeverityBand {
+        index,
+        // Stored reports keep the Chinese label; `--lang` only changes
+        // what is printed.
+        label: Lang::Zh.band_label(index),
+        lower,
+        upper,
+        distance_to_upper: upper.map(|upper| upper - score),
+    }
+}
+
+pub fn severity_label(score: f64) -> &'static str {
+    severity_band(score).label
+}
+
+/// Work-end boundaries swept by `--sensitivity`: 17:00 to 20:00 every 30 minutes.
+const SENSITIVITY_WORK_ENDS: [u32; 7] = [1020, 1050, 1080, 1110, 1140, 1170, 1200];
+
+/// Recomputes the after-hours count, overtime days and severity for each
+/// boundary in [`SENSITIVITY_WORK_ENDS`] (work start as configured), keeping every other input of
+/// `metrics` as is. Each commit is reduced to its second of day once; the
+/// sweep then only counts against the sorted seconds. Overtime days use the
+/// same `overtime_day_min_minutes` filter as the headline figure.
+pub fn boundary_sensitivity<C: Classifier + ?Sized>(
+    commits: &[Commit],
+    metrics: &RepoMetrics,
+    overtime_day_min_minutes: i64,
+    classifier: &C,
+) -> Vec<SensitivityPoint> {
+    let work_start = metrics.work_schedule.start_second();
+    let deduplicated = metrics.severity_overlap == SeverityOverlap::Deduplicated;
+    let mut early = 0usize;
+    let mut seconds: Vec<u32> = Vec::with_capacity(commits.len());
+    // Under deduplicated overlap only commits that are neither night nor
+    // weekend can score as after hours.
+    let mut scored_early = 0usize;
+    let mut scored_seconds: Vec<u32> = Vec::new();
+    // Per day: the first and last second, which is all the overtime filter reads.
+    let mut days: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+    for commit in commits {
+        let second = commit.timestamp.num_seconds_from_midnight();
+        days.entry(commit.timestamp.date_naive())
+            .or_default()
+            .add(second, false);
+        let classification = classifier.classify(commit);
+        let scores_alone =
+            !(classification.night || classification.weekend || classification.holiday);
+        if second < work_start {
+            early += 1;
+            scored_early += usize::from(scores_alone);
+        } else {
+            seconds.push(second);
+            if scores_alone {
+                scored_seconds.push(second);
+            }
+        }
+    }
+    seconds.sort_unstable();
+    scored_seconds.sort_unstable();
+
+    SENSITIVITY_WORK_ENDS
+        .iter()
+        .map(|&end_minute| {
+            let end = end_minute * 60;
+            let late = seconds.len() - seconds.partition_point(|&s| s < end);
+            let after_hours = early + late;
+            let scored_after_hours = if deduplicated {
+                scored_early + scored_seconds.len()
+                    - scored_seconds.partition_point(|&s| s < end)
+            } else {
+                after_hours
+            };
+            let schedule = WorkSchedule {
+                start: metrics.work_schedule.start,
+                end: NaiveTime::from_num_seconds_from_midnight_opt(end, 0)
+                    .unwrap_or(NaiveTime::MIN),
+            };
+            let overtime_days = days
+                .values()
+                .filter(|day| day.is_overtime_under(&schedule, overtime_day_min_minutes))
+                .count();
+            SensitivityPoint {
+                work_end: format!("{:02}:{:02}", end_minute / 60, end_minute % 60),
+                after_hours_commits: after_hours,
+                after_hours_ratio: percentage(after_hours, metrics.total_commits),
+                overtime_days,
+                severity_score: severity_score(
+                    &SeverityCounts {
+                        total: metrics.total_commits,
+                        after_hours: scored_after_hours,
+                        weekend: metrics.severity_buckets.weekend,
+                        night: metrics.severity_buckets.night,
+                        overtime_days,
+                        commit_days: metrics.commit_days,
+                        longest_streak: metrics.longest_streak_days,
+                    },
+                    &metrics.severity_weights,
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Lower bound of the Wilson score interval for `successes` out of
+/// `trials`: the ratio we are ~95% sure the author is at least at. Small
+/// samples are pulled down hard, large ones barely move.
+pub fn wilson_lower_bound(successes: f64, trials: f64) -> f64 {
+    if trials <= 0.0 {
+        return 0.0;
+    }
+    let p = successes / trials;
+    let z2 = COMPOSITE_Z * COMPOSITE_Z;
+    let centre = p + z2 / (2.0 * trials);
+    let spread = COMPOSITE_Z * (p * (1.0 - p) / trials + z2 / (4.0 * trials * trials)).sqrt();
+    ((centre - spread) / (1.0 + z2 / trials)).max(0.0)
+}
+
+/// Leaderboard score for one author: the after-hours ratio, first blended
+/// with [`COMPOSITE_PRIOR_COMMITS`] pseudo-commits at the repo mean and then
+/// taken at its Wilson lower bound, plus small log-scaled bonuses for night
+/// and weekend counts. The bonuses are shrunk by the same prior, so a
+/// handful of night commits cannot lift a tiny sample either.
+///
+/// With a repo mean of 50%, an author with 3 commits all after hours, at
+/// night and on weekends scores about 0.38 while one with 200 commits at 45%
+/// and no night or weekend work scores about 0.39, so volume wins over a
+/// lucky small sample.
+pub fn composite_score(
+    total: usize,
+    after_hours: usize,
+    night: usize,
+    weekend: usize,
+    repo_after_hours_ratio: f64,
+) -> f64 {
+    let trials = total as f64 + COMPOSITE_PRIOR_COMMITS;
+    let successes = after_hours as f64 + COMPOSITE_PRIOR_COMMITS * repo_after_hours_ratio;
+    let reliability = total as f64 / trials;
+    wilson_lower_bound(successes, trials)
+        + reliability
+            * (COMPOSITE_NIGHT_WEIGHT * (night as f64).ln_1p()
+                + COMPOSITE_WEEKEND_WEIGHT * (weekend as f64).ln_1p())
+}
+
 pub fn percentage(part: usize, total: usize) -> f64 {
     if total == 0 {
         0.0
@@ -294,3 +2825,586 @@ pub fn percentage(part: usize, total: usize) -> f64 {
         part as f64 / total as f64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(author: &str, timestamp: &str) -> Commit {
+        Commit {
+            hash: format!("{author}-{timestamp}"),
+            author: author.to_string(),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+            ..Commit::default()
+        }
+    }
+
+    fn options() -> MetricsOptions {
+        MetricsOptions {
+            min_commits: 1,
+            now: DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            ..MetricsOptions::default()
+        }
+    }
+
+    fn metrics_with(commits: &[Commit], options: &MetricsOptions) -> RepoMetrics {
+        compute_metrics(
+            Path::new("."),
+            commits,
+            Vec::new(),
+            Vec::new(),
+            options,
+            &DefaultClassifier::default(),
+        )
+    }
+
+    #[test]
+    fn low_confidence_below_the_working_day_threshold_only() {
+        let mut metrics = metrics_with(&[commit("A", "2026-09-01T20:00:00+08:00")], &options());
+        let label = metrics.severity_label.clone();
+
+        assess_confidence(&mut metrics, LOW_CONFIDENCE_WORKING_DAYS - 1);
+        assert!(metrics.low_confidence);
+        assert_eq!(metrics.expected_working_days, LOW_CONFIDENCE_WORKING_DAYS - 1);
+        assert_eq!(metrics.severity_label, label);
+
+        assess_confidence(&mut metrics, LOW_CONFIDENCE_WORKING_DAYS);
+        assert!(!metrics.low_confidence);
+        assert_eq!(metrics.low_confidence_reason, None);
+        assert_eq!(metrics.severity_label, label);
+    }
+
+    fn date(raw: &str) -> NaiveDate {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn weekend_and_holiday_windows_have_no_working_days() {
+        let weekend = DefaultClassifier::default();
+        assert_eq!(working_days_between(date("2026-09-05"), date("2026-09-06"), &weekend), 0);
+        assert_eq!(working_days_between(date("2026-09-07"), date("2026-09-13"), &weekend), 5);
+
+        // National Day on Thursday and Friday, then the weekend.
+        let mut holidays = DefaultClassifier::default();
+        holidays.holidays.holidays.extend([date("2026-10-01"), date("2026-10-02")]);
+        assert_eq!(working_days_between(date("2026-10-01"), date("2026-10-04"), &holidays), 0);
+        // A makeup Sunday counts as a working day.
+        holidays.holidays.makeup_workdays.insert(date("2026-10-04"));
+        assert_eq!(working_days_between(date("2026-10-01"), date("2026-10-04"), &holidays), 1);
+    }
+
+    #[test]
+    fn a_pure_weekend_window_is_low_confidence_with_a_reason() {
+        let commits = [commit("A", "2026-09-05T11:00:00+08:00"), commit("A", "2026-09-06T22:00:00+08:00")];
+        let classifier = DefaultClassifier::default();
+        let mut metrics = compute_metrics(Path::new("."), &commits, Vec::new(), Vec::new(), &options(), &classifier);
+        assess_confidence(&mut metrics, working_days_between(date("2026-09-05"), date("2026-09-06"), &classifier));
+
+        assert!(metrics.low_confidence);
+        assert_eq!(metrics.expected_working_days, 0);
+        assert_eq!(
+            metrics.low_confidence_reason.as_deref(),
+            Some("分析窗口内只有 0 个工作日，下班后/周末占比不具参考意义")
+        );
+        let band = crate::report::band_text(&metrics, Lang::Zh);
+        assert!(band.ends_with("（样本不足）"), "{band}");
+        assert!(crate::report::band_text(&metrics, Lang::En).ends_with(" (small sample)"));
+    }
+
+    #[test]
+    fn co_author_credits_stay_out_of_attribution_and_author_count() {
+        let mut commits = vec![
+            commit("A", "2026-09-01T21:00:00+08:00"),
+            commit("A", "2026-09-02T22:00:00+08:00"),
+            commit("B", "2026-09-03T23:30:00+08:00"),
+        ];
+        for credit in [commit("C", "2026-09-01T21:00:00+08:00"), commit("B", "2026-09-02T22:00:00+08:00")] {
+            commits.push(Commit { co_author: true, ..credit });
+        }
+
+        let metrics = metrics_with(&commits, &options());
+        assert_eq!(metrics.total_commits, 3);
+        assert_eq!(metrics.unique_authors, 2);
+        let sum = |share: fn(&AttributionEntry) -> f64| {
+            metrics.attribution.iter().map(share).sum::<f64>()
+        };
+        assert!(sum(|e| e.after_hours_share) <= 1.0 + 1e-9);
+        assert!(sum(|e| e.night_share) <= 1.0 + 1e-9);
+        assert!(metrics.attribution.iter().all(|e| e.author != "C"));
+    }
+
+    /// `--json` output of the first release, before any of the optional
+    /// fields existed; `diff` must still read reports like it.
+    const BASELINE_REPORT: &str = r#"{
+  "repo_path": "/tmp/tr",
+  "analysis_start": "2026-08-01T10:00:00+08:00",
+  "analysis_end": "2026-09-10T22:00:00+08:00",
+  "total_commits": 55,
+  "unique_authors": 3,
+  "after_hours_commits": 24,
+  "weekend_commits": 15,
+  "night_commits": 11,
+  "commit_days": 41,
+  "overtime_days": 21,
+  "longest_streak_days": 41,
+  "busiest_day": {
+    "date": "2026-08-13",
+    "total_commits": 3,
+    "after_hours_commits": 2
+  },
+  "severity_score": 42.031042128603104,
+  "severity_label": "持续加班",
+  "top_after_hours_authors": [
+    {
+      "name": "Zhang San",
+      "total_commits": 11,
+      "after_hours_commits": 11,
+      "weekend_commits": 3,
+      "night_commits": 11,
+      "after_hours_ratio": 1.0
+    }
+  ],
+  "chill_authors": [
+    {
+      "name": "A",
+      "total_commits": 41,
+      "after_hours_commits": 10,
+      "weekend_commits": 12,
+      "night_commits": 0,
+      "after_hours_ratio": 0.24390243902439024
+    }
+  ],
+  "ignored_authors": [
+    "BitsAdmin"
+  ],
+  "alias_rules": []
+}"#;
+
+    #[test]
+    fn baseline_report_still_deserializes() {
+        let metrics: RepoMetrics = serde_json::from_str(BASELINE_REPORT).unwrap();
+        assert_eq!(metrics.total_commits, 55);
+        assert_eq!(metrics.severity_label, "持续加班");
+        assert_eq!(metrics.top_after_hours_authors[0].name, "Zhang San");
+        assert_eq!(metrics.busiest_day.unwrap().after_hours_minutes, 0);
+        assert!(metrics.window_description.is_empty());
+        assert!(metrics.attribution.is_empty());
+        assert!(!metrics.low_confidence);
+        assert_eq!(metrics.punch_card, [[0; 24]; 7]);
+    }
+
+    #[test]
+    fn composite_ranks_volume_above_a_tiny_perfect_sample() {
+        let tiny = composite_score(3, 3, 3, 3, 0.5);
+        let steady = composite_score(200, 90, 0, 0, 0.5);
+        assert!(steady > tiny, "{steady} <= {tiny}");
+
+        // Same ratio, more evidence: the larger sample ranks higher.
+        assert!(composite_score(200, 150, 20, 20, 0.3) > composite_score(8, 6, 1, 1, 0.3));
+
+        let mut commits = Vec::new();
+        for day in 1..=3 {
+            commits.push(commit("Tiny", &format!("2026-09-{day:02}T23:30:00+08:00")));
+        }
+        for i in 0..200 {
+            let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap() + Duration::days(i / 2);
+            let hour = if i % 20 < 9 { 20 } else { 11 };
+            commits.push(commit("Steady", &format!("{date}T{hour}:00:00+08:00")));
+        }
+        let metrics = metrics_with(
+            &commits,
+            &MetricsOptions {
+                rank_by: RankBy::Composite,
+                ..options()
+            },
+        );
+        let ranking: Vec<&str> = metrics
+            .top_after_hours_authors
+            .iter()
+            .map(|author| author.name.as_str())
+            .collect();
+        assert_eq!(ranking, ["Steady", "Tiny"]);
+    }
+
+    #[test]
+    fn sensitivity_row_at_the_configured_end_matches_the_headline() {
+        let commits: Vec<Commit> = [
+            // 10 minutes past work end: below the 30-minute threshold.
+            "2026-09-01T09:30:00+08:00",
+            "2026-09-01T18:10:00+08:00",
+            // An hour past work end.
+            "2026-09-02T17:00:00+08:00",
+            "2026-09-02T19:00:00+08:00",
+            // A lone late commit spans no time.
+            "2026-09-03T21:00:00+08:00",
+            "2026-09-04T10:00:00+08:00",
+        ]
+        .iter()
+        .map(|timestamp| commit("A", timestamp))
+        .collect();
+
+        for min_minutes in [0, 30] {
+            let options = MetricsOptions {
+                overtime_day_min_minutes: min_minutes,
+                ..options()
+            };
+            let metrics = metrics_with(&commits, &options);
+            let sweep =
+                boundary_sensitivity(&commits, &metrics, min_minutes, &DefaultClassifier::default());
+            let work_end = metrics.work_schedule.end.format("%H:%M").to_string();
+            let configured = sweep.iter().find(|point| point.work_end == work_end).unwrap();
+            assert_eq!(configured.overtime_days, metrics.overtime_days, "min {min_minutes}");
+            assert_eq!(configured.after_hours_commits, metrics.after_hours_commits);
+            assert!((configured.severity_score - metrics.severity_score).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn timezone_views_read_each_commit_on_both_clocks() {
+        let beijing = FixedOffset::east_opt(8 * 3600).unwrap();
+        // Already re-expressed in UTC, as `--timezone UTC` leaves them.
+        let commits: Vec<Commit> = [
+            // 21:00 in Beijing, 13:00 UTC.
+            "2026-09-01T13:00:00+00:00",
+            "2026-09-02T13:00:00+00:00",
+            // 11:00 in Beijing, 03:00 UTC.
+            "2026-09-03T03:00:00+00:00",
+        ]
+        .iter()
+        .map(|timestamp| Commit {
+            recorded_offset: Some(beijing),
+            ..commit("A", timestamp)
+        })
+        .collect();
+
+        let views = metrics_with(&commits, &options()).timezone_views.unwrap();
+        assert_eq!(views.local_hours[21], 2);
+        assert_eq!(views.local_hours[11], 1);
+        assert_eq!(views.reference_hours[13], 2);
+        assert_eq!(views.reference_hours[3], 1);
+        assert!((views.local_after_hours_ratio - 2.0 / 3.0).abs() < 1e-9);
+        assert!((views.reference_after_hours_ratio - 1.0 / 3.0).abs() < 1e-9);
+
+        let unconverted: Vec<Commit> = commits
+            .iter()
+            .map(|commit| Commit {
+                recorded_offset: None,
+                ..commit.clone()
+            })
+            .collect();
+        assert!(metrics_with(&unconverted, &options()).timezone_views.is_none());
+    }
+
+    /// `count` commits two days apart from 2026-06-01, the first at
+    /// `start_minute` past midnight and each `step` minutes later than the
+    /// last, wrapping past midnight.
+    fn drifting(count: usize, start_minute: i64, step: i64) -> Vec<DateTime<FixedOffset>> {
+        let first = DateTime::parse_from_rfc3339("2026-06-01T00:00:00+08:00").unwrap();
+        (0..count as i64)
+            .map(|i| {
+                let minute = (start_minute + i * step).rem_euclid(24 * 60);
+                first + Duration::days(2 * i) + Duration::minutes(minute)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commit_time_drift_fits_a_later_trend_and_ignores_a_flat_one() {
+        // 24 commits over 46 days, 8 minutes later each time: about 184 minutes.
+        let later = commit_time_drift(&drifting(24, 20 * 60, 8)).unwrap();
+        assert!((later - 184.0).abs() < 3.0, "{later}");
+        let earlier = commit_time_drift(&drifting(24, 20 * 60, -8)).unwrap();
+        assert!((earlier + 184.0).abs() < 3.0, "{earlier}");
+
+        let flat = commit_time_drift(&drifting(24, 21 * 60, 0));
+        assert!(flat.is_none_or(|drift| drift.abs() < 1e-6), "{flat:?}");
+        // Alternating 23:50 and 00:10 is a steady habit, not a 23-hour swing.
+        let straddling: Vec<_> = drifting(24, 23 * 60 + 50, 0)
+            .into_iter()
+            .enumerate()
+            .map(|(i, ts)| if i % 2 == 0 { ts } else { ts + Duration::minutes(20) })
+            .collect();
+        let wrapped = commit_time_drift(&straddling).unwrap();
+        assert!(wrapped.abs() < 15.0, "{wrapped}");
+        // Crossing midnight while drifting keeps the slope.
+        let across = commit_time_drift(&drifting(24, 22 * 60, 8)).unwrap();
+        assert!((across - 184.0).abs() < 3.0, "{across}");
+    }
+
+    #[test]
+    fn commit_time_drift_needs_enough_commits_over_enough_weeks() {
+        assert!(commit_time_drift(&drifting(19, 20 * 60, 8)).is_none());
+        // 24 commits within 23 days.
+        let packed: Vec<_> = drifting(24, 20 * 60, 8)
+            .into_iter()
+            .enumerate()
+            .map(|(i, ts)| ts - Duration::days(i as i64))
+            .collect();
+        assert!(commit_time_drift(&packed).is_none());
+    }
+
+    #[test]
+    fn only_authors_drifting_past_the_threshold_are_warned_about() {
+        let as_commits = |author: &str, timestamps: Vec<DateTime<FixedOffset>>| {
+            timestamps
+                .into_iter()
+                .map(|ts| commit(author, &ts.to_rfc3339()))
+                .collect::<Vec<_>>()
+        };
+        let commits = [
+            as_commits("Late", drifting(24, 18 * 60, 8)),
+            as_commits("Steady", drifting(24, 10 * 60, 2)),
+        ]
+        .concat();
+        let metrics = metrics_with(&commits, &options());
+        let drift = |name: &str| {
+            metrics
+                .authors
+                .iter()
+                .find(|author| author.name == name)
+                .and_then(|author| author.commit_time_drift_minutes)
+                .unwrap()
+        };
+        assert!(drift("Late") > DRIFT_WARNING_MINUTES);
+        assert!(drift("Steady") < DRIFT_WARNING_MINUTES);
+        let warned: Vec<_> = metrics.drift_warnings.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(warned, ["Late"]);
+    }
+
+    #[test]
+    fn directories_are_attributed_by_majority_with_ties_mixed() {
+        let paths = |raw: &[&str]| raw.iter().map(|path| path.to_string()).collect::<Vec<_>>();
+        let cases: [(&[&str], u32, &str); 7] = [
+            (&["backend/api/a.rs", "backend/db/b.rs", "mobile/c.kt"], 1, "backend"),
+            (&["backend/api/a.rs", "backend/db/b.rs", "mobile/c.kt"], 2, MIXED_DIRECTORY),
+            (&["backend/api/a.rs", "backend/api/b.rs", "backend/db/c.rs"], 2, "backend/api"),
+            (&["backend/a.rs", "mobile/b.kt"], 1, MIXED_DIRECTORY),
+            (&["README.md", "Cargo.toml", "src/main.rs"], 1, ROOT_DIRECTORY),
+            // Shallower than the depth: the whole directory path.
+            (&["docs/guide.md"], 3, "docs"),
+            (&[], 1, MIXED_DIRECTORY),
+        ];
+        for (raw, depth, expected) in cases {
+            assert_eq!(attribute_directory(&paths(raw), depth), expected, "{raw:?} at {depth}");
+        }
+    }
+
+    #[test]
+    fn shuffled_input_serializes_byte_for_byte_the_same() {
+        let mut commits = Vec::new();
+        // Authors with identical counts and ratios, so every ranking has ties.
+        for (author, component, directory) in [("Wang", "app", "backend"), ("Li", "lib", "mobile"), ("Zhao", "app", "backend")] {
+            for (day, hour) in [(1, 10), (1, 21), (2, 23), (5, 14), (6, 2), (8, 19)] {
+                let mut commit = commit(author, &format!("2026-09-{day:02}T{hour:02}:30:00+08:00"));
+                commit.component = Some(component.to_string());
+                commit.directory = Some(directory.to_string());
+                commits.push(commit);
+            }
+        }
+        // Same-instant commits by different people, and a co-author credit.
+        commits.push(commit("Zhou", "2026-09-01T21:30:00+08:00"));
+        commits.push(Commit { co_author: true, ..commit("Sun", "2026-09-01T21:30:00+08:00") });
+        // Straddling midnight, for the night window and streaks.
+        for day in 10..=30 {
+            commits.push(commit("Zhou", &format!("2026-09-{day:02}T23:50:00+08:00")));
+        }
+
+        let options = MetricsOptions { top: 3, ..options() };
+        let json = |commits: &[Commit]| serde_json::to_string(&metrics_with(commits, &options)).unwrap();
+        let expected = json(&commits);
+
+        let mut reversed = commits.clone();
+        reversed.reverse();
+        assert_eq!(json(&reversed), expected);
+        for seed in 1..=5u64 {
+            let mut shuffled = commits.clone();
+            shuffled.sort_by_key(|commit| {
+                let mut hash = seed;
+                for byte in commit.hash.bytes() {
+                    hash = hash.wrapping_mul(6364136223846793005).wrapping_add(byte as u64);
+                }
+                hash
+            });
+            assert_eq!(json(&shuffled), expected, "seed {seed}");
+        }
+    }
+
+    /// A commit by `raw` that an alias rule renamed to `name`.
+    fn aliased(name: &str, raw: &str, timestamp: &str) -> Commit {
+        Commit {
+            raw_author: Some(raw.to_string()),
+            ..commit(name, timestamp)
+        }
+    }
+
+    #[test]
+    fn over_merged_identities_are_flagged_with_their_sources() {
+        let mut commits = Vec::new();
+        // Two people folded into "Team": 4 commits a day on two days, over a
+        // ceiling of 3.
+        for day in [1, 2] {
+            for (raw, hour) in [("Alice", 10), ("Bob", 11), ("Alice", 14), ("Bob", 15)] {
+                commits.push(aliased("Team", raw, &format!("2026-09-{day:02}T{hour}:00:00+08:00")));
+            }
+        }
+        // Five commits in one minute from both names.
+        for (raw, second) in [("Alice", 1), ("Bob", 12), ("Alice", 23), ("Bob", 34), ("Bob", 45)] {
+            commits.push(aliased("Team", raw, &format!("2026-09-05T16:20:{second:02}+08:00")));
+        }
+
+        let suspects = find_suspect_identities(&commits, 3);
+        assert_eq!(suspects.len(), 1);
+        assert_eq!(suspects[0].name, "Team");
+        assert_eq!(suspects[0].merged_from, ["Alice", "Bob"]);
+        assert!(matches!(
+            suspects[0].reasons[..],
+            [
+                SuspectReason::HeavyDays { days: 3, limit: 3 },
+                SuspectReason::SameMinuteBursts { minutes: 1, min_commits: SUSPECT_SAME_MINUTE_COMMITS },
+            ]
+        ), "{:?}", suspects[0].reasons);
+    }
+
+    #[test]
+    fn one_raw_name_or_one_heavy_day_is_not_suspect() {
+        // However busy, a single raw name cannot be over-merged.
+        let solo: Vec<_> = (0..20)
+            .flat_map(|minute| {
+                ["01", "02"].map(|day| {
+                    aliased("Solo", "solo-laptop", &format!("2026-09-{day}T10:{:02}:00+08:00", minute / 5))
+                })
+            })
+            .collect();
+        assert!(find_suspect_identities(&solo, 3).is_empty());
+
+        // Two sources but only one heavy day and no dense minute.
+        let once: Vec<_> = ["Alice", "Bob", "Alice", "Bob"]
+            .iter()
+            .enumerate()
+            .map(|(hour, raw)| aliased("Team", raw, &format!("2026-09-01T1{hour}:00:00+08:00")))
+            .collect();
+        assert!(find_suspect_identities(&once, 3).is_empty());
+    }
+
+    #[test]
+    fn buckets_count_each_commit_once_by_priority_when_deduplicated() {
+        // (after hours, weekend, night) -> deduplicated (after hours, weekend, night)
+        let cases = [
+            ((true, true, true), (0, 0, 1)),
+            ((true, true, false), (0, 1, 0)),
+            ((true, false, true), (0, 0, 1)),
+            ((false, true, false), (0, 1, 0)),
+            ((true, false, false), (1, 0, 0)),
+            ((false, false, false), (0, 0, 0)),
+        ];
+        for ((after_hours, weekend, night), expected) in cases {
+            let mut independent = SeverityBuckets::default();
+            independent.add(SeverityOverlap::Independent, after_hours, weekend, night);
+            assert_eq!(
+                (independent.after_hours, independent.weekend, independent.night),
+                (usize::from(after_hours), usize::from(weekend), usize::from(night))
+            );
+            let mut deduplicated = SeverityBuckets::default();
+            deduplicated.add(SeverityOverlap::Deduplicated, after_hours, weekend, night);
+            assert_eq!((deduplicated.after_hours, deduplicated.weekend, deduplicated.night), expected);
+        }
+    }
+
+    #[test]
+    fn deduplicated_overlap_scores_saturday_nights_once() {
+        // A dozen Saturday commits at 23:30: night, weekend and after hours at once.
+        let commits: Vec<_> = (0..12)
+            .map(|week| {
+                let saturday = NaiveDate::from_ymd_opt(2026, 6, 6).unwrap() + Duration::weeks(week);
+                commit("A", &format!("{saturday}T23:30:00+08:00"))
+            })
+            .collect();
+        let independent = metrics_with(&commits, &options());
+        let deduplicated = metrics_with(
+            &commits,
+            &MetricsOptions { severity_overlap: SeverityOverlap::Deduplicated, ..options() },
+        );
+
+        assert!(
+            deduplicated.severity_score < independent.severity_score,
+            "{} vs {}",
+            deduplicated.severity_score,
+            independent.severity_score
+        );
+        let buckets = |m: &RepoMetrics| {
+            (m.severity_buckets.after_hours, m.severity_buckets.weekend, m.severity_buckets.night)
+        };
+        assert_eq!(buckets(&independent), (12, 12, 12));
+        assert_eq!(buckets(&deduplicated), (0, 0, 12));
+        // The raw counts are reported either way.
+        for metrics in [&independent, &deduplicated] {
+            assert_eq!(
+                (metrics.after_hours_commits, metrics.weekend_commits, metrics.night_commits),
+                (12, 12, 12)
+            );
+        }
+        assert_eq!(deduplicated.severity_overlap, SeverityOverlap::Deduplicated);
+    }
+
+    fn at(timestamp: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap()
+    }
+
+    /// Rest-day overtime of sessions split by a 60-minute gap, padded 30
+    /// minutes: every session minute counts.
+    fn rest_day_sessions(timestamps: &[DateTime<FixedOffset>]) -> i64 {
+        session_overtime_minutes(timestamps, 60, 30, &WorkSchedule::default(), &DefaultClassifier::default())
+    }
+
+    #[test]
+    fn a_gap_of_exactly_the_threshold_splits_sessions() {
+        let first = at("2026-09-05T14:00:00+08:00");
+        assert_eq!(commit_gap(first, first + Duration::minutes(60)), Duration::minutes(60));
+        // Two sessions of 30 padded minutes each.
+        assert_eq!(rest_day_sessions(&[first, first + Duration::minutes(60)]), 60);
+        // One session: 30 padded minutes plus 59m59s.
+        assert_eq!(rest_day_sessions(&[first, first + Duration::seconds(3599)]), 89);
+    }
+
+    #[test]
+    fn sessions_across_an_offset_jump_use_elapsed_time() {
+        // 01:30 CET to 03:20 CEST on 2026-03-29 (a Sunday) is 50 minutes,
+        // though the wall clocks are 110 minutes apart.
+        let before = at("2026-03-29T01:30:00+01:00");
+        let after = at("2026-03-29T03:20:00+02:00");
+        assert_eq!(commit_gap(before, after), Duration::minutes(50));
+        assert_eq!(rest_day_sessions(&[before, after]), 80);
+    }
+
+    /// Five commits a minute apart from `start`, enough for an all-nighter.
+    fn burst(start: DateTime<FixedOffset>) -> Vec<DateTime<FixedOffset>> {
+        (0..5).map(|i| start + Duration::minutes(i)).collect()
+    }
+
+    fn all_nighter_sizes(night: Vec<DateTime<FixedOffset>>) -> Vec<usize> {
+        all_nighters("A", night).iter().map(|event| event.commits).collect()
+    }
+
+    #[test]
+    fn a_gap_of_exactly_the_threshold_splits_all_nighters() {
+        let first = burst(at("2026-09-01T23:00:00+08:00"));
+        let last = *first.last().unwrap();
+        let gap = Duration::minutes(ALL_NIGHTER_GAP_MINUTES);
+
+        let split = [first.clone(), burst(last + gap)].concat();
+        assert_eq!(all_nighter_sizes(split), [5, 5]);
+        let joined = [first, burst(last + gap - Duration::seconds(1))].concat();
+        assert_eq!(all_nighter_sizes(joined), [10]);
+    }
+
+    #[test]
+    fn all_nighters_across_an_offset_jump_use_elapsed_time() {
+        // 01:04 CET to 03:10 CEST is 66 minutes apart, under the gap, while
+        // the wall clocks are 126 minutes apart.
+        let first = burst(at("2026-03-29T01:00:00+01:00"));
+        let second = burst(at("2026-03-29T03:10:00+02:00"));
+        assert_eq!(all_nighter_sizes([first, second].concat()), [10]);
+    }
+}
@@ -0,0 +1,188 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+use crate::cli::Cli;
+use crate::metrics::{AggregateMetrics, RepoMetrics};
+use crate::report::{
+    write_failures, write_human_report, write_json, write_report, write_repo_table,
+};
+
+/// Layout of the main report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// The terminal summary.
+    #[default]
+    Human,
+    Json,
+    /// One row per author, as `--csv-authors` writes.
+    Csv,
+    /// GitHub-flavored Markdown, for wikis and PR comments.
+    Markdown,
+    /// A self-contained HTML page with charts.
+    Html,
+    /// A shields.io endpoint badge showing the severity score.
+    Badge,
+}
+
+/// Whether the human report uses ANSI colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Always,
+    /// Only when writing to a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self, to_file: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                !to_file && io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+            }
+        }
+    }
+}
+
+/// One rendering of the computed metrics (human summary, JSON, ...).
+pub trait OutputSink {
+    fn render(&self, metrics: &RepoMetrics, out: &mut dyn Write) -> Result<()>;
+}
+
+impl<T: OutputSink + ?Sized> OutputSink for Box<T> {
+    fn render(&self, metrics: &RepoMetrics, out: &mut dyn Write) -> Result<()> {
+        (**self).render(metrics, out)
+    }
+}
+
+/// The report in one of the `--format` layouts.
+pub struct ReportSink<'a> {
+    pub format: Format,
+    pub cli: &'a Cli,
+}
+
+impl OutputSink for ReportSink<'_> {
+    fn render(&self, metrics: &RepoMetrics, out: &mut dyn Write) -> Result<()> {
+        write_report(self.format, metrics, self.cli, out)
+    }
+}
+
+pub struct JsonSink;
+
+impl OutputSink for JsonSink {
+    fn render(&self, metrics: &RepoMetrics, out: &mut dyn Write) -> Result<()> {
+        write_json(metrics, out)
+    }
+}
+
+pub enum Destination {
+    Stdout,
+    File(PathBuf),
+}
+
+pub struct Output<'a> {
+    pub sink: Box<dyn OutputSink + 'a>,
+    pub destination: Destination,
+}
+
+impl<'a> Output<'a> {
+    pub fn stdout(sink: impl OutputSink + 'a) -> Self {
+        Output {
+            sink: Box::new(sink),
+            destination: Destination::Stdout,
+        }
+    }
+
+    pub fn file(sink: impl OutputSink + 'a, path: PathBuf) -> Self {
+        Output {
+            sink: Box::new(sink),
+            destination: Destination::File(path),
+        }
+    }
+}
+
+/// Builds the list of outputs requested on the command line. Exactly one
+/// sink goes to stdout, or to `--output` when given; file sinks are added on
+/// top of it.
+pub fn outputs_for(cli: &Cli) -> Vec<Output<'_>> {
+    let mut outputs = Vec::new();
+    let main = ReportSink {
+        format: cli.report_format(),
+        cli,
+    };
+    outputs.push(match &cli.output {
+        Some(path) => Output::file(main, path.clone()),
+        None => Output::stdout(main),
+    });
+    if let Some(path) = &cli.json_file {
+        outputs.push(Output::file(JsonSink, path.clone()));
+    }
+    outputs
+}
+
+/// The multi-repo counterpart of [`emit_all`]: the human report gets the
+/// per-repo table, JSON the `repos`/`aggregate` object, and the other
+/// formats render the combined metrics.
+pub fn emit_aggregate(cli: &Cli, report: &AggregateMetrics<'_>) -> Result<()> {
+    let render = |out: &mut dyn Write| -> Result<()> {
+        match cli.report_format() {
+            Format::Human => {
+                write_repo_table(report, cli.lang, out)?;
+                write_human_report(report.aggregate, cli, out)?;
+                write_failures(report.failures, cli.lang, out)?;
+            }
+            Format::Json => write_json(report, out)?,
+            format => write_report(format, report.aggregate, cli, out)?,
+        }
+        Ok(())
+    };
+    match &cli.output {
+        Some(path) => write_to_file(path, render)?,
+        None => {
+            let stdout = io::stdout();
+            let mut lock = stdout.lock();
+            render(&mut lock)?;
+            lock.flush()?;
+        }
+    }
+    if let Some(path) = &cli.json_file {
+        write_to_file(path, |writer| write_json(report, writer))?;
+    }
+    Ok(())
+}
+
+pub fn emit_all(outputs: &[Output<'_>], metrics: &RepoMetrics) -> Result<()> {
+    for output in outputs {
+        match &output.destination {
+            Destination::Stdout => {
+                let stdout = io::stdout();
+                let mut lock = stdout.lock();
+                output.sink.render(metrics, &mut lock)?;
+                lock.flush()?;
+            }
+            Destination::File(path) => {
+                write_to_file(path, |writer| output.sink.render(metrics, writer))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn write_to_file(
+    path: &Path,
+    render: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("无法创建输出文件：{}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    render(&mut writer)
+        .and_then(|_| Ok(writer.flush()?))
+        .with_context(|| format!("写入输出文件失败：{}", path.display()))
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{percentage, RepoMetrics};
+
+const VALID_METRICS: &[&str] = &["after_hours", "weekend", "night", "overtime_days", "severity"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = "<=")]
+    AtMost,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = ">")]
+    Above,
+}
+
+impl Comparison {
+    fn holds(self, actual: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::AtMost => actual <= threshold,
+            Comparison::Below => actual < threshold,
+            Comparison::AtLeast => actual >= threshold,
+            Comparison::Above => actual > threshold,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Comparison::AtMost => "<=",
+            Comparison::Below => "<",
+            Comparison::AtLeast => ">=",
+            Comparison::Above => ">",
+        };
+        f.write_str(op)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub metric: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyResult {
+    pub metric: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub actual: f64,
+    pub passed: bool,
+}
+
+/// Parses `after_hours<=0.10,weekend<=0.05` into rules. Ratios are fractions
+/// (0.10 = 10%); `severity` is compared against the 0-100 score.
+pub fn parse_policy(raw: &str) -> Result<Vec<PolicyRule>> {
+    let mut rules = Vec::new();
+    for entry in raw.split(',') {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        // Two-character operators first so `<=` is not read as `<`.
+        let (metric, comparison, threshold) = ["<=", ">=", "<", ">"]
+            .iter()
+            .find_map(|op| {
+                entry.split_once(op).map(|(metric, threshold)| {
+                    let comparison = match *op {
+                        "<=" => Comparison::AtMost,
+                        ">=" => Comparison::AtLeast,
+                        "<" => Comparison::Below,
+                        _ => Comparison::Above,
+                    };
+                    (metric.trim(), comparison, threshold.trim())
+                })
+            })
+            .ok_or_else(|| {
+                anyhow!("策略规则格式应为 指标<=阈值（支持 <= < >= >），当前为：{entry}")
+            })?;
+
+        if !VALID_METRICS.contains(&metric) {
+            bail!(
+                "未知的策略指标 {metric:?}，可用指标：{}",
+                VALID_METRICS.join(", ")
+            );
+        }
+        let threshold: f64 = threshold
+            .parse()
+            .map_err(|_| anyhow!("策略阈值不是数字：{entry}"))?;
+        if !threshold.is_finite() || threshold < 0.0 {
+            bail!("策略阈值必须是非负数：{entry}");
+        }
+
+        rules.push(PolicyRule {
+            metric: metric.to_string(),
+            comparison,
+            threshold,
+        });
+    }
+
+    if rules.is_empty() {
+        bail!("--policy 至少需要一条规则");
+    }
+    Ok(rules)
+}
+
+pub fn evaluate_policy(rules: &[PolicyRule], metrics: &RepoMetrics) -> Vec<PolicyResult> {
+    rules
+        .iter()
+        .map(|rule| {
+            let actual = metric_value(&rule.metric, metrics);
+            PolicyResult {
+                metric: rule.metric.clone(),
+                comparison: rule.comparison,
+                threshold: rule.threshold,
+                actual,
+                passed: rule.comparison.holds(actual, rule.threshold),
+            }
+        })
+        .collect()
+}
+
+fn metric_value(metric: &str, metrics: &RepoMetrics) -> f64 {
+    match metric {
+        "after_hours" => percentage(metrics.after_hours_commits, metrics.total_commits),
+        "weekend" => percentage(metrics.weekend_commits, metrics.total_commits),
+        "night" => percentage(metrics.night_commits, metrics.total_commits),
+        "overtime_days" => percentage(metrics.overtime_days, metrics.commit_days),
+        "severity" => metrics.severity_score,
+        _ => unreachable!("policy metrics are validated while parsing"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::metrics::{compute_metrics, DefaultClassifier, MetricsOptions};
+
+    /// 100 commits over 20 days: 12 after hours, 5 weekend, 1 night, 4
+    /// overtime days, severity 42.
+    fn metrics() -> RepoMetrics {
+        let mut metrics = compute_metrics(
+            Path::new("."),
+            &[],
+            Vec::new(),
+            Vec::new(),
+            &MetricsOptions::default(),
+            &DefaultClassifier::default(),
+        );
+        metrics.total_commits = 100;
+        metrics.after_hours_commits = 12;
+        metrics.weekend_commits = 5;
+        metrics.night_commits = 1;
+        metrics.commit_days = 20;
+        metrics.overtime_days = 4;
+        metrics.severity_score = 42.0;
+        metrics
+    }
+
+    #[test]
+    fn rules_evaluate_against_the_ratios() {
+        let cases = [
+            ("after_hours<=0.10", 0.12, false),
+            ("after_hours<=0.12", 0.12, true),
+            ("after_hours<0.12", 0.12, false),
+            ("weekend<=0.05", 0.05, true),
+            ("night<0.02", 0.01, true),
+            ("night>0.01", 0.01, false),
+            ("overtime_days>=0.2", 0.2, true),
+            ("severity<=40", 42.0, false),
+            ("severity > 40", 42.0, true),
+        ];
+        let metrics = metrics();
+        for (raw, actual, passed) in cases {
+            let results = evaluate_policy(&parse_policy(raw).unwrap(), &metrics);
+            assert_eq!(results.len(), 1, "{raw}");
+            assert!((results[0].actual - actual).abs() < 1e-9, "{raw}");
+            assert_eq!(results[0].passed, passed, "{raw}");
+        }
+    }
+
+    #[test]
+    fn parses_every_operator_and_skips_empty_entries() {
+        let rules = parse_policy(" after_hours<=0.10, weekend<0.05,,night>=0,severity>60 ").unwrap();
+        let parsed: Vec<_> = rules
+            .iter()
+            .map(|rule| (rule.metric.as_str(), rule.comparison, rule.threshold))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("after_hours", Comparison::AtMost, 0.10),
+                ("weekend", Comparison::Below, 0.05),
+                ("night", Comparison::AtLeast, 0.0),
+                ("severity", Comparison::Above, 60.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_rules_are_rejected() {
+        let unknown = parse_policy("lunch<=0.1").unwrap_err().to_string();
+        assert!(unknown.contains("lunch"), "{unknown}");
+        for metric in VALID_METRICS {
+            assert!(unknown.contains(metric), "{unknown}");
+        }
+        for raw in ["after_hours=0.1", "after_hours<=many", "after_hours<=-0.1", "after_hours<=inf", " , "] {
+            assert!(parse_policy(raw).is_err(), "{raw}");
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use crate::cli::Cli;
+
+/// `CommitsRead` is sent every this many raw commit records.
+pub const COMMITS_READ_STEP: usize = 500;
+
+/// Milestones of one analysis run, in the order they happen. Fetch events
+/// repeat for every repository read (the repo itself, then submodules).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent<'a> {
+    FetchStarted { repo: &'a Path },
+    /// Raw commit records parsed so far from `repo`; restarts from zero when
+    /// a transient `git log` failure is retried.
+    CommitsRead { repo: &'a Path, read: usize },
+    FetchFinished {
+        repo: &'a Path,
+        read: usize,
+        stopped_early: bool,
+    },
+    /// Commits left after ignore, alias and exclude rules.
+    MetricsStarted { commits: usize },
+    MetricsFinished,
+}
+
+/// Receives [`ProgressEvent`]s on the analyzing thread, so implementations
+/// should return quickly. A sink that panics is reported once on stderr and
+/// gets no further events; the analysis itself carries on.
+pub trait ProgressSink {
+    fn on_event(&self, event: ProgressEvent<'_>);
+}
+
+/// The `--verbose` diagnostics.
+pub struct StderrProgress;
+
+impl ProgressSink for StderrProgress {
+    fn on_event(&self, event: ProgressEvent<'_>) {
+        match event {
+            ProgressEvent::FetchFinished {
+                repo,
+                read,
+                stopped_early,
+            } => eprintln!(
+                "{}：已读取 {read} 条提交记录{}",
+                repo.display(),
+                if stopped_early { "（已覆盖窗口，提前停止读取）" } else { "" }
+            ),
+            ProgressEvent::MetricsStarted { commits } => {
+                eprintln!("纳入分析 {commits} 条提交");
+            }
+            _ => {}
+        }
+    }
+}
+
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn on_event(&self, _event: ProgressEvent<'_>) {}
+}
+
+/// The sink the command line asks for.
+pub fn cli_sink(cli: &Cli) -> &'static dyn ProgressSink {
+    if cli.verbose { &StderrProgress } else { &NoProgress }
+}
+
+/// Forwards events to a sink, containing its panics.
+pub struct Progress<'a> {
+    sink: &'a dyn ProgressSink,
+    broken: Cell<bool>,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(sink: &'a dyn ProgressSink) -> Self {
+        Progress {
+            sink,
+            broken: Cell::new(false),
+        }
+    }
+
+    pub fn emit(&self, event: ProgressEvent<'_>) {
+        if self.broken.get() {
+            return;
+        }
+        if panic::catch_unwind(AssertUnwindSafe(|| self.sink.on_event(event))).is_err() {
+            self.broken.set(true);
+            eprintln!("警告：进度回调发生 panic，后续进度事件不再发送");
+        }
+    }
+}
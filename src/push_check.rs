@@ -6,6 +6,9 @@ use chrono::{DateTime, Duration, Utc};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
+use crate::error::{CodedError, ErrorCode};
+use crate::i18n::{Lang, Msg};
+
 const DEFAULT_THRESHOLD_MINUTES: u64 = 60;
 const STATE_FILE: &str = "push_check.json";
 
@@ -19,6 +22,10 @@ pub struct PushCheckCli {
     /// 静默模式：只有需要提醒时才输出
     #[arg(long, default_value_t = false)]
     pub quiet: bool,
+
+    /// 提示语言
+    #[arg(long, value_enum, env = "COW_HORSE_LANG", default_value_t = Lang::Zh)]
+    pub lang: Lang,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -31,23 +38,23 @@ pub fn run_push_check(args: &PushCheckCli) -> Result<()> {
     let now = Utc::now();
     let last_push = read_last_push(&path)?;
 
+    let lang = args.lang;
     if let Some(last) = last_push {
         let diff = now - last;
         let threshold = Duration::minutes(args.threshold_minutes as i64);
         if diff >= threshold {
-            println!(
-                "距离上一次 git push 已经过了 {} 分钟，出去走走喝杯水再回来继续吧！",
-                diff.num_minutes()
-            );
+            println!("{}", lang.format(Msg::PushLongGap, &[&diff.num_minutes()]));
         } else if !args.quiet {
             println!(
-                "距上次 push 仅 {} 分钟（提醒阈值 {} 分钟）。继续保持，但别忘了补水~",
-                diff.num_minutes(),
-                args.threshold_minutes
+                "{}",
+                lang.format(
+                    Msg::PushShortGap,
+                    &[&diff.num_minutes(), &args.threshold_minutes]
+                )
             );
         }
     } else if !args.quiet {
-        println!("第一次记录 push，完成后我会提醒你注意休息。" );
+        println!("{}", lang.text(Msg::PushFirst));
     }
 
     write_last_push(&path, now)?;
@@ -72,7 +79,10 @@ fn read_last_push(path: &Path) -> Result<Option<DateTime<Utc>>> {
     let data = fs::read_to_string(path)
         .with_context(|| format!("无法读取 push 记录：{}", path.display()))?;
     let state: PushCheckState = serde_json::from_str(&data)
-        .with_context(|| format!("push 记录损坏：{}", path.display()))?;
+        .with_context(|| {
+            CodedError::new(ErrorCode::StateCorrupt, format!("push 记录损坏：{}", path.display()))
+                .with_path(path)
+        })?;
     Ok(Some(state.last_push))
 }
 
@@ -1,24 +1,411 @@
-use chrono::{DateTime, FixedOffset};
+mod charts;
+mod html;
 
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, Write};
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate};
+use serde::Serialize;
+
+use crate::attendance::write_authors_csv;
 use crate::cli::Cli;
-use crate::metrics::{percentage, RepoMetrics};
+use crate::i18n::{display_width, pad_to, quip_count, Lang, Msg};
+use crate::metrics::{
+    percentage, severity_band, AggregateMetrics, AttributionEntry, ATTRIBUTION_OTHERS, AuthorMetrics, AuthorSummary, DailyStats, IgnoreSource, IgnoredAuthorStat, RepoFailure, RepoMetrics,
+    SeverityOverlap, SeverityWeights, ShowCommits, SuspectReason, Trend, TrendDirection, WeekendDays, WorkSchedule,
+};
+use crate::output::Format;
 
-pub fn print_human_report(metrics: &RepoMetrics, cli: &Cli) {
-    println!("仓库：{}", metrics.repo_path.display());
-    if let (Some(start), Some(end)) = (&metrics.analysis_start, &metrics.analysis_end) {
-        println!(
-            "时间范围：{}  ->  {}",
-            format_timestamp(start),
-            format_timestamp(end)
-        );
+pub use charts::{hour_histogram, weekday_bars, RenderTarget};
+use html::render_html;
+
+/// Writes the report in `format`; `cli` supplies the options the human
+/// report echoes.
+pub fn write_report(
+    format: Format,
+    metrics: &RepoMetrics,
+    cli: &Cli,
+    out: &mut dyn Write,
+) -> Result<()> {
+    match format {
+        Format::Human => write_human_report(metrics, cli, out)?,
+        Format::Json => write_json(metrics, out)?,
+        Format::Csv => write_authors_csv(metrics, out)?,
+        Format::Markdown => out.write_all(render_markdown(metrics, cli.lang).as_bytes())?,
+        Format::Badge => {
+            serde_json::to_writer(&mut *out, &badge(metrics, cli.lang))?;
+            writeln!(out)?;
+        }
+        Format::Html => {
+            let args: Vec<String> = env::args().skip(1).collect();
+            out.write_all(render_html(metrics, &args, Local::now(), cli.lang).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// A shields.io endpoint badge
+/// (<https://shields.io/badges/endpoint-badge>).
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Badge {
+    pub schema_version: u8,
+    pub label: &'static str,
+    pub message: String,
+    pub color: &'static str,
+}
+
+/// The score to one decimal, colored by severity band: the two lowest bands
+/// are green, then yellow, orange and red.
+pub fn badge(metrics: &RepoMetrics, lang: Lang) -> Badge {
+    let color = match severity_band(metrics.severity_score).index {
+        0 | 1 => "green",
+        2 => "yellow",
+        3 => "orange",
+        _ => "red",
+    };
+    Badge {
+        schema_version: 1,
+        label: lang.text(Msg::BadgeLabel),
+        message: format!("{:.1}", metrics.severity_score),
+        color,
     }
+}
 
-    if cli.author.is_some() {
-        println!("作者过滤：{}", cli.author.as_deref().unwrap());
+pub fn write_json(value: &impl Serialize, out: &mut dyn Write) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// One line per repository of a multi-repo run; the combined report
+/// follows it.
+pub fn write_repo_table(
+    report: &AggregateMetrics<'_>,
+    lang: Lang,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(out, "{}", lang.format(Msg::ReposOverview, &[&report.repos.len()]))?;
+    for repo in report.repos {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::ScoredRow,
+                &[
+                    &repo.repo_path.display(),
+                    &repo.total_commits,
+                    &pct(percentage(repo.after_hours_commits, repo.total_commits)),
+                    &format!("{:.1}", repo.severity_score),
+                ]
+            )
+        )?;
     }
+    writeln!(out, "{}", lang.text(Msg::Combined))?;
+    Ok(())
+}
 
-    if !metrics.ignored_authors.is_empty() {
-        println!("忽略作者：{}", metrics.ignored_authors.join(", "));
+/// The repositories a multi-repo run had to leave out, after everything
+/// else so they are not missed.
+pub fn write_failures(failures: &[RepoFailure], lang: Lang, out: &mut dyn Write) -> io::Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "{}", lang.format(Msg::FailedRepos, &[&failures.len()]))?;
+    for failure in failures {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::FailedRepoRow,
+                &[&failure.path.display(), &failure.code, &failure.message]
+            )
+        )?;
+    }
+    Ok(())
+}
+
+/// The `--author-report` layout: the author's own figures, then their
+/// ratios against the repository's, their weekday x hour punch card and
+/// their calendar over the window.
+pub fn write_author_report(
+    report: &AuthorMetrics,
+    lang: Lang,
+    color: bool,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(out, "{}", lang.format(Msg::AuthorReportTitle, &[&report.name]))?;
+    writeln!(out, "{}", lang.format(Msg::Repo, &[&report.repo_path.display()]))?;
+    writeln!(out, "{}", lang.format(Msg::Window, &[&report.window_description]))?;
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::AuthorCommitsLine,
+            &[&report.total_commits, &pct(report.commit_share)]
+        )
+    )?;
+    if let (Some(first), Some(last)) = (&report.first_commit, &report.last_commit) {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::AuthorFirstLast,
+                &[&format_timestamp(first), &format_timestamp(last)]
+            )
+        )?;
+    }
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::AuthorDaysLine,
+            &[&report.commit_days, &report.longest_streak_days]
+        )
+    )?;
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::AuthorOvertimeHours,
+            &[&format!("{:.1}", report.estimated_overtime_hours)]
+        )
+    )?;
+    if let Some(day) = &report.busiest_day {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::BusiestDayLine,
+                &[
+                    &day.date,
+                    &day.total_commits,
+                    &day.after_hours_commits,
+                    &day.after_hours_minutes,
+                ]
+            )
+        )?;
+    }
+
+    if !report.repos.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::AuthorPerRepo)]))?;
+        for repo in &report.repos {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::AuthorRepoRow,
+                    &[
+                        &repo.repo_path.display(),
+                        &repo.total_commits,
+                        &repo.after_hours_commits,
+                        &format!("{:.1}", repo.estimated_overtime_hours),
+                    ]
+                )
+            )?;
+        }
+        if let Some(repo) = &report.most_after_hours_repo {
+            writeln!(
+                out,
+                "{}",
+                lang.format(Msg::AuthorMostAfterHoursRepo, &[&repo.display()])
+            )?;
+        }
+    }
+
+    writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::AuthorVsRepo)]))?;
+    let width = lang.pick(10, 19);
+    for (label, bucket) in [
+        (Msg::AfterHoursCommits, &report.after_hours),
+        (Msg::WeekendCommits, &report.weekend),
+        (Msg::NightCommits, &report.night),
+    ] {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::AuthorRatioRow,
+                &[
+                    &pad_to(lang.text(label), width),
+                    &format!("{:>4}", bucket.commits),
+                    &pct(bucket.ratio),
+                    &pct(bucket.repo_ratio),
+                ]
+            )
+        )?;
+    }
+
+    writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::PunchCard)]))?;
+    write_punch_card(&report.punch_card, &report.work_schedule, lang, out)?;
+
+    writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::Calendar)]))?;
+    write_heatmap(
+        &report.daily,
+        (report.calendar_start, report.calendar_end),
+        AUTHOR_CALENDAR_MAX_WEEKS,
+        color,
+        lang,
+        out,
+    )
+}
+
+/// A ratio as a percentage with one decimal, without the `%`.
+fn pct(ratio: f64) -> String {
+    format!("{:.1}", ratio * 100.0)
+}
+
+pub fn write_human_report(
+    metrics: &RepoMetrics,
+    cli: &Cli,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let lang = cli.lang;
+    let color = cli.color.enabled(cli.output.is_some());
+    match &metrics.head {
+        Some(head) => writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::RepoWithHead,
+                &[&metrics.repo_path.display(), &head.describe()]
+            )
+        )?,
+        None => writeln!(
+            out,
+            "{}",
+            lang.format(Msg::Repo, &[&metrics.repo_path.display()])
+        )?,
+    }
+    match metrics.branches.as_slice() {
+        [] => {}
+        [all] if all == "--all" => writeln!(out, "{}", lang.text(Msg::AllBranches))?,
+        branches => writeln!(out, "{}", lang.format(Msg::Branches, &[&branches.join(", ")]))?,
+    }
+    match (&metrics.analysis_start, &metrics.analysis_end) {
+        (Some(start), Some(end)) => writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::WindowWithRange,
+                &[
+                    &metrics.window_description,
+                    &format_timestamp(start),
+                    &format_timestamp(end),
+                ]
+            )
+        )?,
+        _ => writeln!(
+            out,
+            "{}",
+            lang.format(Msg::Window, &[&metrics.window_description])
+        )?,
+    }
+
+    if let Some(zone) = &metrics.timezone {
+        writeln!(out, "{}", lang.format(Msg::Timezone, &[zone]))?;
+        if let Some(views) = &metrics.timezone_views {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::TimezoneViews,
+                    &[
+                        &pct(views.local_after_hours_ratio),
+                        zone,
+                        &pct(views.reference_after_hours_ratio),
+                    ]
+                )
+            )?;
+        }
+    }
+    if metrics.work_schedule != WorkSchedule::default() {
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::WorkHours, &[&metrics.work_schedule.describe()])
+        )?;
+    }
+
+    if let Some(coverage) = &metrics.coverage {
+        let status = lang.text(if coverage.met {
+            Msg::CoverageMet
+        } else {
+            Msg::CoverageMissed
+        });
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::Coverage,
+                &[&coverage.goal_days, &coverage.covered_days, &status]
+            )
+        )?;
+    }
+
+    if metrics.low_confidence {
+        writeln!(out, "{}", lang.format(Msg::Note, &[&low_confidence_reason(metrics, lang)]))?;
+    }
+
+    if let Some(path) = &metrics.user_config_path {
+        writeln!(out, "{}", lang.format(Msg::UserConfig, &[&path.display()]))?;
+    }
+    if let Some(path) = &metrics.repo_config_path {
+        writeln!(out, "{}", lang.format(Msg::RepoConfig, &[&path.display()]))?;
+    }
+
+    if let Some(author) = &cli.author {
+        writeln!(out, "{}", lang.format(Msg::AuthorFilter, &[author]))?;
+    }
+    if !metrics.pathspecs.is_empty() {
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::PathFilter, &[&metrics.pathspecs.join(", ")])
+        )?;
+    }
+
+    for (source, label) in [
+        (IgnoreSource::Default, Msg::IgnoredDefault),
+        (IgnoreSource::User, Msg::IgnoredUser),
+        (IgnoreSource::Pattern, Msg::IgnoredPattern),
+        (IgnoreSource::Bot, Msg::IgnoredBot),
+    ] {
+        let stats: Vec<&IgnoredAuthorStat> = metrics
+            .ignored_author_stats
+            .iter()
+            .filter(|stat| stat.source == source)
+            .collect();
+        if stats.is_empty() {
+            continue;
+        }
+        let names: Vec<&str> = stats.iter().map(|stat| stat.name.as_str()).collect();
+        let dropped: usize = stats.iter().map(|stat| stat.commits).sum();
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::IgnoredLine,
+                &[&lang.text(label), &names.join(", "), &dropped]
+            )
+        )?;
+    }
+    if !metrics.excluded_spans.is_empty() {
+        let spans: Vec<String> = metrics
+            .excluded_spans
+            .iter()
+            .map(|span| {
+                lang.format(
+                    Msg::ExcludedSpan,
+                    &[&span.author, &span.range, &span.commits],
+                )
+            })
+            .collect();
+        writeln!(out, "{}", lang.format(Msg::ExcludedSpans, &[&spans.join(", ")]))?;
+    }
+    if let Some(merges) = metrics.skipped_merge_commits {
+        writeln!(out, "{}", lang.format(Msg::SkippedMerges, &[&merges]))?;
     }
     if !metrics.alias_rules.is_empty() {
         let pairs: Vec<String> = metrics
@@ -26,74 +413,1446 @@ pub fn print_human_report(metrics: &RepoMetrics, cli: &Cli) {
             .iter()
             .map(|rule| format!("{}=>{}", rule.from, rule.to))
             .collect();
-        println!("别名合并：{}", pairs.join(", "));
+        writeln!(out, "{}", lang.format(Msg::AliasesMerged, &[&pairs.join(", ")]))?;
     }
 
-    println!(
-        "分析提交：{}（作者：{} 人，活跃天数：{} 天）",
-        metrics.total_commits, metrics.unique_authors, metrics.commit_days
-    );
-    println!(
-        "牛马指数：{:>5.1}/100 -> {}",
-        metrics.severity_score, metrics.severity_label
-    );
-    println!(
-        "下班后提交：{}（{:.1}%）",
-        metrics.after_hours_commits,
-        percentage(metrics.after_hours_commits, metrics.total_commits) * 100.0
-    );
-    println!(
-        "周末提交：{}（{:.1}%）",
-        metrics.weekend_commits,
-        percentage(metrics.weekend_commits, metrics.total_commits) * 100.0
-    );
-    println!(
-        "深夜提交 (23:00-05:59)：{}（{:.1}%）",
-        metrics.night_commits,
-        percentage(metrics.night_commits, metrics.total_commits) * 100.0
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::Analyzed,
+            &[
+                &metrics.total_commits,
+                &metrics.unique_authors,
+                &metrics.commit_days,
+            ]
+        )
+    )?;
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::Score,
+            &[
+                &format!("{:>5.1}", metrics.severity_score),
+                &paint(
+                    &band_text(metrics, lang),
+                    band_color(metrics.severity_band.index),
+                    color
+                ),
+                &band_note(metrics, lang),
+            ]
+        )
+    )?;
+    if let Some(quip) = &metrics.quip {
+        writeln!(out, "  {quip}")?;
+    }
+    if metrics.total_commits > 0 {
+        write_severity_breakdown(metrics, lang, out)?;
+    }
+    if metrics.severity_weights != SeverityWeights::default() {
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::CustomWeights, &[&metrics.severity_weights.describe()])
+        )?;
+    }
+    if let Some(forecast) = &metrics.forecast {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::Forecast,
+                &[
+                    &forecast.until.format("%Y-%m-%d %H:%M UTC"),
+                    &format!("{:.1}", forecast.projected_severity_score),
+                    &lang.band_label(severity_band(forecast.projected_severity_score).index),
+                    &format!("{:.0}", forecast.projected_total_commits),
+                    &format!("{:.0}", forecast.projected_after_hours_commits),
+                    &forecast.remaining_weekdays,
+                    &forecast.remaining_weekend_days,
+                ]
+            )
+        )?;
+    }
+    let share = |part: usize| pct(percentage(part, metrics.total_commits));
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::AfterHoursLine,
+            &[
+                &metrics.after_hours_commits,
+                &after_hours_pct(
+                    percentage(metrics.after_hours_commits, metrics.total_commits),
+                    color
+                ),
+            ]
+        )
+    )?;
+    if metrics.weekend_days.is_empty() {
+        writeln!(out, "{}", lang.text(Msg::WeekendNotCounted))?;
+    } else {
+        let days = if metrics.weekend_days == WeekendDays::default() {
+            String::new()
+        } else {
+            format!(" ({})", metrics.weekend_days.describe())
+        };
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::WeekendLine,
+                &[&days, &metrics.weekend_commits, &share(metrics.weekend_commits)]
+            )
+        )?;
+    }
+    if metrics.holiday_commits > 0 {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::HolidayLine,
+                &[&metrics.holiday_commits, &share(metrics.holiday_commits)]
+            )
+        )?;
+    }
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::NightLine,
+            &[
+                &metrics.night_window.describe(),
+                &metrics.night_commits,
+                &share(metrics.night_commits),
+            ]
+        )
+    )?;
+    if metrics.severity_overlap == SeverityOverlap::Deduplicated {
+        let buckets = &metrics.severity_buckets;
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::Deduplicated,
+                &[&buckets.night, &buckets.weekend, &buckets.after_hours]
+            )
+        )?;
+    }
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::OvertimeDaysLine,
+            &[&metrics.overtime_days, &metrics.commit_days]
+        )
+    )?;
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::EstimatedOvertimeLine,
+            &[
+                &format!("{:.1}", metrics.estimated_overtime_hours),
+                &metrics.session_gap_minutes,
+                &metrics.session_padding_minutes,
+            ]
+        )
+    )?;
+    if metrics.weeks_with_commits > 0 {
+        write_996(metrics, lang, out)?;
+    }
+    let span = &metrics.workday_span;
+    if span.days > 0 {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::WorkdaySpanLine,
+                &[
+                    &format!("{:.1}", span.average_hours),
+                    &format!("{:.1}", span.p50_hours),
+                    &format!("{:.1}", span.p90_hours),
+                    &span.long_day_hours,
+                    &span.long_days.len(),
+                ]
+            )
+        )?;
+    }
+    writeln!(
+        out,
+        "{}",
+        lang.format(Msg::LongestStreakLine, &[&metrics.longest_streak_days])
+    )?;
+    if metrics.current_streak_active {
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::CurrentStreakLine, &[&metrics.current_streak_days])
+        )?;
+    }
+    if let (Some(start), Some(end)) = (
+        metrics.longest_overtime_streak_start,
+        metrics.longest_overtime_streak_end,
+    ) {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::OvertimeStreakLine,
+                &[&metrics.longest_overtime_streak_days, &start, &end]
+            )
+        )?;
+    }
+
+    if metrics.all_nighters > 0 {
+        writeln!(out, "{}", lang.format(Msg::AllNighters, &[&metrics.all_nighters]))?;
+    }
+    if let Some(event) = &metrics.worst_all_nighter {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::WorstAllNighter,
+                &[
+                    &event.author,
+                    &format_timestamp(&event.start),
+                    &format!("{:.1}", event.duration_minutes as f64 / 60.0),
+                    &event.commits,
+                ]
+            )
+        )?;
+    }
+
+    if let Some(day) = &metrics.busiest_day {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::BusiestDayLine,
+                &[
+                    &day.date,
+                    &day.total_commits,
+                    &day.after_hours_commits,
+                    &day.after_hours_minutes,
+                ]
+            )
+        )?;
+    }
+    if let Some(sparkline) = weekly_sparkline(metrics) {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::WeeklySparkline,
+                &[
+                    &sparkline.weeks,
+                    &sparkline.bars,
+                    &format!("{:.0}", sparkline.first * 100.0),
+                    &format!("{:.0}", sparkline.last * 100.0),
+                ]
+            )
+        )?;
+    }
+    if metrics.total_commits > 0 {
+        writeln!(out, "{}", trend_line(&metrics.trend, lang))?;
+    }
+
+    if !metrics.crunch_periods.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::CrunchPeriods)]))?;
+        for period in &metrics.crunch_periods {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::CrunchRow,
+                    &[
+                        &period.start,
+                        &period.end,
+                        &period.total_commits,
+                        &pct(period.after_hours_ratio),
+                    ]
+                )
+            )?;
+        }
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::CrunchLegend, &[&pct(metrics.crunch_threshold)])
+        )?;
+    }
+
+    if !metrics.breaks.is_empty() {
+        write_breaks(metrics, lang, out)?;
+    }
+
+    if metrics.total_commits > 0 {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::PunchCard)]))?;
+        write_punch_card(&metrics.punch_card, &metrics.work_schedule, lang, out)?;
+    }
+
+    if cli.heatmap && !metrics.daily.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::Calendar)]))?;
+        let range = (metrics.daily[0].date, metrics.daily[metrics.daily.len() - 1].date);
+        write_heatmap(&metrics.daily, range, HEATMAP_MAX_WEEKS, color, lang, out)?;
+    }
+
+    if metrics.weekly.len() > 1 {
+        let recent = &metrics.weekly[metrics.weekly.len().saturating_sub(RECENT_WEEKS)..];
+        let title = lang.format(Msg::RecentWeeks, &[&recent.len()]);
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&title]))?;
+        writeln!(out, "{}", lang.text(Msg::WeeklyHeader))?;
+        // Column widths follow the header of each language.
+        let (commits, after_hours, weekend, night) = lang.pick((4, 6, 4, 4), (7, 11, 7, 5));
+        for week in recent {
+            writeln!(
+                out,
+                "  {:<9} | {:>commits$} | {:>after_hours$} | {:>5.1}% | {:>weekend$} | {:>night$}",
+                week.label,
+                week.total_commits,
+                week.after_hours_commits,
+                percentage(week.after_hours_commits, week.total_commits) * 100.0,
+                week.weekend_commits,
+                week.night_commits
+            )?;
+        }
+    }
+
+    if !metrics.components.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::Submodules)]))?;
+        for component in &metrics.components {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::ComponentRow,
+                    &[
+                        &component.path,
+                        &component.total_commits,
+                        &after_hours_pct(component.after_hours_ratio, color),
+                    ]
+                )
+            )?;
+        }
+    }
+    if !metrics.directories.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::Directories)]))?;
+        for directory in &metrics.directories {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::ScoredRow,
+                    &[
+                        &directory.path,
+                        &directory.total_commits,
+                        &after_hours_pct(directory.after_hours_ratio, color),
+                        &format!("{:.1}", directory.severity_score),
+                    ]
+                )
+            )?;
+        }
+    }
+    if !metrics.skipped_submodules.is_empty() {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::SkippedSubmodules,
+                &[&metrics.skipped_submodules.join(", ")]
+            )
+        )?;
+    }
+
+    if !metrics.sensitivity.is_empty() {
+        let title = lang.format(
+            Msg::Sensitivity,
+            &[&metrics.work_schedule.start.format("%H:%M")],
+        );
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&title]))?;
+        writeln!(out, "{}", lang.text(Msg::SensitivityHeader))?;
+        let (after_hours, overtime, score) = lang.pick((10, 8, 6), (11, 13, 8));
+        for point in &metrics.sensitivity {
+            writeln!(
+                out,
+                "  {:>8} | {:>after_hours$} | {:>5.1}% | {:>overtime$} | {:>score$.1}",
+                point.work_end,
+                point.after_hours_commits,
+                point.after_hours_ratio * 100.0,
+                point.overtime_days,
+                point.severity_score
+            )?;
+        }
+    }
+
+    if !metrics.drift_warnings.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::DriftWarnings)]))?;
+        for warning in &metrics.drift_warnings {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::DriftRow,
+                    &[&warning.name, &format!("{:.0}", warning.drift_minutes)]
+                )
+            )?;
+        }
+    }
+
+    if !metrics.policy_results.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::PolicyChecks)]))?;
+        for result in &metrics.policy_results {
+            let status = lang.text(if result.passed {
+                Msg::PolicyPassed
+            } else {
+                Msg::PolicyFailed
+            });
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::PolicyRow,
+                    &[
+                        &status,
+                        &result.metric,
+                        &result.comparison,
+                        &format_policy_value(&result.metric, result.threshold),
+                        &format_policy_value(&result.metric, result.actual),
+                    ]
+                )
+            )?;
+        }
+    }
+
+    if !metrics.attribution.is_empty() {
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::Attribution)]))?;
+        write_attribution(metrics, lang, out)?;
+    }
+
+    if !metrics.suspect_identities.is_empty() {
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::Heading, &[&lang.text(Msg::SuspectIdentities)])
+        )?;
+        for suspect in &metrics.suspect_identities {
+            writeln!(
+                out,
+                "{}",
+                lang.format(
+                    Msg::SuspectRow,
+                    &[
+                        &suspect.name,
+                        &suspect.merged_from.join(", "),
+                        &suspect
+                            .reasons
+                            .iter()
+                            .map(|reason| suspect_reason(reason, lang))
+                            .collect::<Vec<_>>()
+                            .join(lang.text(Msg::ListSeparator)),
+                    ]
+                )
+            )?;
+        }
+    }
+
+    for (title, authors) in [
+        (Msg::NightOwls, &metrics.top_after_hours_authors),
+        (Msg::ChillAuthors, &metrics.chill_authors),
+    ] {
+        if authors.is_empty() {
+            continue;
+        }
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(title)]))?;
+        write_author_rows(metrics, authors, lang, color, out)?;
+    }
+
+    if metrics.below_min_commits_authors > 0 {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::BelowMinCommits,
+                &[
+                    &metrics.below_min_commits_authors,
+                    &metrics.leaderboard_min_commits,
+                ]
+            )
+        )?;
+    }
+
+    if cli.all_authors && !metrics.authors.is_empty() {
+        let title = lang.format(Msg::AllAuthors, &[&metrics.authors.len()]);
+        writeln!(out, "{}", lang.format(Msg::Heading, &[&title]))?;
+        write_author_rows(metrics, &metrics.authors, lang, color, out)?;
+    }
+
+    if let Some(listing) = &metrics.shown_commits {
+        let title = match listing.kind {
+            ShowCommits::Night => Msg::NightCommits,
+            ShowCommits::AfterHours => Msg::AfterHoursCommits,
+            ShowCommits::Weekend => Msg::WeekendCommits,
+        };
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::CommitListing,
+                &[&lang.text(title), &listing.total, &listing.commits.len()]
+            )
+        )?;
+        for commit in &listing.commits {
+            writeln!(
+                out,
+                "  {}  {}  {}  {}",
+                &commit.hash[..commit.hash.len().min(SHORT_HASH_LEN)],
+                commit.timestamp.format("%H:%M"),
+                commit.author,
+                commit.subject
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A leaderboard or the full author table, names in bold and every column
+/// lined up; wide (CJK) names count two columns per character.
+fn write_author_rows(
+    metrics: &RepoMetrics,
+    authors: &[AuthorSummary],
+    lang: Lang,
+    color: bool,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let width = |value: fn(&AuthorSummary) -> usize| {
+        authors
+            .iter()
+            .map(|author| value(author).to_string().len())
+            .max()
+            .unwrap_or(0)
+    };
+    let (commits, weekend, night) = (
+        width(|author| author.total_commits),
+        width(|author| author.weekend_commits),
+        width(|author| author.night_commits),
     );
-    println!(
-        "加班天数：{} / {} 天",
-        metrics.overtime_days, metrics.commit_days
+    let names = authors
+        .iter()
+        .map(|author| display_width(&author.name))
+        .max()
+        .unwrap_or(0);
+    for author in authors {
+        let name = format!(
+            "{}{}",
+            paint(&author.name, BOLD, color),
+            " ".repeat(names - display_width(&author.name))
+        );
+        let ratio = format!("{:>5.1}", author.after_hours_ratio * 100.0);
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::AuthorRow,
+                &[
+                    &name,
+                    &format!("{:>commits$}", author.total_commits),
+                    &highlight_after_hours(ratio, author.after_hours_ratio, color),
+                    &format!("{:>weekend$}", author.weekend_commits),
+                    &format!("{:>night$}", author.night_commits),
+                    &suspect_marker(metrics, &author.name, lang),
+                ]
+            )
+        )?;
+    }
+    Ok(())
+}
+
+const BOLD: &str = "1";
+const RED: &str = "31";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+/// After-hours shares above this stand out in color.
+const AFTER_HOURS_HIGHLIGHT_RATIO: f64 = 0.3;
+
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// The two lowest bands are green, the middle one yellow, the rest red.
+fn band_color(index: usize) -> &'static str {
+    match index {
+        0 | 1 => GREEN,
+        2 => YELLOW,
+        _ => RED,
+    }
+}
+
+/// `ratio` as a percentage with one decimal, without the `%`, highlighted
+/// when high.
+fn after_hours_pct(ratio: f64, color: bool) -> String {
+    highlight_after_hours(pct(ratio), ratio, color)
+}
+
+fn highlight_after_hours(text: String, ratio: f64, color: bool) -> String {
+    if ratio > AFTER_HOURS_HIGHLIGHT_RATIO {
+        paint(&text, YELLOW, color)
+    } else {
+        text
+    }
+}
+
+const SHORT_HASH_LEN: usize = 8;
+const RECENT_WEEKS: usize = 8;
+
+/// GitHub-flavored Markdown version of the human report: header with repo
+/// and window, a key-metrics table, and the two leaderboards as tables.
+pub fn render_markdown(metrics: &RepoMetrics, lang: Lang) -> String {
+    let mut md = lang.format(
+        Msg::MdTitle,
+        &[&markdown_escape(&metrics.repo_path.display().to_string())],
     );
-    println!("最长连续工作天数：{} 天", metrics.longest_streak_days);
+    if let Some(head) = &metrics.head {
+        md.push_str(&lang.format(Msg::MdHead, &[&markdown_escape(&head.describe())]));
+    }
+    md.push_str(&lang.format(
+        Msg::MdWindow,
+        &[&markdown_escape(&metrics.window_description)],
+    ));
+    if let (Some(start), Some(end)) = (&metrics.analysis_start, &metrics.analysis_end) {
+        md.push_str(&lang.format(
+            Msg::MdRange,
+            &[&format_timestamp(start), &format_timestamp(end)],
+        ));
+    }
+    if metrics.low_confidence {
+        md.push_str(&lang.format(
+            Msg::MdNote,
+            &[&markdown_escape(&low_confidence_reason(metrics, lang))],
+        ));
+    }
 
+    md.push_str(lang.text(Msg::MdKeyMetrics));
+    let share = |part: usize| {
+        lang.format(
+            Msg::MdCountShare,
+            &[&part, &pct(percentage(part, metrics.total_commits))],
+        )
+    };
+    let rows = [
+        (
+            lang.text(Msg::MdSeverity).to_string(),
+            lang.format(
+                Msg::MdScoreValue,
+                &[
+                    &format!("{:.1}", metrics.severity_score),
+                    &markdown_escape(&band_text(metrics, lang)),
+                ],
+            ),
+        ),
+        (
+            lang.text(Msg::MdAnalyzed).to_string(),
+            lang.format(
+                Msg::MdAnalyzedValue,
+                &[
+                    &metrics.total_commits,
+                    &metrics.unique_authors,
+                    &metrics.commit_days,
+                ],
+            ),
+        ),
+        (
+            lang.text(Msg::AfterHoursCommits).to_string(),
+            share(metrics.after_hours_commits),
+        ),
+        (
+            lang.text(Msg::WeekendCommits).to_string(),
+            share(metrics.weekend_commits),
+        ),
+        (
+            lang.format(Msg::MdNightCommits, &[&metrics.night_window.describe()]),
+            share(metrics.night_commits),
+        ),
+        (
+            lang.text(Msg::MdOvertimeDays).to_string(),
+            format!(
+                "{} / {}",
+                metrics.overtime_days,
+                lang.format(Msg::MdDays, &[&metrics.commit_days])
+            ),
+        ),
+        (
+            lang.text(Msg::MdLongestStreak).to_string(),
+            lang.format(Msg::MdDays, &[&metrics.longest_streak_days]),
+        ),
+    ];
+    for (name, value) in rows {
+        md.push_str(&format!("| {name} | {value} |\n"));
+    }
     if let Some(day) = &metrics.busiest_day {
-        println!(
-            "最忙的一天：{} -> {} 次提交（{} 次下班后）",
-            day.date, day.total_commits, day.after_hours_commits
-        );
+        md.push_str(&lang.format(
+            Msg::MdBusiestDay,
+            &[&day.date, &day.total_commits, &day.after_hours_commits],
+        ));
     }
 
-    if !metrics.top_after_hours_authors.is_empty() {
-        println!("\n夜猫子榜单：");
-        for author in &metrics.top_after_hours_authors {
-            println!(
-                "  - {} -> {} 次提交 | {:.1}% 下班后 | {} 次周末 | {} 次深夜",
-                author.name,
+    if metrics.total_commits > 0 {
+        md.push_str(&lang.format(Msg::MdSection, &[&lang.text(Msg::HourChart)]));
+        md.push_str(&hour_histogram(
+            &metrics.punch_card,
+            &metrics.work_schedule,
+            lang,
+            RenderTarget::Markdown,
+        ));
+        md.push_str(&lang.format(Msg::MdSection, &[&lang.text(Msg::WeekdayChart)]));
+        md.push_str(&weekday_bars(
+            &metrics.punch_card,
+            &metrics.weekend_days,
+            lang,
+            RenderTarget::Markdown,
+        ));
+    }
+
+    for (title, authors) in [
+        (Msg::NightOwls, &metrics.top_after_hours_authors),
+        (Msg::ChillAuthors, &metrics.chill_authors),
+    ] {
+        if authors.is_empty() {
+            continue;
+        }
+        md.push_str(&lang.format(Msg::MdAuthorTable, &[&lang.text(title)]));
+        for author in authors {
+            md.push_str(&format!(
+                "| {} | {} | {:.1}% | {} | {} |\n",
+                markdown_escape(&author.name),
                 author.total_commits,
                 author.after_hours_ratio * 100.0,
                 author.weekend_commits,
                 author.night_commits
-            );
+            ));
         }
     }
+    if metrics.below_min_commits_authors > 0 {
+        md.push_str(&lang.format(
+            Msg::MdBelowMinCommits,
+            &[
+                &metrics.below_min_commits_authors,
+                &metrics.leaderboard_min_commits,
+            ],
+        ));
+    }
+    md
+}
 
-    if !metrics.chill_authors.is_empty() {
-        println!("\n摸鱼榜单：");
-        for author in &metrics.chill_authors {
-            println!(
-                "  - {} -> {} 次提交 | {:.1}% 下班后 | {} 次周末 | {} 次深夜",
-                author.name,
-                author.total_commits,
-                author.after_hours_ratio * 100.0,
-                author.weekend_commits,
-                author.night_commits
-            );
+/// Escapes characters that would break a table cell or open a code span.
+fn markdown_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace('`', "\\`")
+}
+
+/// One reason an identity was flagged, in `lang`.
+pub fn suspect_reason(reason: &SuspectReason, lang: Lang) -> String {
+    match reason {
+        SuspectReason::HeavyDays { days, limit } => {
+            lang.format(Msg::SuspectHeavyDays, &[days, limit])
+        }
+        SuspectReason::SameMinuteBursts { minutes, min_commits } => {
+            lang.format(Msg::SuspectSameMinute, &[minutes, min_commits])
+        }
+    }
+}
+
+/// The severity band in `lang`, marked when the window is too short to
+/// trust it.
+pub fn band_text(metrics: &RepoMetrics, lang: Lang) -> String {
+    let label = lang.band_label(metrics.severity_band.index);
+    if metrics.low_confidence {
+        format!("{label}{}", lang.text(Msg::LowConfidenceSuffix))
+    } else {
+        label.to_string()
+    }
+}
+
+pub fn low_confidence_reason(metrics: &RepoMetrics, lang: Lang) -> String {
+    lang.format(Msg::LowConfidence, &[&metrics.expected_working_days])
+}
+
+fn suspect_marker(metrics: &RepoMetrics, name: &str, lang: Lang) -> &'static str {
+    if metrics.suspect_identities.iter().any(|s| s.name == name) {
+        lang.text(Msg::SuspectMarker)
+    } else {
+        ""
+    }
+}
+
+/// Each term's ratio, weight and points; the points add up to the score.
+fn write_severity_breakdown(
+    metrics: &RepoMetrics,
+    lang: Lang,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let terms = metrics.severity_breakdown.terms();
+    let width = terms
+        .iter()
+        .map(|(label, _)| display_width(lang.text(*label)))
+        .max()
+        .unwrap_or(0);
+    writeln!(
+        out,
+        "  {}{}",
+        pad_to(lang.text(Msg::BreakdownTerm), width),
+        lang.text(Msg::BreakdownColumns)
+    )?;
+    for (label, term) in terms {
+        writeln!(
+            out,
+            "  {}{:>6.1}%  ×{:>5.1}  {:>5.1}",
+            pad_to(lang.text(label), width),
+            term.ratio * 100.0,
+            term.weight,
+            term.points
+        )?;
+    }
+    Ok(())
+}
+
+/// How close the score sits to a band edge, so a label flip from 60.4 to
+/// 61.0 reads as the small move it is.
+fn band_note(metrics: &RepoMetrics, lang: Lang) -> String {
+    let band = &metrics.severity_band;
+    let above_lower = metrics.severity_score - band.lower;
+    match band.distance_to_upper {
+        Some(_) if band.index > 0 && above_lower < 1.0 => {
+            lang.format(Msg::BandJustEntered, &[&format!("{above_lower:.1}")])
+        }
+        Some(distance) => lang.format(Msg::BandBelowNext, &[&format!("{distance:.1}")]),
+        None if band.index > 0 => {
+            lang.format(Msg::BandAboveFloor, &[&format!("{above_lower:.1}")])
+        }
+        None => String::new(),
+    }
+}
+
+const ATTRIBUTION_BAR_WIDTH: usize = 40;
+const ATTRIBUTION_GLYPHS: [char; 7] = ['█', '▓', '▒', '░', '#', '=', '·'];
+
+/// One stacked bar per category; each author owns a glyph, with the legend
+/// listing the exact shares.
+fn write_attribution(metrics: &RepoMetrics, lang: Lang, out: &mut dyn Write) -> io::Result<()> {
+    let entries = &metrics.attribution;
+    let categories: [(Msg, ShareFn); 3] = [
+        (Msg::AttributionAfterHours, |e| e.after_hours_share),
+        (Msg::AttributionWeekend, |e| e.weekend_share),
+        (Msg::AttributionNight, |e| e.night_share),
+    ];
+    let width = categories
+        .iter()
+        .map(|(label, _)| display_width(lang.text(*label)))
+        .max()
+        .unwrap_or(0);
+    for (label, share) in categories {
+        let mut bar = String::new();
+        let mut filled = 0usize;
+        for (i, entry) in entries.iter().enumerate() {
+            let cells = (share(entry) * ATTRIBUTION_BAR_WIDTH as f64).round() as usize;
+            let cells = cells.min(ATTRIBUTION_BAR_WIDTH - filled);
+            bar.extend(std::iter::repeat_n(ATTRIBUTION_GLYPHS[i.min(ATTRIBUTION_GLYPHS.len() - 1)], cells));
+            filled += cells;
+        }
+        bar.extend(std::iter::repeat_n(' ', ATTRIBUTION_BAR_WIDTH - filled));
+        writeln!(out, "  {} |{bar}|", pad_to(lang.text(label), width))?;
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        // The fold-in bucket is stored under its Chinese name.
+        let author = match entry.author.as_str() {
+            ATTRIBUTION_OTHERS => lang.text(Msg::AttributionOthers),
+            name => name,
+        };
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::AttributionLegend,
+                &[
+                    &ATTRIBUTION_GLYPHS[i.min(ATTRIBUTION_GLYPHS.len() - 1)],
+                    &author,
+                    &pct(entry.after_hours_share),
+                    &pct(entry.weekend_share),
+                    &pct(entry.night_share),
+                ]
+            )
+        )?;
+    }
+    Ok(())
+}
+
+const BREAKS_SHOWN: usize = 10;
+
+/// Authors with the fewest and shortest breaks first, then how long ago
+/// anyone last took one.
+fn write_breaks(metrics: &RepoMetrics, lang: Lang, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{}", lang.format(Msg::Heading, &[&lang.text(Msg::Breaks)]))?;
+    for author in metrics.breaks.iter().take(BREAKS_SHOWN) {
+        let range = format!("{} ~ {}", author.first_day, author.last_day);
+        let line = if author.breaks == 0 {
+            lang.format(Msg::NoBreakRow, &[&author.name, &range, &metrics.vacation_days])
+        } else {
+            lang.format(
+                Msg::BreakRow,
+                &[&author.name, &range, &author.breaks, &author.longest_break_days],
+            )
+        };
+        writeln!(out, "{line}")?;
+    }
+    if metrics.breaks.len() > BREAKS_SHOWN {
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::BreaksOmitted, &[&(metrics.breaks.len() - BREAKS_SHOWN)])
+        )?;
+    }
+    let summary = match metrics.days_since_last_break {
+        Some(days) => lang.format(Msg::DaysSinceBreak, &[&metrics.vacation_days, &days]),
+        None => lang.format(Msg::NobodyBreaks, &[&metrics.vacation_days]),
+    };
+    writeln!(out, "{summary}")
+}
+
+fn write_996(metrics: &RepoMetrics, lang: Lang, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::Weeks996Line,
+            &[&metrics.weeks_996, &metrics.weeks_with_commits]
+        )
+    )?;
+    if !metrics.worst_996_weeks.is_empty() {
+        let weeks: Vec<String> = metrics
+            .worst_996_weeks
+            .iter()
+            .map(|week| {
+                lang.format(
+                    Msg::Week996Entry,
+                    &[
+                        &week.week,
+                        &week.days,
+                        &week.median_first_commit.format("%H:%M"),
+                        &week.median_last_commit.format("%H:%M"),
+                    ],
+                )
+            })
+            .collect();
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::Worst996Weeks, &[&weeks.join(lang.text(Msg::Comma))])
+        )?;
+    }
+    if !metrics.authors_996.is_empty() {
+        let authors: Vec<String> = metrics
+            .authors_996
+            .iter()
+            .map(|author| lang.format(Msg::Author996Entry, &[&author.name, &author.weeks]))
+            .collect();
+        writeln!(
+            out,
+            "{}",
+            lang.format(Msg::Authors996, &[&authors.join(lang.text(Msg::Comma))])
+        )?;
+    }
+    Ok(())
+}
+
+fn trend_line(trend: &Trend, lang: Lang) -> String {
+    let direction = match trend.direction {
+        TrendDirection::InsufficientData => return lang.text(Msg::TrendInsufficient).to_string(),
+        TrendDirection::Improving => Msg::TrendImproving,
+        TrendDirection::Stable => Msg::TrendStable,
+        TrendDirection::Worsening => Msg::TrendWorsening,
+    };
+    let delta = format!("{:+.1}", trend.half_delta_points.unwrap_or_default());
+    lang.format(Msg::TrendLine, &[&lang.text(direction), &delta])
+}
+
+type ShareFn = fn(&AttributionEntry) -> f64;
+
+const SPARKLINE_WEEKS: i64 = 12;
+const SPARKLINE_GLYPHS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+struct Sparkline {
+    weeks: usize,
+    bars: String,
+    /// After-hours ratio of the first and last week with commits.
+    first: f64,
+    last: f64,
+}
+
+/// After-hours ratio per calendar week over the last [`SPARKLINE_WEEKS`]
+/// weeks of commits, scaled against the highest week; weeks without commits
+/// are blank. `None` when the commits span less than two weeks.
+fn weekly_sparkline(metrics: &RepoMetrics) -> Option<Sparkline> {
+    let (first, last) = (metrics.daily.first()?, metrics.daily.last()?);
+    let monday = |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_monday().into());
+    let last_week = monday(last.date);
+    let start = monday(first.date).max(last_week - Duration::weeks(SPARKLINE_WEEKS - 1));
+    let weeks = ((last_week - start).num_days() / 7 + 1) as usize;
+    if weeks < 2 {
+        return None;
+    }
+
+    let mut counts = vec![(0usize, 0usize); weeks];
+    for day in metrics.daily.iter().filter(|day| day.date >= start) {
+        let week = ((monday(day.date) - start).num_days() / 7) as usize;
+        counts[week].0 += day.total_commits;
+        counts[week].1 += day.after_hours_commits;
+    }
+    let ratios: Vec<Option<f64>> = counts
+        .iter()
+        .map(|&(total, after_hours)| (total > 0).then(|| percentage(after_hours, total)))
+        .collect();
+    let max = ratios.iter().flatten().copied().fold(0.0, f64::max);
+    let bars = ratios
+        .iter()
+        .map(|ratio| match ratio {
+            None => ' ',
+            Some(_) if max == 0.0 => SPARKLINE_GLYPHS[0],
+            Some(ratio) => {
+                let top = SPARKLINE_GLYPHS.len() - 1;
+                SPARKLINE_GLYPHS[((ratio / max) * top as f64).round() as usize]
+            }
+        })
+        .collect();
+    Some(Sparkline {
+        weeks,
+        bars,
+        first: *ratios.iter().flatten().next()?,
+        last: *ratios.iter().flatten().next_back()?,
+    })
+}
+
+const HEATMAP_MAX_WEEKS: i64 = 53;
+/// The `--author-report` calendar stays narrower than the repository one.
+const AUTHOR_CALENDAR_MAX_WEEKS: i64 = 26;
+const HEATMAP_GLYPHS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+/// Same densities for after-hours-heavy days when there is no color.
+const HEATMAP_HEAVY_GLYPHS: [char; 5] = ['·', '◔', '◑', '◕', '●'];
+/// Days with at least this share of after-hours commits are marked.
+const HEATMAP_HEAVY_RATIO: f64 = 0.5;
+
+fn after_hours_heavy(day: &DailyStats) -> bool {
+    day.total_commits > 0
+        && percentage(day.after_hours_commits, day.total_commits) >= HEATMAP_HEAVY_RATIO
+}
+
+/// One column per week (Monday on top) from `first` to `last`, shaded by
+/// commits per day in `daily`; month numbers mark the week holding the 1st.
+/// Days outside the range stay blank, so a range starting mid-week lines up.
+/// Only the latest `max_weeks` are drawn and older ones are summarized.
+/// After-hours-heavy days are red with `color` and drawn with
+/// [`HEATMAP_HEAVY_GLYPHS`] without.
+fn write_heatmap(
+    daily: &[DailyStats],
+    (first, last): (NaiveDate, NaiveDate),
+    max_weeks: i64,
+    color: bool,
+    lang: Lang,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let monday = |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_monday().into());
+    let last_week = monday(last);
+    let earliest = last_week - Duration::weeks(max_weeks - 1);
+    let start = monday(first).max(earliest);
+    let weeks = ((last_week - start).num_days() / 7 + 1) as usize;
+    let days: HashMap<NaiveDate, &DailyStats> = daily.iter().map(|day| (day.date, day)).collect();
+    let max = daily.iter().map(|day| day.total_commits).max().unwrap_or(0);
+
+    let mut months = String::new();
+    let mut column = 0usize;
+    for week in 0..weeks {
+        let week_start = start + Duration::weeks(week as i64);
+        let new_month = (0..7).find_map(|offset| {
+            let date = week_start + Duration::days(offset);
+            (date.day() == 1).then_some(date.month())
+        });
+        let month = match new_month {
+            Some(month) => Some(month),
+            None if week == 0 => Some(week_start.month()),
+            None => None,
+        };
+        if let Some(month) = month
+            && column <= week
+        {
+            months.push_str(&" ".repeat(week - column));
+            let label = lang.month(month);
+            column = week + display_width(&label);
+            months.push_str(&label);
+        }
+    }
+    writeln!(out, "     {months}")?;
+
+    for weekday in 0..7 {
+        let label = lang.weekday_short(weekday);
+        let mut row = String::new();
+        for week in 0..weeks {
+            let date = start + Duration::days(week as i64 * 7 + weekday as i64);
+            if date < first.max(start) || date > last {
+                row.push(' ');
+                continue;
+            }
+            let count = days.get(&date).map_or(0, |day| day.total_commits);
+            let level = if count == 0 {
+                0
+            } else {
+                1 + (count - 1) * (HEATMAP_GLYPHS.len() - 1) / max
+            };
+            let heavy = days.get(&date).is_some_and(|day| after_hours_heavy(day));
+            match (heavy, color) {
+                (true, true) => {
+                    row.push_str(&paint(&HEATMAP_GLYPHS[level].to_string(), RED, true));
+                }
+                (true, false) => row.push(HEATMAP_HEAVY_GLYPHS[level]),
+                (false, _) => row.push(HEATMAP_GLYPHS[level]),
+            }
         }
+        writeln!(out, "  {label} {row}")?;
+    }
+
+    let older: Vec<&DailyStats> = daily.iter().filter(|day| day.date < start).collect();
+    if monday(first) < earliest {
+        writeln!(
+            out,
+            "{}",
+            lang.format(
+                Msg::CalendarOlderWeeks,
+                &[
+                    &((start - monday(first)).num_days() / 7),
+                    &older.iter().map(|day| day.total_commits).sum::<usize>(),
+                    &older.len(),
+                    &older.iter().filter(|day| after_hours_heavy(day)).count(),
+                ]
+            )
+        )?;
+    }
+    let heavy_note = if color {
+        lang.text(Msg::CalendarLegendColor).to_string()
+    } else {
+        lang.format(
+            Msg::CalendarLegendShape,
+            &[&HEATMAP_HEAVY_GLYPHS[1..].iter().collect::<String>()],
+        )
+    };
+    writeln!(
+        out,
+        "{}",
+        lang.format(Msg::CalendarLegend, &[&max, &heavy_note])
+    )?;
+    Ok(())
+}
+
+const PUNCH_CARD_GLYPHS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// One row per weekday, one cell per hour, shaded against the busiest cell.
+/// Hours that are at least partly outside the work schedule get a `^` below.
+fn write_punch_card(
+    punch_card: &[[usize; 24]; 7],
+    schedule: &WorkSchedule,
+    lang: Lang,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let max = punch_card
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0);
+    let hours: String = (0..24).step_by(3).map(|hour| format!("{hour:<6}")).collect();
+    writeln!(out, "        {}", hours.trim_end())?;
+    for (weekday, row) in punch_card.iter().enumerate() {
+        let cells: String = row
+            .iter()
+            .map(|&count| {
+                // 1..=4 for any non-zero count; only the busiest cells get █.
+                let level = if count == 0 {
+                    0
+                } else {
+                    1 + (count - 1) * (PUNCH_CARD_GLYPHS.len() - 1) / max
+                };
+                PUNCH_CARD_GLYPHS[level].to_string().repeat(2)
+            })
+            .collect();
+        // Four columns: 周一 or Mon plus a space.
+        writeln!(out, "  {} |{cells}|", pad_to(lang.weekday(weekday), 4))?;
+    }
+    let (start, end) = (schedule.start_second(), schedule.end_second());
+    let marks: String = (0..24u32)
+        .map(|hour| {
+            if hour * 3600 < start || (hour + 1) * 3600 > end {
+                "^^"
+            } else {
+                "  "
+            }
+        })
+        .collect();
+    writeln!(out, "        {marks}")?;
+    writeln!(
+        out,
+        "{}",
+        lang.format(
+            Msg::PunchCardLegend,
+            &[&PUNCH_CARD_GLYPHS[1..].iter().collect::<String>(), &max]
+        )
+    )?;
+    Ok(())
+}
+
+/// Pure `(band, seed) -> index` selection so a fixed `--seed` always yields
+/// the same line.
+pub fn quip_index(band: usize, seed: u64) -> usize {
+    let count = quip_count(band) as u64;
+    (splitmix64(seed ^ band as u64) % count) as usize
+}
+
+pub fn pick_quip(score: f64, seed: u64, lang: Lang) -> String {
+    let band = severity_band(score).index;
+    lang.quip(band, quip_index(band, seed)).to_string()
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn format_policy_value(metric: &str, value: f64) -> String {
+    if metric == "severity" {
+        format!("{value:.1}")
+    } else {
+        format!("{:.1}%", value * 100.0)
     }
 }
 
 fn format_timestamp(value: &DateTime<FixedOffset>) -> String {
     value.format("%Y-%m-%d %H:%M").to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::{NaiveTime, Utc};
+
+    use super::*;
+    use crate::gitlog::Commit;
+    use crate::metrics::{compute_metrics, DefaultClassifier, MetricsOptions};
+
+    fn day(date: &str, total_commits: usize, after_hours_commits: usize) -> DailyStats {
+        DailyStats {
+            date: date.parse().unwrap(),
+            total_commits,
+            after_hours_commits,
+            after_hours_minutes: 0,
+            first_commit: NaiveTime::MIN,
+            last_commit: NaiveTime::MIN,
+            span_hours: 0.0,
+        }
+    }
+
+    fn heatmap(daily: &[DailyStats], first: &str, last: &str, max_weeks: i64) -> String {
+        let mut out = Vec::new();
+        let range = (first.parse().unwrap(), last.parse().unwrap());
+        write_heatmap(daily, range, max_weeks, false, Lang::En, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn calendar_aligns_a_window_starting_mid_week() {
+        let daily = [day("2026-09-02", 1, 0), day("2026-09-14", 3, 2)];
+        let rendered = heatmap(&daily, "2026-09-02", "2026-09-15", AUTHOR_CALENDAR_MAX_WEEKS);
+        let expected = [
+            "     Sep",
+            "  Mo  ·◕",
+            "  Tu  ··",
+            "  We ░· ",
+            "  Th ·· ",
+            "  Fr ·· ",
+            "  Sa ·· ",
+            "  Su ·· ",
+        ];
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[..expected.len()], expected);
+        assert!(lines[expected.len()].contains("◔◑◕● marks days"));
+    }
+
+    #[test]
+    fn calendar_summarizes_weeks_past_the_cap() {
+        // 2026-01-05 is a Monday; the last drawn week starts 2026-07-27.
+        let daily = [
+            day("2026-01-06", 2, 2),
+            day("2026-01-20", 1, 0),
+            day("2026-07-28", 4, 0),
+        ];
+        let rendered = heatmap(&daily, "2026-01-05", "2026-07-28", AUTHOR_CALENDAR_MAX_WEEKS);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert!(lines[1..8].iter().all(|line| line.chars().count() == 5 + 26));
+        assert_eq!(
+            lines[8],
+            "  4 older weeks not drawn: 3 commits on 2 days, 1 of them mostly after hours"
+        );
+    }
+
+    #[test]
+    fn quip_selection_is_a_pure_function_of_band_and_seed() {
+        for band in 0..5 {
+            let picks: Vec<usize> = (0..64).map(|seed| quip_index(band, seed)).collect();
+            assert!(picks.iter().all(|index| *index < quip_count(band)));
+            assert_eq!(picks, (0..64).map(|seed| quip_index(band, seed)).collect::<Vec<_>>());
+            // Every line of the band is reachable.
+            for index in 0..quip_count(band) {
+                assert!(picks.contains(&index), "band {band} never picks line {index}");
+            }
+        }
+        assert_eq!(pick_quip(90.0, 7, Lang::Zh), pick_quip(90.0, 7, Lang::Zh));
+        // The band comes from the score, and the slot is the same in either language.
+        let slot = quip_index(4, 7);
+        assert_eq!(pick_quip(90.0, 7, Lang::Zh), Lang::Zh.quip(4, slot));
+        assert_eq!(pick_quip(81.0, 7, Lang::En), Lang::En.quip(4, slot));
+        assert_eq!(pick_quip(80.9, 7, Lang::En), Lang::En.quip(3, quip_index(3, 7)));
+    }
+
+    /// A week of commits by an author whose name needs escaping, and two
+    /// quieter ones.
+    fn markdown_metrics() -> RepoMetrics {
+        let commits: Vec<Commit> = [
+            ("Zhang | `zs`", "2026-09-01T10:00:00+08:00"),
+            ("Zhang | `zs`", "2026-09-01T21:30:00+08:00"),
+            ("Zhang | `zs`", "2026-09-02T23:40:00+08:00"),
+            ("Zhang | `zs`", "2026-09-05T15:00:00+08:00"),
+            ("Li Si", "2026-09-02T11:00:00+08:00"),
+            ("Li Si", "2026-09-03T19:30:00+08:00"),
+            ("Wang Wu", "2026-09-04T14:00:00+08:00"),
+        ]
+        .into_iter()
+        .map(|(author, timestamp)| Commit {
+            hash: format!("{author}-{timestamp}"),
+            author: author.to_string(),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+            ..Commit::default()
+        })
+        .collect();
+        let options = MetricsOptions {
+            min_commits: 2,
+            now: DateTime::parse_from_rfc3339("2026-09-08T00:00:00Z").unwrap().with_timezone(&Utc),
+            ..MetricsOptions::default()
+        };
+        let mut metrics = compute_metrics(
+            Path::new("demo"),
+            &commits,
+            Vec::new(),
+            Vec::new(),
+            &options,
+            &DefaultClassifier::default(),
+        );
+        metrics.window_description = "2026-09-01 ~ 2026-09-07".to_string();
+        metrics
+    }
+
+    #[test]
+    fn markdown_report_matches_the_golden_files() {
+        let metrics = markdown_metrics();
+        assert_eq!(render_markdown(&metrics, Lang::Zh), include_str!("report/golden/report.zh.md"));
+        assert_eq!(render_markdown(&metrics, Lang::En), include_str!("report/golden/report.en.md"));
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_backticks_and_backslashes() {
+        assert_eq!(markdown_escape("Zhang | `zs`"), "Zhang \\| \\`zs\\`");
+        assert_eq!(markdown_escape("dom\\user"), "dom\\\\user");
+        assert_eq!(markdown_escape("张三"), "张三");
+    }
+
+    fn scored(score: f64) -> RepoMetrics {
+        let mut metrics = compute_metrics(
+            Path::new("."),
+            &[],
+            Vec::new(),
+            Vec::new(),
+            &MetricsOptions::default(),
+            &DefaultClassifier::default(),
+        );
+        metrics.severity_score = score;
+        metrics
+    }
+
+    #[test]
+    fn badge_colors_follow_the_severity_bands() {
+        let cases = [
+            (0.0, "green"),
+            (20.9, "green"),
+            (21.0, "green"),
+            (40.9, "green"),
+            (41.0, "yellow"),
+            (60.9, "yellow"),
+            (61.0, "orange"),
+            (80.9, "orange"),
+            (81.0, "red"),
+            (100.0, "red"),
+        ];
+        for (score, color) in cases {
+            assert_eq!(badge(&scored(score), Lang::Zh).color, color, "{score}");
+        }
+    }
+
+    #[test]
+    fn badge_message_rounds_to_one_decimal() {
+        let cases = [(73.44, "73.4"), (73.46, "73.5"), (0.0, "0.0"), (99.96, "100.0"), (40.96, "41.0")];
+        for (score, message) in cases {
+            assert_eq!(badge(&scored(score), Lang::Zh).message, message, "{score}");
+        }
+        // The color comes from the unrounded score.
+        assert_eq!(badge(&scored(40.96), Lang::Zh).color, "green");
+    }
+
+    #[test]
+    fn badge_serializes_to_the_shields_endpoint_schema() {
+        assert_eq!(
+            serde_json::to_string(&badge(&scored(73.4), Lang::Zh)).unwrap(),
+            r#"{"schemaVersion":1,"label":"牛马指数","message":"73.4","color":"orange"}"#
+        );
+        assert_eq!(badge(&scored(73.4), Lang::En).label, "workhorse index");
+    }
+
+    #[test]
+    fn the_attribution_others_bucket_follows_lang() {
+        let mut metrics = scored(50.0);
+        metrics.attribution = vec![
+            AttributionEntry {
+                author: "Zhang San".to_string(),
+                after_hours_share: 0.9,
+                weekend_share: 1.0,
+                night_share: 1.0,
+            },
+            AttributionEntry {
+                author: ATTRIBUTION_OTHERS.to_string(),
+                after_hours_share: 0.1,
+                weekend_share: 0.0,
+                night_share: 0.0,
+            },
+        ];
+        let legend = |lang| {
+            let mut out = Vec::new();
+            write_attribution(&metrics, lang, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let en = legend(Lang::En);
+        assert!(en.contains("Others") && !en.contains(ATTRIBUTION_OTHERS), "{en}");
+        assert!(en.contains("Zhang San"), "{en}");
+        assert!(legend(Lang::Zh).contains(ATTRIBUTION_OTHERS));
+    }
+}
@@ -0,0 +1,246 @@
+//! The hour histogram and weekday bars, drawn from the punch card once per
+//! [`RenderTarget`]: block glyphs and color for a terminal, plain ASCII in a
+//! fenced block for Markdown, inline SVG for HTML. Escape codes and block
+//! glyphs only ever reach the terminal.
+
+use std::fmt::Write as _;
+
+use chrono::Weekday;
+
+use crate::i18n::{display_width, pad_to, Lang, Msg};
+use crate::metrics::{WeekendDays, WorkSchedule};
+
+use super::{paint, RED};
+
+/// Where a chart ends up; each chart has one rendering per target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// `█` bars, marked rows in red with `color`.
+    Terminal { color: bool },
+    /// `#` bars in a fenced code block.
+    Markdown,
+    /// An inline `<svg>`.
+    Html,
+}
+
+/// Text charts are exactly this wide, whatever the terminal.
+pub const TEXT_WIDTH: usize = 80;
+
+/// One bar: its label, value and whether it is marked (after hours,
+/// weekend).
+struct Bar {
+    label: String,
+    count: usize,
+    marked: bool,
+}
+
+/// Commits per hour of day over all weekdays; hours at least partly outside
+/// `schedule` are marked.
+pub fn hour_histogram(
+    punch_card: &[[usize; 24]; 7],
+    schedule: &WorkSchedule,
+    lang: Lang,
+    target: RenderTarget,
+) -> String {
+    let (start, end) = (schedule.start_second(), schedule.end_second());
+    let bars: Vec<Bar> = (0..24u32)
+        .map(|hour| Bar {
+            label: format!("{hour:02}:00"),
+            count: punch_card.iter().map(|row| row[hour as usize]).sum(),
+            marked: hour * 3600 < start || (hour + 1) * 3600 > end,
+        })
+        .collect();
+    match target {
+        RenderTarget::Html => svg_columns(&bars, lang.text(Msg::HourChart)),
+        _ => text_bars(&bars, Msg::ChartMarksAfterHours, lang, target),
+    }
+}
+
+/// Commits per weekday, Monday first; `weekend` days are marked.
+pub fn weekday_bars(
+    punch_card: &[[usize; 24]; 7],
+    weekend: &WeekendDays,
+    lang: Lang,
+    target: RenderTarget,
+) -> String {
+    let bars: Vec<Bar> = punch_card
+        .iter()
+        .enumerate()
+        .map(|(index, row)| Bar {
+            label: lang.weekday(index).to_string(),
+            count: row.iter().sum(),
+            marked: Weekday::try_from(index as u8).is_ok_and(|day| weekend.contains(day)),
+        })
+        .collect();
+    match target {
+        RenderTarget::Html => svg_rows(&bars, lang.text(Msg::WeekdayChart)),
+        _ => text_bars(&bars, Msg::ChartMarksWeekend, lang, target),
+    }
+}
+
+/// `label* bar count` lines of [`TEXT_WIDTH`] columns, `*` after marked
+/// labels, and a legend for the mark.
+fn text_bars(bars: &[Bar], legend: Msg, lang: Lang, target: RenderTarget) -> String {
+    let max = bars.iter().map(|bar| bar.count).max().unwrap_or(0);
+    let label_width = bars.iter().map(|bar| display_width(&bar.label)).max().unwrap_or(0) + 1;
+    let count_width = max.to_string().len();
+    let bar_width = TEXT_WIDTH - label_width - count_width - 2;
+    let (glyph, color) = match target {
+        RenderTarget::Terminal { color } => ("█", color),
+        _ => ("#", false),
+    };
+
+    let mut text = String::new();
+    if target == RenderTarget::Markdown {
+        text.push_str("```text\n");
+    }
+    for bar in bars {
+        // Rounded, but any commit at all gets a cell.
+        let cells = match bar.count {
+            0 => 0,
+            count => ((count * bar_width + max / 2) / max).max(1),
+        };
+        let label = format!("{}{}", bar.label, if bar.marked { "*" } else { "" });
+        let drawn = glyph.repeat(cells);
+        let drawn = if bar.marked && cells > 0 { paint(&drawn, RED, color) } else { drawn };
+        let _ = writeln!(
+            text,
+            "{} {drawn}{} {:>count_width$}",
+            pad_to(&label, label_width),
+            " ".repeat(bar_width - cells),
+            bar.count
+        );
+    }
+    match target {
+        RenderTarget::Markdown => {
+            text.push_str("```\n\n");
+            text.push_str(&lang.format(legend, &[&"`*`"]));
+        }
+        _ => text.push_str(&lang.format(legend, &[&"*"])),
+    }
+    text.push('\n');
+    text
+}
+
+const SVG_BAR_COLOR: &str = "#7f8c8d";
+const SVG_MARKED_COLOR: &str = "#c0392b";
+const SVG_WIDTH: usize = 480;
+
+fn svg_color(bar: &Bar) -> &'static str {
+    if bar.marked { SVG_MARKED_COLOR } else { SVG_BAR_COLOR }
+}
+
+/// Vertical bars with every third label below.
+fn svg_columns(bars: &[Bar], title: &str) -> String {
+    const PLOT_HEIGHT: f64 = 140.0;
+    let max = bars.iter().map(|bar| bar.count).max().unwrap_or(0).max(1);
+    let step = SVG_WIDTH / bars.len().max(1);
+    let mut svg = format!(
+        "<svg class=\"chart\" viewBox=\"0 0 {SVG_WIDTH} 160\" role=\"img\" aria-label=\"{title}\">\n"
+    );
+    for (index, bar) in bars.iter().enumerate() {
+        let height = bar.count as f64 / max as f64 * PLOT_HEIGHT;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{:.1}\" width=\"{}\" height=\"{height:.1}\" fill=\"{}\"><title>{} {}</title></rect>",
+            index * step + 1,
+            PLOT_HEIGHT - height,
+            step - 2,
+            svg_color(bar),
+            bar.label,
+            bar.count
+        );
+        if index % 3 == 0 {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"156\" text-anchor=\"middle\">{index}</text>",
+                index * step + step / 2
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Horizontal bars, label on the left and count after the bar.
+fn svg_rows(bars: &[Bar], title: &str) -> String {
+    const ROW: usize = 22;
+    const LABEL: usize = 48;
+    const PLOT_WIDTH: f64 = 380.0;
+    let max = bars.iter().map(|bar| bar.count).max().unwrap_or(0).max(1);
+    let mut svg = format!(
+        "<svg class=\"chart\" viewBox=\"0 0 {SVG_WIDTH} {}\" role=\"img\" aria-label=\"{title}\">\n",
+        bars.len() * ROW
+    );
+    for (index, bar) in bars.iter().enumerate() {
+        let width = bar.count as f64 / max as f64 * PLOT_WIDTH;
+        let y = index * ROW;
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{}\">{}</text><rect x=\"{LABEL}\" y=\"{}\" width=\"{width:.1}\" height=\"16\" fill=\"{}\"></rect><text x=\"{:.1}\" y=\"{}\">{}</text>",
+            y + 15,
+            bar.label,
+            y + 3,
+            svg_color(bar),
+            LABEL as f64 + width + 4.0,
+            y + 15,
+            bar.count
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Monday: two at 09:00, one at 21:00; Saturday: one at 14:00.
+    fn punch_card() -> [[usize; 24]; 7] {
+        let mut card = [[0; 24]; 7];
+        card[0][9] = 2;
+        card[0][21] = 1;
+        card[5][14] = 1;
+        card
+    }
+
+    fn hours(target: RenderTarget) -> String {
+        hour_histogram(&punch_card(), &WorkSchedule::default(), Lang::En, target)
+    }
+
+    fn weekdays(target: RenderTarget) -> String {
+        weekday_bars(&punch_card(), &WeekendDays::default(), Lang::Zh, target)
+    }
+
+    #[test]
+    fn hour_histogram_per_target() {
+        assert_eq!(
+            hours(RenderTarget::Terminal { color: true }),
+            include_str!("golden/hours.terminal.txt")
+        );
+        assert_eq!(hours(RenderTarget::Markdown), include_str!("golden/hours.md"));
+        assert_eq!(hours(RenderTarget::Html), include_str!("golden/hours.svg"));
+    }
+
+    #[test]
+    fn weekday_bars_per_target() {
+        assert_eq!(
+            weekdays(RenderTarget::Terminal { color: true }),
+            include_str!("golden/weekdays.terminal.txt")
+        );
+        assert_eq!(weekdays(RenderTarget::Markdown), include_str!("golden/weekdays.md"));
+        assert_eq!(weekdays(RenderTarget::Html), include_str!("golden/weekdays.svg"));
+    }
+
+    #[test]
+    fn text_targets_stay_within_80_columns_and_only_the_terminal_gets_escapes() {
+        for text in [hours(RenderTarget::Markdown), weekdays(RenderTarget::Markdown)] {
+            assert!(!text.contains('\x1b') && !text.contains('█'));
+            let rows = text.lines().skip(1).take_while(|line| !line.starts_with("```"));
+            assert!(rows.into_iter().all(|line| display_width(line) == TEXT_WIDTH));
+        }
+        assert!(hours(RenderTarget::Terminal { color: true }).contains("\x1b[31m"));
+        assert!(!hours(RenderTarget::Terminal { color: false }).contains('\x1b'));
+        assert!(!hours(RenderTarget::Html).contains('\x1b'));
+    }
+}
@@ -0,0 +1,265 @@
+//! Self-contained HTML report: inline CSS, no scripts, no external requests.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+
+use crate::i18n::{Lang, Msg};
+use crate::metrics::{AuthorSummary, DailyStats, RepoMetrics, percentage};
+
+use super::charts::{hour_histogram, weekday_bars, RenderTarget};
+use super::{band_text, low_confidence_reason};
+
+const STYLE: &str = "\
+body{font-family:-apple-system,'PingFang SC','Microsoft YaHei',sans-serif;margin:2rem auto;max-width:960px;color:#222;padding:0 1rem}
+h1{font-size:1.5rem}h2{font-size:1.15rem;margin-top:2rem}
+.meta{color:#666;font-size:.9rem}
+.cards{display:flex;flex-wrap:wrap;gap:.75rem}
+.card{border:1px solid #ddd;border-radius:8px;padding:.75rem 1rem;min-width:150px}
+.card .value{font-size:1.6rem;font-weight:600}
+.card .label{color:#666;font-size:.85rem}
+.score{border-color:#c0392b}
+table{border-collapse:collapse;width:100%}
+th,td{border-bottom:1px solid #eee;padding:.35rem .5rem;text-align:right}
+th:first-child,td:first-child{text-align:left}
+.chart{width:100%;height:auto;font-size:11px}
+.chart text{fill:#666}
+.heatmap{display:grid;grid-template-rows:repeat(7,12px);grid-auto-flow:column;grid-auto-columns:12px;gap:2px}
+.heatmap span{border-radius:2px;background:#ebedf0}
+.heatmap .l1{background:#c6e48b}.heatmap .l2{background:#7bc96f}.heatmap .l3{background:#239a3b}.heatmap .l4{background:#196127}
+.heatmap .heavy{outline:2px solid #c0392b;outline-offset:-2px}
+.heatmap .out{background:transparent}
+footer{margin-top:3rem;color:#888;font-size:.8rem;border-top:1px solid #eee;padding-top:.5rem}
+";
+
+/// Days with at least this share of after-hours commits get a red outline.
+const HEAVY_RATIO: f64 = 0.5;
+const MAX_WEEKS: i64 = 53;
+
+/// Renders the whole page in `lang`. `args` are the command-line arguments
+/// echoed in the footer.
+pub fn render_html(
+    metrics: &RepoMetrics,
+    args: &[String],
+    generated_at: DateTime<Local>,
+    lang: Lang,
+) -> String {
+    let mut html = String::new();
+    let title = lang.format(Msg::HtmlTitle, &[&escape(&metrics.repo_path.display().to_string())]);
+    html.push_str(&format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n",
+        lang.text(Msg::HtmlLang)
+    ));
+    html.push_str("<p class=\"meta\">");
+    html.push_str(&lang.format(Msg::HtmlWindow, &[&escape(&metrics.window_description)]));
+    if let (Some(start), Some(end)) = (&metrics.analysis_start, &metrics.analysis_end) {
+        html.push_str(&lang.format(
+            Msg::HtmlRange,
+            &[&start.format("%Y-%m-%d %H:%M"), &end.format("%Y-%m-%d %H:%M")],
+        ));
+    }
+    html.push_str("</p>\n");
+    if metrics.low_confidence {
+        html.push_str(&format!(
+            "<p class=\"meta\">{}</p>\n",
+            lang.format(Msg::HtmlNote, &[&escape(&low_confidence_reason(metrics, lang))])
+        ));
+    }
+
+    write_cards(&mut html, metrics, lang);
+    write_charts(&mut html, metrics, lang);
+    write_heatmap(&mut html, metrics, lang);
+    write_authors(&mut html, lang, Msg::NightOwls, &metrics.top_after_hours_authors);
+    write_authors(&mut html, lang, Msg::ChillAuthors, &metrics.chill_authors);
+
+    let command = format!("<code>cow-horse {}</code>", escape(&args.join(" ")));
+    html.push_str(&format!(
+        "<footer>{}</footer>\n</body>\n</html>\n",
+        lang.format(Msg::HtmlFooter, &[&generated_at.format("%Y-%m-%d %H:%M:%S %:z"), &command])
+    ));
+    html
+}
+
+fn write_cards(html: &mut String, metrics: &RepoMetrics, lang: Lang) {
+    let share = |part: usize| percentage(part, metrics.total_commits) * 100.0;
+    html.push_str("<div class=\"cards\">\n");
+    html.push_str(&format!("<div class=\"card score\"><div class=\"value\">{:.1}</div><div class=\"label\">{}</div></div>\n",
+        metrics.severity_score,
+        lang.format(Msg::HtmlScoreLabel, &[&escape(&band_text(metrics, lang))])
+    ));
+    let cards = [
+        (
+            metrics.total_commits.to_string(),
+            lang.format(Msg::HtmlCommitsCard, &[&metrics.unique_authors]),
+        ),
+        (
+            format!("{:.1}%", share(metrics.after_hours_commits)),
+            lang.text(Msg::AfterHoursCommits).to_string(),
+        ),
+        (
+            format!("{:.1}%", share(metrics.weekend_commits)),
+            lang.text(Msg::WeekendCommits).to_string(),
+        ),
+        (
+            format!("{:.1}%", share(metrics.night_commits)),
+            lang.text(Msg::NightCommits).to_string(),
+        ),
+        (
+            format!("{} / {}", metrics.overtime_days, metrics.commit_days),
+            lang.text(Msg::MdOvertimeDays).to_string(),
+        ),
+        (
+            lang.format(Msg::MdDays, &[&metrics.longest_streak_days]),
+            lang.text(Msg::HtmlLongestStreak).to_string(),
+        ),
+    ];
+    for (value, label) in cards {
+        html.push_str(&format!("<div class=\"card\"><div class=\"value\">{value}</div><div class=\"label\">{label}</div></div>\n"
+        ));
+    }
+    html.push_str("</div>\n");
+}
+
+/// Commits per hour of day and per weekday as inline SVG bar charts.
+fn write_charts(html: &mut String, metrics: &RepoMetrics, lang: Lang) {
+    html.push_str(&format!("<h2>{}</h2>\n", lang.text(Msg::HourChart)));
+    html.push_str(&hour_histogram(
+        &metrics.punch_card,
+        &metrics.work_schedule,
+        lang,
+        RenderTarget::Html,
+    ));
+    html.push_str(&format!("<h2>{}</h2>\n", lang.text(Msg::WeekdayChart)));
+    html.push_str(&weekday_bars(
+        &metrics.punch_card,
+        &metrics.weekend_days,
+        lang,
+        RenderTarget::Html,
+    ));
+}
+
+/// One column per week, Monday on top, from the first to the last commit day.
+fn write_heatmap(html: &mut String, metrics: &RepoMetrics, lang: Lang) {
+    let (Some(first), Some(last)) = (metrics.daily.first(), metrics.daily.last()) else {
+        return;
+    };
+    let monday =
+        |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_monday().into());
+    let start = monday(first.date).max(monday(last.date) - Duration::weeks(MAX_WEEKS - 1));
+    let end = monday(last.date) + Duration::days(6);
+    let days: HashMap<NaiveDate, &DailyStats> =
+        metrics.daily.iter().map(|day| (day.date, day)).collect();
+    let max = metrics
+        .daily
+        .iter()
+        .map(|day| day.total_commits)
+        .max()
+        .unwrap_or(1);
+
+    html.push_str(&format!("<h2>{}</h2>\n<div class=\"heatmap\">\n", lang.text(Msg::HtmlCalendar)));
+    for date in start.iter_days().take_while(|date| *date <= end) {
+        if date < first.date || date > last.date {
+            html.push_str("<span class=\"out\"></span>\n");
+            continue;
+        }
+        let (count, after_hours) = days
+            .get(&date)
+            .map_or((0, 0), |day| (day.total_commits, day.after_hours_commits));
+        let class = if count == 0 {
+            String::new()
+        } else {
+            let level = 1 + (count - 1) * 4 / max;
+            let heavy = percentage(after_hours, count) >= HEAVY_RATIO;
+            format!("l{level}{}", if heavy { " heavy" } else { "" })
+        };
+        html.push_str(&format!(
+            "<span class=\"{class}\" title=\"{}\"></span>\n",
+            lang.format(Msg::HtmlDayTitle, &[&date, &count])
+        ));
+    }
+    html.push_str(&format!(
+        "</div>\n<p class=\"meta\">{}</p>\n",
+        lang.text(Msg::HtmlCalendarLegend)
+    ));
+}
+
+fn write_authors(html: &mut String, lang: Lang, title: Msg, authors: &[AuthorSummary]) {
+    if authors.is_empty() {
+        return;
+    }
+    html.push_str(&format!(
+        "<h2>{}</h2>\n<table>\n{}\n",
+        lang.text(title),
+        lang.text(Msg::HtmlAuthorHeader)
+    ));
+    for author in authors {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{:.1}%</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&author.name),
+            author.total_commits,
+            author.after_hours_ratio * 100.0,
+            author.weekend_commits,
+            author.night_commits
+        ));
+    }
+    html.push_str("</table>\n");
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::gitlog::Commit;
+    use crate::metrics::{compute_metrics, DefaultClassifier, MetricsOptions};
+
+    fn page(lang: Lang) -> String {
+        let commits: Vec<Commit> = ["2026-09-01T10:00:00+08:00", "2026-09-01T22:30:00+08:00"]
+            .into_iter()
+            .map(|timestamp| Commit {
+                hash: timestamp.to_string(),
+                author: "Zhang San".to_string(),
+                timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap(),
+                ..Commit::default()
+            })
+            .collect();
+        let mut metrics = compute_metrics(
+            Path::new("demo"),
+            &commits,
+            Vec::new(),
+            Vec::new(),
+            &MetricsOptions { min_commits: 1, ..MetricsOptions::default() },
+            &DefaultClassifier::default(),
+        );
+        metrics.window_description = "2026-09-01 ~ 2026-09-07".to_string();
+        metrics.low_confidence = true;
+        let generated_at = Local.with_ymd_and_hms(2026, 9, 8, 9, 0, 0).unwrap();
+        render_html(&metrics, &["--format".to_string(), "html".to_string()], generated_at, lang)
+    }
+
+    #[test]
+    fn the_page_follows_lang() {
+        let en = page(Lang::En);
+        assert!(en.contains("<html lang=\"en\">"), "{en}");
+        assert!(en.contains("<h1>Workhorse report: demo</h1>"), "{en}");
+        assert!(en.contains("Night owls") && en.contains("Commit calendar"), "{en}");
+        let cjk: String = en.chars().filter(|c| ('\u{4e00}'..='\u{9fff}').contains(c)).collect();
+        assert!(cjk.is_empty(), "Chinese left in the English page: {cjk}");
+
+        let zh = page(Lang::Zh);
+        assert!(zh.contains("<html lang=\"zh-CN\">"), "{zh}");
+        assert!(zh.contains("<h1>牛马报告：demo</h1>") && zh.contains("夜猫子榜单"), "{zh}");
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Result};
+
+use crate::gitlog::{git_failure, spawn_error};
+
+/// An initialized submodule checkout, `path` relative to the superproject.
+pub struct SubmoduleRepo {
+    pub path: String,
+    pub abs_path: PathBuf,
+}
+
+pub struct SubmoduleScan {
+    pub repos: Vec<SubmoduleRepo>,
+    /// Paths listed in `.gitmodules` that have not been checked out.
+    pub skipped: Vec<String>,
+}
+
+/// Walks `.gitmodules` of `repo_path`, following nested submodules up to
+/// `depth` levels (1 = only direct submodules).
+pub fn scan_submodules(repo_path: &Path, depth: u32) -> Result<SubmoduleScan> {
+    let mut scan = SubmoduleScan {
+        repos: Vec::new(),
+        skipped: Vec::new(),
+    };
+    collect(repo_path, "", depth, &mut scan)?;
+    Ok(scan)
+}
+
+fn collect(repo_path: &Path, prefix: &str, depth: u32, scan: &mut SubmoduleScan) -> Result<()> {
+    if depth == 0 {
+        return Ok(());
+    }
+
+    for rel in declared_paths(repo_path)? {
+        let abs_path = repo_path.join(&rel);
+        let display = format!("{prefix}{rel}");
+        if !abs_path.join(".git").exists() {
+            scan.skipped.push(display);
+            continue;
+        }
+        collect(&abs_path, &format!("{display}/"), depth - 1, scan)?;
+        scan.repos.push(SubmoduleRepo {
+            path: display,
+            abs_path,
+        });
+    }
+    Ok(())
+}
+
+fn declared_paths(repo_path: &Path) -> Result<Vec<String>> {
+    let gitmodules = repo_path.join(".gitmodules");
+    if !gitmodules.exists() {
+        return Ok(Vec::new());
+    }
+
+    let output = Command::new("git")
+        .arg("config")
+        .arg("-f")
+        .arg(&gitmodules)
+        .args(["--get-regexp", r"^submodule\..*\.path$"])
+        .output()
+        .map_err(|err| spawn_error(err, "git config", repo_path))?;
+
+    // Exit status 1 just means no submodule declares a path.
+    if !output.status.success() && output.status.code() != Some(1) {
+        bail!(git_failure("git config -f .gitmodules", repo_path, &output.stderr));
+    }
+
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.split_once(' ').map(|(_, path)| path.trim().to_string()))
+        .filter(|path| !path.is_empty())
+        .collect())
+}
@@ -0,0 +1,70 @@
+//! Scratch git repositories for tests.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// A fresh repository under the system temp dir, deleted on drop.
+pub struct TempRepo {
+    path: PathBuf,
+}
+
+impl TempRepo {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "cowhorse-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        let repo = TempRepo { path };
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// An empty commit by `author` at `date` (RFC 3339, any offset), used
+    /// as both author and committer date.
+    pub fn commit(&self, author: &str, date: &str) {
+        self.commit_with_message(author, date, &format!("commit at {date}"));
+    }
+
+    pub fn commit_with_message(&self, author: &str, date: &str, message: &str) {
+        let email = format!("{}@example.com", author.to_lowercase().replace(' ', "."));
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(["commit", "-q", "--allow-empty", "-m", message])
+            .env("GIT_AUTHOR_NAME", author)
+            .env("GIT_AUTHOR_EMAIL", &email)
+            .env("GIT_COMMITTER_NAME", author)
+            .env("GIT_COMMITTER_EMAIL", &email)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git commit failed in {}", self.path.display());
+    }
+
+    pub fn git(&self, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed in {}", self.path.display());
+    }
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::{anyhow, bail, Result};
 use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
 
+use crate::error::{CodedError, ErrorCode};
+
 pub fn parse_time_filter(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
     if let Some(relative) = try_parse_relative(value, now) {
         return Ok(relative);
@@ -17,10 +19,18 @@ pub fn parse_time_filter(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc
         return Ok(Utc.from_utc_datetime(&naive));
     }
 
-    bail!("Cannot parse time filter {value:?}");
+    bail!(CodedError::new(
+        ErrorCode::BadTimeFilter,
+        format!("Cannot parse time filter {value:?}")
+    ));
 }
 
 fn try_parse_relative(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_duration(value).map(|duration| now - duration)
+}
+
+/// Parses a positive span such as `7d`, `2w` or `12h`.
+pub fn parse_duration(value: &str) -> Option<Duration> {
     let trimmed = value.trim().to_ascii_lowercase();
     if trimmed.len() < 2 {
         return None;
@@ -32,12 +42,10 @@ fn try_parse_relative(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>>
         return None;
     }
 
-    let duration = match suffix.chars().next()? {
-        'd' => Duration::days(num),
-        'w' => Duration::weeks(num),
-        'h' => Duration::hours(num),
-        _ => return None,
-    };
-
-    Some(now - duration)
+    match suffix.chars().next()? {
+        'd' => Some(Duration::days(num)),
+        'w' => Some(Duration::weeks(num)),
+        'h' => Some(Duration::hours(num)),
+        _ => None,
+    }
 }
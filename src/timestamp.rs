@@ -1,5 +1,14 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local, Utc};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(about = "把 Unix 时间戳转换为可读时间", name = "unix")]
+pub struct UnixCli {
+    /// 秒级 Unix 时间戳，可以为负数
+    #[arg(value_name = "TIMESTAMP", allow_negative_numbers = true)]
+    pub timestamp: i64,
+}
 
 pub struct UnixConversion {
     pub timestamp: i64,
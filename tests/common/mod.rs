@@ -0,0 +1,65 @@
+//! Scratch repositories and a runner for the `zzh` binary, shared by the
+//! integration tests.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A scratch directory with a two-commit repository in `repo/` and a plain
+/// directory in `plain/`.
+pub struct Scratch(pub PathBuf);
+
+impl Scratch {
+    pub fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("cowhorse-it-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("repo")).unwrap();
+        std::fs::create_dir_all(root.join("plain")).unwrap();
+        let scratch = Scratch(root);
+        scratch.git(&["init", "-q"]);
+        for date in ["2026-09-01T10:00:00+08:00", "2026-09-01T22:00:00+08:00"] {
+            scratch.git_at(&["commit", "-q", "--allow-empty", "-m", "work"], date);
+        }
+        scratch
+    }
+
+    pub fn repo(&self) -> PathBuf {
+        self.0.join("repo")
+    }
+
+    fn git(&self, args: &[&str]) {
+        self.git_at(args, "2026-09-01T10:00:00+08:00");
+    }
+
+    fn git_at(&self, args: &[&str], date: &str) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(self.repo())
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "A")
+            .env("GIT_AUTHOR_EMAIL", "a@example.com")
+            .env("GIT_COMMITTER_NAME", "A")
+            .env("GIT_COMMITTER_EMAIL", "a@example.com")
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Runs `zzh` on `path` over all history, without config files.
+pub fn zzh(path: &Path, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_zzh"))
+        .arg("--path")
+        .arg(path)
+        .args(["--all-history", "--no-config"])
+        .args(extra)
+        .output()
+        .unwrap()
+}
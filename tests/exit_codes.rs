@@ -0,0 +1,22 @@
+//! Exit codes of the threshold gates, checked against the real binary.
+
+mod common;
+
+use common::{zzh, Scratch};
+
+#[test]
+fn exceeded_thresholds_exit_2_with_the_json_still_on_stdout() {
+    // One commit at 10:00 and one at 22:00: a 50% after-hours ratio.
+    let scratch = Scratch::new("gates");
+    for (flags, expected) in [
+        (["--fail-above", "0"], 2),
+        (["--fail-above", "100"], 0),
+        (["--fail-after-hours-ratio", "40"], 2),
+        (["--fail-after-hours-ratio", "50"], 0),
+    ] {
+        let output = zzh(&scratch.repo(), &[&["--format", "json", "-q"][..], &flags].concat());
+        assert_eq!(output.status.code(), Some(expected), "{flags:?}");
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["total_commits"], 2, "{flags:?}");
+    }
+}
@@ -0,0 +1,50 @@
+//! The frozen `cow-horse: ...` stderr line, checked against the real binary.
+
+mod common;
+
+use std::path::Path;
+
+use common::{zzh, Scratch};
+use regex::Regex;
+
+/// Runs `zzh` on `path` and returns its exit code and `cow-horse:` lines.
+fn status_lines(path: &Path, extra: &[&str]) -> (i32, Vec<String>) {
+    let output = zzh(path, extra);
+    let lines = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.starts_with("cow-horse:"))
+        .map(String::from)
+        .collect();
+    (output.status.code().unwrap(), lines)
+}
+
+#[test]
+fn success_prints_one_analyzed_line() {
+    let scratch = Scratch::new("success");
+    for format in ["human", "json", "markdown"] {
+        let (code, lines) = status_lines(&scratch.repo(), &["--format", format]);
+        assert_eq!(code, 0);
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        let pattern = Regex::new(&format!(
+            r"^cow-horse: analyzed 2 commits in {} \(severity \d+\.\d\) in \d+ms$",
+            regex::escape(&scratch.repo().display().to_string())
+        ))
+        .unwrap();
+        assert!(pattern.is_match(&lines[0]), "{}", lines[0]);
+    }
+}
+
+#[test]
+fn handled_failure_prints_the_error_code() {
+    let scratch = Scratch::new("failure");
+    let (code, lines) = status_lines(&scratch.0.join("plain"), &[]);
+    assert_eq!(code, 1);
+    assert_eq!(lines, ["cow-horse: failed (NOT_A_REPO)"]);
+}
+
+#[test]
+fn quiet_suppresses_the_line() {
+    let scratch = Scratch::new("quiet");
+    assert_eq!(status_lines(&scratch.repo(), &["--quiet"]), (0, Vec::new()));
+    assert_eq!(status_lines(&scratch.0.join("plain"), &["--quiet"]).1, Vec::<String>::new());
+}